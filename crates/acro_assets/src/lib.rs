@@ -19,7 +19,7 @@ pub use serde;
 
 use acro_ecs::{
     systems::NotifyChangeError, Application, ComponentId, EntityId, Plugin, Stage,
-    SystemRunContext, World,
+    SystemRunContext, SystemSchedulingRequirement, World,
 };
 use notify::{event::AccessKind, EventKind, RecursiveMode, Watcher};
 use parking_lot::{Mutex, RwLock};
@@ -317,6 +317,14 @@ impl Assets {
         asset_data.notify_assets.write().insert(notify.to_string());
     }
 
+    /// Stops notifying any of the components on `entity` when an asset is reloaded. Called when
+    /// the entity is despawned.
+    pub fn remove_notify_changes_from_entity(&self, entity: EntityId) {
+        for asset_data in self.data.read().values() {
+            asset_data.notify_components.write().remove(&entity);
+        }
+    }
+
     pub fn register_loader<T: Loadable>(&mut self) {
         self.asset_loaders.insert(
             TypeId::of::<T>(),
@@ -345,7 +353,16 @@ pub struct AssetsPlugin;
 
 impl Plugin for AssetsPlugin {
     fn build(&mut self, app: &mut Application) {
-        app.add_system(Stage::PreUpdate, [], load_queued_assets)
-            .insert_resource(Assets::new());
+        // Loaders get the whole world and can use the renderer
+        app.add_system(
+            Stage::PreUpdate,
+            [SystemSchedulingRequirement::RunOnMainThread],
+            load_queued_assets,
+        )
+        .insert_resource(Assets::new());
+    }
+
+    fn name(&self) -> &'static str {
+        "assets"
     }
 }
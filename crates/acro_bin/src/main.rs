@@ -1,13 +1,12 @@
 use std::any::Any;
 
-use acro_assets::{Assets, AssetsPlugin};
+use acro_assets::Assets;
+use acro_default_plugins::DefaultPlugins;
 use acro_ecs::{Application, Plugin, Query, Res, Stage, SystemRunContext, With};
-use acro_math::{Children, GlobalTransform, MathPlugin, Parent, Root, Transform};
-use acro_physics::PhysicsPlugin;
-use acro_render::{Mesh, RenderPlugin, WindowState};
-use acro_scene::{SceneManager, ScenePlugin};
-use acro_scripting::{Behavior, ScriptingPlugin, SourceFile};
-use acro_ui::UiPlugin;
+use acro_math::{Children, GlobalTransform, Parent, Root, Transform};
+use acro_render::{Mesh, WindowState};
+use acro_scene::SceneManager;
+use acro_scripting::{Behavior, SourceFile};
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt as _, EnvFilter};
 
@@ -29,6 +28,10 @@ impl Plugin for TestPlugin {
 
         world.resource_mut::<SceneManager>().queue("main.scene");
     }
+
+    fn dependencies(&self) -> Vec<&'static str> {
+        vec!["scene"]
+    }
 }
 
 fn main() {
@@ -47,13 +50,7 @@ fn main() {
     }
 
     Application::new()
-        .add_plugin(AssetsPlugin)
-        .add_plugin(ScriptingPlugin)
-        .add_plugin(MathPlugin::default())
-        .add_plugin(ScenePlugin)
-        .add_plugin(RenderPlugin)
-        .add_plugin(PhysicsPlugin)
-        .add_plugin(UiPlugin)
+        .add_plugins(DefaultPlugins)
         .add_plugin(TestPlugin)
         .run();
 }
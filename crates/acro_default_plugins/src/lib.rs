@@ -0,0 +1,25 @@
+use acro_assets::AssetsPlugin;
+use acro_ecs::{PluginGroup, PluginGroupBuilder};
+use acro_math::MathPlugin;
+use acro_physics::PhysicsPlugin;
+use acro_render::RenderPlugin;
+use acro_scene::ScenePlugin;
+use acro_scripting::ScriptingPlugin;
+use acro_ui::UiPlugin;
+
+/// Every engine plugin, shared by the native and web builds. Plugins can be turned off with
+/// `DefaultPlugins.build().disable::<PhysicsPlugin>()`.
+pub struct DefaultPlugins;
+
+impl PluginGroup for DefaultPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::new()
+            .add_plugin(AssetsPlugin)
+            .add_plugin(ScriptingPlugin)
+            .add_plugin(MathPlugin::default())
+            .add_plugin(ScenePlugin)
+            .add_plugin(RenderPlugin)
+            .add_plugin(PhysicsPlugin)
+            .add_plugin(UiPlugin)
+    }
+}
@@ -14,6 +14,41 @@ fn criterion_benchmark(c: &mut Criterion) {
         });
     });
 
+    c.bench_function("insert 1000 bundles of 5", |cx| {
+        let mut world = bundle_world();
+
+        cx.iter(|| {
+            for i in 0..1000 {
+                let entity = black_box(world.spawn_empty());
+                world.insert(entity, i as u32);
+                world.insert(entity, i as u64);
+                world.insert(entity, i as i8);
+                world.insert(entity, i % 2 == 0);
+                world.insert(entity, i as f32);
+            }
+        });
+    });
+
+    c.bench_function("spawn 1000 bundles of 5", |cx| {
+        let mut world = bundle_world();
+
+        cx.iter(|| {
+            for i in 0..1000 {
+                black_box(world.spawn((i as u32, i as u64, i as i8, i % 2 == 0, i as f32)));
+            }
+        });
+    });
+
+    c.bench_function("spawn_batch 1000 bundles of 5", |cx| {
+        let mut world = bundle_world();
+
+        cx.iter(|| {
+            black_box(world.spawn_batch(
+                (0..1000).map(|i| (i as u32, i as u64, i as i8, i % 2 == 0, i as f32)),
+            ));
+        });
+    });
+
     c.bench_function("query 1_000_000", |cx| {
         let mut world = World::new();
         world.init_component::<u32>();
@@ -71,5 +106,15 @@ fn criterion_benchmark(c: &mut Criterion) {
     });
 }
 
+fn bundle_world() -> World {
+    let mut world = World::new();
+    world.init_component::<u32>();
+    world.init_component::<u64>();
+    world.init_component::<i8>();
+    world.init_component::<bool>();
+    world.init_component::<f32>();
+    world
+}
+
 criterion_group!(benches, criterion_benchmark);
 criterion_main!(benches);
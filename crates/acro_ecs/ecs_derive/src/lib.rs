@@ -0,0 +1,140 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Index, LitStr, Path};
+
+/// Implements `Component` and `Bundle` for a type. Metadata is set with the `component`
+/// attribute:
+///
+/// `#[component(storage = "table", script_name = "Mesh", on_add = path, on_insert = path, on_remove = path)]`
+#[proc_macro_derive(Component, attributes(component))]
+pub fn component_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    component_derive_impl(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `Bundle` for a struct. Every field has to be a bundle itself, either a type that
+/// derives `Component` or another bundle.
+#[proc_macro_derive(Bundle)]
+pub fn bundle_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    bundle_derive_impl(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn component_derive_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let mut storage = None;
+    let mut script_name = None;
+    let mut hooks = vec![];
+
+    for attr in input.attrs.iter() {
+        if !attr.path().is_ident("component") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("storage") {
+                let value: LitStr = meta.value()?.parse()?;
+                match value.value().as_str() {
+                    "table" => storage = Some(quote! { acro_ecs::StorageType::Table }),
+                    other => {
+                        return Err(meta.error(format!("unknown storage type `{other}`")));
+                    }
+                }
+            } else if meta.path.is_ident("script_name") {
+                let value: LitStr = meta.value()?.parse()?;
+                script_name = Some(value);
+            } else if ["on_add", "on_insert", "on_remove"]
+                .iter()
+                .any(|hook| meta.path.is_ident(hook))
+            {
+                let hook = meta.path.get_ident().cloned();
+                let path: Path = meta.value()?.parse()?;
+                hooks.push(quote! { .#hook(#path) });
+            } else {
+                return Err(meta.error("unknown component attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let storage = storage.map(|storage| {
+        quote! { const STORAGE: acro_ecs::StorageType = #storage; }
+    });
+    let script_name = script_name.map(|script_name| {
+        quote! { const SCRIPT_NAME: Option<&'static str> = Some(#script_name); }
+    });
+
+    Ok(quote! {
+        impl #impl_generics acro_ecs::Component for #name #ty_generics #where_clause {
+            #storage
+            #script_name
+
+            fn hooks() -> acro_ecs::ComponentHooks {
+                acro_ecs::ComponentHooks::new() #(#hooks)*
+            }
+        }
+
+        impl #impl_generics acro_ecs::Bundle for #name #ty_generics #where_clause {
+            fn component_ids(
+                components: &mut acro_ecs::ComponentRegistry,
+                ids: &mut Vec<acro_ecs::ComponentId>,
+            ) {
+                ids.push(components.register::<Self>());
+            }
+
+            fn component_ptrs(&self, ptrs: &mut Vec<*const u8>) {
+                ptrs.push(self as *const Self as *const u8);
+            }
+        }
+    })
+}
+
+fn bundle_derive_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(struct_data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Bundle can only be derived for structs",
+        ));
+    };
+
+    let field_types = struct_data.fields.iter().map(|field| &field.ty);
+    let field_accessors = struct_data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let index = Index::from(index);
+                quote! { #index }
+            }
+        });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics acro_ecs::Bundle for #name #ty_generics #where_clause {
+            fn component_ids(
+                components: &mut acro_ecs::ComponentRegistry,
+                ids: &mut Vec<acro_ecs::ComponentId>,
+            ) {
+                #(<#field_types as acro_ecs::Bundle>::component_ids(components, ids);)*
+            }
+
+            fn component_ptrs(&self, ptrs: &mut Vec<*const u8>) {
+                #(acro_ecs::Bundle::component_ptrs(&self.#field_accessors, ptrs);)*
+            }
+        }
+    })
+}
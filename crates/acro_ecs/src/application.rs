@@ -8,10 +8,15 @@ use chrono::Utc;
 use tracing::info;
 
 use crate::{
-    plugin::Plugin,
+    component::Component,
+    events::Events,
+    plugin::{Plugin, PluginGroup},
     pointer::change_detection::Tick,
+    registry::ComponentHooks,
     schedule::{Schedule, Stage, SystemSchedulingRequirement},
-    systems::{IntoSystem, SystemData, SystemId, SystemRunContext},
+    state::{NextState, State, StateTransition, States},
+    systems::{IntoSystem, SystemData, SystemId, SystemIndex, SystemRunContext},
+    time::{Clock, FixedTimestep},
     world::World,
     EntityId, Res, ResMut,
 };
@@ -22,6 +27,9 @@ pub struct Application {
     // systems: Vec<SystemData>,
     schedule: Schedule,
     runner: Box<dyn FnOnce(Application)>,
+    built_plugins: Vec<&'static str>,
+    // Plugins waiting for their dependencies to be added
+    pending_plugins: Vec<Box<dyn Plugin>>,
 }
 
 impl Application {
@@ -31,14 +39,83 @@ impl Application {
             current_tick: Tick::new(0),
             schedule: Schedule::new(),
             runner: Box::new(|_app| panic!("no runner set!")),
+            built_plugins: vec![],
+            pending_plugins: vec![],
         }
     }
 
-    pub fn add_plugin(mut self, mut plugin: impl Plugin) -> Self {
-        plugin.build(&mut self);
+    /// Adds a plugin, building it right away if all of its dependencies have been built. Otherwise
+    /// it is built as soon as they are.
+    pub fn add_plugin(mut self, plugin: impl Plugin) -> Self {
+        self.queue_plugin(Box::new(plugin));
+        self.build_ready_plugins();
         self
     }
 
+    pub fn add_plugins(mut self, group: impl PluginGroup) -> Self {
+        for plugin in group.build().into_enabled_plugins() {
+            self.queue_plugin(plugin);
+        }
+        self.build_ready_plugins();
+        self
+    }
+
+    pub fn is_plugin_added(&self, name: &str) -> bool {
+        self.built_plugins.contains(&name)
+            || self
+                .pending_plugins
+                .iter()
+                .any(|plugin| plugin.name() == name)
+    }
+
+    fn queue_plugin(&mut self, plugin: Box<dyn Plugin>) {
+        if self.is_plugin_added(plugin.name()) {
+            panic!("plugin {} was added twice", plugin.name());
+        }
+        self.pending_plugins.push(plugin);
+    }
+
+    fn build_ready_plugins(&mut self) {
+        while let Some(index) = self.pending_plugins.iter().position(|plugin| {
+            plugin
+                .dependencies()
+                .iter()
+                .all(|dependency| self.built_plugins.contains(dependency))
+        }) {
+            let mut plugin = self.pending_plugins.remove(index);
+            plugin.build(self);
+            self.built_plugins.push(plugin.name());
+        }
+    }
+
+    /// Makes sure every plugin has been built, panicking if some are still waiting on
+    /// dependencies that were never added.
+    pub fn finish_plugins(&mut self) {
+        self.build_ready_plugins();
+
+        if self.pending_plugins.is_empty() {
+            return;
+        }
+
+        let waiting = self
+            .pending_plugins
+            .iter()
+            .map(|plugin| {
+                let missing = plugin
+                    .dependencies()
+                    .into_iter()
+                    .filter(|dependency| !self.built_plugins.contains(dependency))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{} (needs {missing})", plugin.name())
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        panic!(
+            "plugins are missing dependencies, they were never added or depend on each other: {waiting}"
+        );
+    }
+
     pub fn world(&self) -> RefMut<World> {
         self.world.borrow_mut()
     }
@@ -48,16 +125,44 @@ impl Application {
         self
     }
 
+    /// Inserts a resource that can only be used from the main thread. Systems using it never run
+    /// in parallel with other systems.
+    pub fn insert_non_send_resource<T: Any>(&mut self, resource: T) -> &mut Self {
+        self.world.borrow_mut().resources.insert_non_send(resource);
+        self
+    }
+
     pub fn with_resource<T: Any>(&mut self, f: impl FnOnce(ResMut<T>) -> ()) -> &mut Self {
         f(self.world.borrow().resources.get_mut::<T>());
         self
     }
 
-    pub fn init_component<T: 'static>(&mut self) -> &mut Self {
+    pub fn init_component<T: Component>(&mut self) -> &mut Self {
         self.world.borrow_mut().init_component::<T>();
         self
     }
 
+    /// Registers a component along with its metadata, see [`World::register_component`].
+    pub fn register_component<T: Component>(&mut self) -> &mut Self {
+        self.world.borrow_mut().register_component::<T>();
+        self
+    }
+
+    pub fn set_component_hooks<T: 'static>(&mut self, hooks: ComponentHooks) -> &mut Self {
+        self.world.borrow_mut().set_component_hooks::<T>(hooks);
+        self
+    }
+
+    /// Registers an `Events<T>` resource along with the system that swaps its buffers every frame.
+    pub fn add_event<T: 'static>(&mut self) -> &mut Self {
+        if self.world.borrow().resources().contains::<Events<T>>() {
+            return self;
+        }
+
+        self.insert_resource(Events::<T>::default());
+        self.add_system(Stage::PreUpdate, [], Events::<T>::update_system)
+    }
+
     pub fn get_world_handle(&self) -> Rc<RefCell<World>> {
         Rc::clone(&self.world)
     }
@@ -72,25 +177,99 @@ impl Application {
         I: IntoSystem<P> + 'static,
         P: 'static,
     {
-        let parameters = I::init(&self.world.borrow_mut());
+        let system = self.create_system(scheduling_requirements, system);
         self.schedule
-            .add_system(
-                stage,
-                SystemData {
-                    id: SystemId::Native(system.type_id()),
-                    name: std::any::type_name_of_val(&system).to_string(),
-                    run: system.into_system(),
-                    last_run_tick: Tick::new(0),
-                    parameters,
-                    scheduling_requirements: scheduling_requirements.into_iter().collect(),
-                },
-            )
+            .add_system(stage, system)
             .expect("add system failed");
 
         self
     }
 
+    /// Adds the state `S`, starting out as `initial`.
+    pub fn add_state<S: State>(&mut self, initial: S) -> &mut Self {
+        self.insert_resource(States::new(initial))
+            .insert_resource(NextState::<S>::default());
+        self.schedule.add_state::<S>();
+        self
+    }
+
+    /// Adds a system that runs when entering or exiting a state, e.g. `OnEnter(GameState::Menu)`.
+    pub fn add_state_system<S, I, P>(
+        &mut self,
+        transition: impl Into<StateTransition<S>>,
+        scheduling_requirements: impl IntoIterator<Item = SystemSchedulingRequirement>,
+        system: I,
+    ) -> &mut Self
+    where
+        S: State,
+        I: IntoSystem<P> + 'static,
+        P: 'static,
+    {
+        let system = self.create_system(scheduling_requirements, system);
+        self.schedule
+            .add_state_system(transition.into(), system)
+            .expect("add state system failed");
+
+        self
+    }
+
+    fn create_system<I, P>(
+        &self,
+        scheduling_requirements: impl IntoIterator<Item = SystemSchedulingRequirement>,
+        system: I,
+    ) -> SystemData
+    where
+        I: IntoSystem<P> + 'static,
+        P: 'static,
+    {
+        let world = self.world.borrow();
+        let parameters = I::init(&world);
+        let access = I::access(&world, parameters.as_ref());
+
+        SystemData {
+            id: SystemId::Native(system.type_id()),
+            index: SystemIndex::next(),
+            name: std::any::type_name_of_val(&system).to_string(),
+            run: system.into_system(),
+            last_run_tick: Tick::new(0),
+            parameters,
+            access,
+            scheduling_requirements: scheduling_requirements.into_iter().collect(),
+        }
+    }
+
+    pub fn set_fixed_timestep(&mut self, timestep: std::time::Duration) -> &mut Self {
+        let mut world = self.world.borrow_mut();
+        if world.resources().contains::<FixedTimestep>() {
+            world.resource_mut::<FixedTimestep>().set_timestep(timestep);
+        } else {
+            world.insert_resource(FixedTimestep::new(timestep));
+        }
+        drop(world);
+
+        self
+    }
+
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) -> &mut Self {
+        self.schedule.set_clock(clock);
+        self
+    }
+
+    pub fn set_frame_rate_limit(&mut self, frames_per_second: f64) -> &mut Self {
+        self.schedule.set_frame_rate_limit(frames_per_second);
+        self
+    }
+
+    pub fn set_parallel_execution(&mut self, parallel: bool) -> &mut Self {
+        self.schedule.set_parallel(parallel);
+        self
+    }
+
     pub fn run_once(&mut self) {
+        if !self.pending_plugins.is_empty() {
+            self.finish_plugins();
+        }
+
         // let start = Utc::now();
         self.schedule.run_once(&self.world);
 
@@ -103,7 +282,9 @@ impl Application {
     }
 
     pub fn run(mut self) {
-        for system in &self.schedule.stages[&Stage::PreUpdate] {
+        self.finish_plugins();
+
+        for system in self.schedule.get_systems(Stage::PreUpdate) {
             info!("{}", system.name);
         }
 
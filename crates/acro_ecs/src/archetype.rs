@@ -1,20 +1,15 @@
-use std::{
-    any::TypeId,
-    cell::{RefCell, UnsafeCell},
-    collections::HashMap,
-    ptr::NonNull,
-    rc::Rc,
-};
+use std::{any::TypeId, cell::UnsafeCell, collections::HashMap, ptr::NonNull, rc::Rc};
 
+use atomic_refcell::AtomicRefCell;
 use fnv::FnvHashMap;
 use tracing::info;
 
 use crate::{
     entity::{Entities, EntityId, EntityMeta},
-    pointer::change_detection::{ChangeDetectionContext, Tick},
+    events::Events,
+    pointer::change_detection::{ChangeDetectionContext, ComponentTicks, Tick},
     registry::{ComponentGroup, ComponentId, ComponentRegistry},
     storage::{anyvec::AnyVec, table::Table},
-    ComponentType,
 };
 
 #[derive(Debug)]
@@ -22,10 +17,14 @@ pub struct Archetypes {
     pub(crate) generation: usize,
     current_id: usize,
     /// The generation is used to know if query archetype ids needs to recomputed
-    archetypes: FnvHashMap<ArchetypeId, RefCell<Archetype>>,
+    archetypes: FnvHashMap<ArchetypeId, AtomicRefCell<Archetype>>,
     components: FnvHashMap<ComponentGroup, ArchetypeId>,
     // Maps from an old archetype to a set of new archetypes based on components added or removed
     pub(crate) edges: Edges,
+    // Entities that had a component removed (or were despawned), read by `RemovedComponents`
+    pub(crate) removed_components: FnvHashMap<ComponentId, Events<EntityId>>,
+    // Maps from an archetype and a bundle type to the archetype with the bundle's components added
+    bundle_edges: FnvHashMap<(ArchetypeId, TypeId), ArchetypeId>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -48,21 +47,23 @@ impl Archetypes {
             archetypes,
             components,
             edges: Edges::new(),
+            removed_components: FnvHashMap::default(),
+            bundle_edges: FnvHashMap::default(),
         }
     }
 
     fn init_defaults(
-        archetypes: &mut FnvHashMap<ArchetypeId, RefCell<Archetype>>,
+        archetypes: &mut FnvHashMap<ArchetypeId, AtomicRefCell<Archetype>>,
         components: &mut FnvHashMap<ComponentGroup, ArchetypeId>,
     ) {
         // Create the archetype with no components
         archetypes.insert(
             ArchetypeId(0),
-            RefCell::new(Archetype::new(ComponentGroup::new(vec![]), ArchetypeId(0))),
+            AtomicRefCell::new(Archetype::new(ComponentGroup::new(vec![]), ArchetypeId(0))),
         );
         archetypes.insert(
             ArchetypeId::INVALID,
-            RefCell::new(Archetype::new(
+            AtomicRefCell::new(Archetype::new(
                 ComponentGroup::new(vec![]),
                 ArchetypeId::INVALID,
             )),
@@ -78,7 +79,7 @@ impl Archetypes {
         self.current_id += 1;
 
         let archetype = Archetype::new(components.clone(), id);
-        self.archetypes.insert(id, RefCell::new(archetype));
+        self.archetypes.insert(id, AtomicRefCell::new(archetype));
         self.components.insert(components, id);
 
         id
@@ -114,14 +115,52 @@ impl Archetypes {
         }
     }
 
+    /// Returns the archetype an entity in `current_archetype` moves to when the bundle `bundle`,
+    /// made of `components`, is inserted.
+    pub fn get_or_create_bundle_archetype(
+        &mut self,
+        current_archetype: ArchetypeId,
+        bundle: TypeId,
+        components: &[ComponentId],
+        component_registry: &ComponentRegistry,
+    ) -> ArchetypeId {
+        if let Some(&id) = self.bundle_edges.get(&(current_archetype, bundle)) {
+            return id;
+        }
+
+        let new_archetype_components = {
+            let old_components = &self.archetypes[&current_archetype].borrow().components;
+            ComponentGroup::new(
+                old_components
+                    .iter()
+                    .cloned()
+                    .chain(
+                        components
+                            .iter()
+                            .filter(|&&id| !old_components.contains(id))
+                            .map(|&id| component_registry.get_info(id).clone()),
+                    )
+                    .collect(),
+            )
+        };
+
+        let id = match self.components.get(&new_archetype_components).cloned() {
+            Some(id) => id,
+            None => self.new_archetype(new_archetype_components),
+        };
+        self.bundle_edges.insert((current_archetype, bundle), id);
+
+        id
+    }
+
     #[inline]
     fn move_entity(
         &self,
         id: EntityId,
-        from: &RefCell<Archetype>,
-        to: &RefCell<Archetype>,
+        from: &AtomicRefCell<Archetype>,
+        to: &AtomicRefCell<Archetype>,
         entities: &mut Entities,
-        new_component_data: impl Iterator<Item = (ComponentId, *const u8)>,
+        new_component_data: impl Iterator<Item = (ComponentId, *const u8, ComponentTicks)>,
     ) {
         let current_meta = entities.get_mut(id).expect("entity not found");
 
@@ -147,6 +186,8 @@ impl Archetypes {
         entities.get_mut(id).unwrap().table_index = to.borrow().entities.len() - 1;
     }
 
+    /// Adds a component to an entity, replacing its value if the entity already has it. Returns
+    /// true if the component was not there before.
     pub fn add_component<T: 'static>(
         &mut self,
         component_registry: &ComponentRegistry,
@@ -154,8 +195,61 @@ impl Archetypes {
         entity: EntityId,
         new_component: ComponentId,
         data: T,
-    ) {
+        change_tick: Tick,
+    ) -> bool {
+        let added = unsafe {
+            self.add_component_untyped(
+                component_registry,
+                entities,
+                entity,
+                new_component,
+                &data as *const T as *const u8,
+                change_tick,
+            )
+        };
+
+        std::mem::forget(data);
+        added
+    }
+
+    /// Adds a component from a pointer to its data, which is copied into the archetype table.
+    ///
+    /// # Safety
+    /// `data` must point to a valid value of the component's layout. The archetype takes ownership
+    /// of it, so the caller must not drop it.
+    pub unsafe fn add_component_untyped(
+        &mut self,
+        component_registry: &ComponentRegistry,
+        entities: &mut Entities,
+        entity: EntityId,
+        new_component: ComponentId,
+        data: *const u8,
+        change_tick: Tick,
+    ) -> bool {
         let meta = entities.get_mut(entity).expect("entity not found");
+
+        {
+            let archetype = self.archetypes[&meta.archetype_id].borrow();
+            if archetype.components.contains(new_component) {
+                // The entity already has the component, drop the old value and overwrite it
+                let old_data = archetype
+                    .pointer_to_entity_component(meta.table_index, new_component, Some(change_tick))
+                    .expect("component data not found");
+                let component_type = &component_registry.get_info(new_component).component_type;
+                if let Some(dropper) = component_type.dropper() {
+                    unsafe { dropper(old_data) };
+                }
+                unsafe {
+                    std::ptr::copy_nonoverlapping(
+                        data,
+                        old_data.as_ptr(),
+                        component_type.layout().size(),
+                    )
+                };
+
+                return false;
+            }
+        }
         let new_archetype_id = self.get_or_create_archetype(
             meta.archetype_id,
             new_component,
@@ -171,10 +265,71 @@ impl Archetypes {
             old_archetype,
             new_archetype,
             entities,
-            [(new_component, &data as *const T as *const u8)].into_iter(),
+            [(new_component, data, ComponentTicks::new(change_tick))].into_iter(),
         );
 
-        std::mem::forget(data);
+        true
+    }
+
+    /// Adds several components at once, moving the entity to `new_archetype_id` in one step.
+    ///
+    /// # Safety
+    /// `new_archetype_id` must be the entity's archetype with exactly `components` added, none of
+    /// which the entity already has. Each pointer must point to a valid value of its component,
+    /// which the archetype takes ownership of.
+    pub unsafe fn add_components_untyped(
+        &mut self,
+        entities: &mut Entities,
+        entity: EntityId,
+        new_archetype_id: ArchetypeId,
+        components: impl Iterator<Item = (ComponentId, *const u8)>,
+        change_tick: Tick,
+    ) {
+        let meta = entities.get(entity).expect("entity not found");
+        let new_archetype = &self.archetypes[&new_archetype_id];
+        let old_archetype = &self.archetypes[&meta.archetype_id];
+
+        self.move_entity(
+            entity,
+            old_archetype,
+            new_archetype,
+            entities,
+            components.map(|(id, data)| (id, data, ComponentTicks::new(change_tick))),
+        );
+    }
+
+    /// Spawns an entity directly into an archetype.
+    ///
+    /// # Safety
+    /// `components` must contain a valid value for every component of the archetype, which the
+    /// archetype takes ownership of.
+    pub unsafe fn push_entity(
+        &mut self,
+        entities: &mut Entities,
+        archetype_id: ArchetypeId,
+        components: impl Iterator<Item = (ComponentId, *const u8)>,
+        change_tick: Tick,
+    ) -> EntityId {
+        self.flush_reserved(entities);
+
+        let mut archetype = self.archetypes[&archetype_id].borrow_mut();
+        let table_index = archetype.entities.len();
+        let entity = entities.spawn_in(archetype_id, table_index);
+        archetype.entities.push(entity);
+        unsafe {
+            archetype
+                .table
+                .push_row(components.map(|(id, data)| (id, data, ComponentTicks::new(change_tick))))
+        };
+
+        entity
+    }
+
+    /// Reserves space for `additional` more entities in an archetype.
+    pub fn reserve(&mut self, archetype_id: ArchetypeId, additional: usize) {
+        let mut archetype = self.archetypes[&archetype_id].borrow_mut();
+        archetype.entities.reserve(additional);
+        archetype.table.reserve(additional);
     }
 
     pub fn get_component<T: 'static>(
@@ -214,6 +369,56 @@ impl Archetypes {
         entity: EntityId,
         remove_component: ComponentId,
     ) -> T {
+        let mut removed = std::mem::MaybeUninit::<T>::uninit();
+        self.remove_component_with(
+            component_registry,
+            entities,
+            entity,
+            remove_component,
+            |data| {
+                unsafe { removed.write((data.as_ptr() as *const T).read()) };
+            },
+        );
+
+        unsafe { removed.assume_init() }
+    }
+
+    /// Removes a component and drops its data.
+    pub fn remove_component_and_drop(
+        &mut self,
+        component_registry: &ComponentRegistry,
+        entities: &mut Entities,
+        entity: EntityId,
+        remove_component: ComponentId,
+    ) {
+        let dropper = component_registry
+            .get_info(remove_component)
+            .component_type
+            .dropper();
+        self.remove_component_with(
+            component_registry,
+            entities,
+            entity,
+            remove_component,
+            |data| {
+                if let Some(dropper) = dropper {
+                    unsafe { dropper(data) };
+                }
+            },
+        );
+    }
+
+    /// Moves the entity to the archetype without the component. `take` is given the removed
+    /// component's data before it is overwritten and is responsible for moving it out or dropping
+    /// it.
+    fn remove_component_with(
+        &mut self,
+        component_registry: &ComponentRegistry,
+        entities: &mut Entities,
+        entity: EntityId,
+        remove_component: ComponentId,
+        take: impl FnOnce(NonNull<u8>),
+    ) {
         let meta = entities.get_mut(entity).expect("entity not found");
         let new_archetype_id = self.get_or_create_archetype(
             meta.archetype_id,
@@ -228,8 +433,9 @@ impl Archetypes {
         let removed_component_data = old_archetype
             .borrow_mut()
             .pointer_to_entity_component(meta.table_index, remove_component, None)
-            .expect("component data not found")
-            .as_ptr() as *const T;
+            .expect("component data not found");
+        // Moving the entity swap removes its row, so the data has to be taken out first
+        take(removed_component_data);
 
         self.move_entity(
             entity,
@@ -239,10 +445,62 @@ impl Archetypes {
             std::iter::empty(),
         );
 
-        unsafe { removed_component_data.read() }
+        self.removed_components
+            .entry(remove_component)
+            .or_default()
+            .send(entity);
+    }
+
+    /// Removes an entity from its archetype, dropping its components and freeing its id. Returns
+    /// false if the entity does not exist.
+    pub fn despawn(&mut self, entities: &mut Entities, entity: EntityId) -> bool {
+        self.flush_reserved(entities);
+
+        let meta = match entities.get(entity) {
+            Some(meta) => *meta,
+            None => return false,
+        };
+
+        let mut archetype = self.archetypes[&meta.archetype_id].borrow_mut();
+        for info in archetype.components.iter() {
+            self.removed_components
+                .entry(info.id)
+                .or_default()
+                .send(entity);
+        }
+        let moved_last_entity = archetype.remove_and_drop(&meta);
+        drop(archetype);
+
+        if let Some(moved_entity) = moved_last_entity {
+            entities
+                .get_mut(moved_entity)
+                .expect("entity not found")
+                .table_index = meta.table_index;
+        }
+
+        entities.free(entity);
+
+        true
+    }
+
+    /// Places all entities reserved through `Entities::reserve` into the empty archetype.
+    pub fn flush_reserved(&mut self, entities: &mut Entities) {
+        if !entities.has_reserved() {
+            return;
+        }
+
+        let mut empty_archetype = self.archetypes[&ArchetypeId::EMPTY].borrow_mut();
+        entities.flush_reserved(|entity| {
+            let table_index = empty_archetype.entities.len();
+            empty_archetype.entities.push(entity);
+            unsafe { empty_archetype.table.push_row([].into_iter()) };
+            table_index
+        });
     }
 
     pub fn push_empty_entity(&mut self, entities: &mut Entities) -> EntityId {
+        self.flush_reserved(entities);
+
         let mut empty_archetype = self.archetypes[&ArchetypeId::EMPTY].borrow_mut();
         let new_entity_table_index = empty_archetype.entities.len();
         let entity = entities.spawn(new_entity_table_index);
@@ -251,10 +509,21 @@ impl Archetypes {
         entity
     }
 
-    pub fn get_archetype(&self, id: ArchetypeId) -> Option<&RefCell<Archetype>> {
+    /// Swaps the buffers of the removed component events, should be called once per frame.
+    pub fn update_removed_components(&mut self) {
+        for events in self.removed_components.values_mut() {
+            events.update();
+        }
+    }
+
+    pub fn get_archetype(&self, id: ArchetypeId) -> Option<&AtomicRefCell<Archetype>> {
         self.archetypes.get(&id)
     }
 
+    pub fn iter(&self) -> impl Iterator<Item = &AtomicRefCell<Archetype>> {
+        self.archetypes.values()
+    }
+
     pub fn get_archetypes_with<'a>(&'a self, components: &'a ComponentGroup) -> Vec<ArchetypeId> {
         self.archetypes
             .values()
@@ -273,6 +542,8 @@ impl Archetypes {
         self.archetypes.clear();
         self.components.clear();
         self.edges = Edges::new();
+        self.removed_components.clear();
+        self.bundle_edges.clear();
         Self::init_defaults(&mut self.archetypes, &mut self.components);
     }
 
@@ -283,9 +554,7 @@ impl Archetypes {
             let components = archetype
                 .components
                 .iter()
-                .map(|info| match &info.component_type {
-                    ComponentType::Native { name, .. } => name.clone(),
-                })
+                .map(|info| info.component_type.name())
                 .collect::<Vec<_>>()
                 .join("\n    ");
             info!("Archetype {:?} with components {}", id, components);
@@ -319,7 +588,7 @@ impl Archetype {
         component: ComponentId,
         update_change_detection: Option<Tick>,
     ) -> Option<NonNull<u8>> {
-        let column = &self.table.columns[&component];
+        let column = self.table.columns.get(&component)?;
         if let Some(tick) = update_change_detection {
             let changed_tick = unsafe { &mut *column.change_detection.get() };
             changed_tick.changed_ticks[table_index] = tick;
@@ -332,8 +601,18 @@ impl Archetype {
         let last_affected = self
             .table
             .remove_row(entity_meta.table_index)
-            .map(|index| self.entities.get(index).cloned())
-            .flatten();
+            .and_then(|index| self.entities.get(index).cloned());
+        self.entities.swap_remove(entity_meta.table_index);
+        last_affected
+    }
+
+    /// Removes an entity and drops all of its component data. Returns the entity that was moved
+    /// into the removed entity's row, if any.
+    pub fn remove_and_drop(&mut self, entity_meta: &EntityMeta) -> Option<EntityId> {
+        let last_affected = self
+            .table
+            .drop_row(entity_meta.table_index)
+            .and_then(|index| self.entities.get(index).cloned());
         self.entities.swap_remove(entity_meta.table_index);
         last_affected
     }
@@ -345,17 +624,19 @@ impl Archetype {
         entity_id: EntityId,
         entity_meta: &EntityMeta,
         old_archetype: &Archetype,
-        new_component_data: impl Iterator<Item = (ComponentId, *const u8)>,
+        new_component_data: impl Iterator<Item = (ComponentId, *const u8, ComponentTicks)>,
     ) {
         self.table.push_row(
             new_component_data
-                // Chain an iterator of component pointers from the old archetype
+                // Chain an iterator of component pointers from the old archetype, keeping their ticks
                 .chain(old_archetype.components.iter().filter_map(|info| {
                     if self.components.contains(info.id) {
+                        let ticks = old_archetype.table.columns[&info.id]
+                            .get_ticks(entity_meta.table_index);
                         Some(
                             old_archetype
                                 .pointer_to_entity_component(entity_meta.table_index, info.id, None)
-                                .map(|ptr| (info.id, ptr.as_ptr() as *const u8))
+                                .map(|ptr| (info.id, ptr.as_ptr() as *const u8, ticks))
                                 .expect("entity not found in old archetype"),
                         )
                     } else {
@@ -400,23 +681,50 @@ impl Column {
         }
     }
 
-    pub unsafe fn push_from_ptr(&self, ptr: *const u8) {
+    pub unsafe fn push_from_ptr(&self, ptr: *const u8, ticks: ComponentTicks) {
         (&mut *self.data.get()).push_from_ptr(ptr);
-        (*self.change_detection.get())
-            .changed_ticks
-            .push(Tick::new(1));
+        let change_detection = &mut *self.change_detection.get();
+        change_detection.added_ticks.push(ticks.added);
+        change_detection.changed_ticks.push(ticks.changed);
+    }
+
+    pub unsafe fn reserve(&self, additional: usize) {
+        (&mut *self.data.get()).reserve(additional);
+        let change_detection = &mut *self.change_detection.get();
+        change_detection.added_ticks.reserve(additional);
+        change_detection.changed_ticks.reserve(additional);
     }
 
     pub unsafe fn remove(&self, index: usize) {
         (*self.data.get()).swap_remove(index);
-        (*self.change_detection.get())
-            .changed_ticks
-            .swap_remove(index);
+        self.remove_ticks(index);
+    }
+
+    pub unsafe fn remove_and_drop(&self, index: usize) {
+        (*self.data.get()).swap_remove_and_drop(index);
+        self.remove_ticks(index);
+    }
+
+    unsafe fn remove_ticks(&self, index: usize) {
+        let change_detection = &mut *self.change_detection.get();
+        change_detection.added_ticks.swap_remove(index);
+        change_detection.changed_ticks.swap_remove(index);
     }
 
     pub fn get_changed_tick(&self, index: usize) -> Tick {
         unsafe { (*self.change_detection.get()).changed_ticks[index] }
     }
+
+    pub fn get_added_tick(&self, index: usize) -> Tick {
+        unsafe { (*self.change_detection.get()).added_ticks[index] }
+    }
+
+    pub fn get_ticks(&self, index: usize) -> ComponentTicks {
+        ComponentTicks {
+            added: self.get_added_tick(index),
+            changed: self.get_changed_tick(index),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -1,19 +1,48 @@
-use crate::{entity::EntityId, world::World};
+use crate::{
+    component::Component,
+    entity::EntityId,
+    registry::{ComponentId, ComponentRegistry},
+    world::World,
+};
 
-pub trait Bundle {
-    fn build(self, world: &mut World, entity: EntityId);
+/// A set of components that are spawned or inserted together. The entity is moved straight to
+/// the archetype with all of them, instead of through one archetype per component.
+///
+/// Implemented for tuples of up to 8 components, and for structs with `#[derive(Bundle)]`, whose
+/// fields can be components or other bundles.
+pub trait Bundle: 'static {
+    /// Appends the ids of the bundle's components, in the same order as `component_ptrs`.
+    /// Components that are not registered yet are registered.
+    fn component_ids(components: &mut ComponentRegistry, ids: &mut Vec<ComponentId>);
+
+    /// Appends a pointer to each of the bundle's components. Whoever reads the pointers takes
+    /// ownership of the components, so the bundle must not be dropped afterwards.
+    fn component_ptrs(&self, ptrs: &mut Vec<*const u8>);
+
+    fn build(self, world: &mut World, entity: EntityId)
+    where
+        Self: Sized,
+    {
+        world.insert_bundle(entity, self);
+    }
 }
 
 macro_rules! impl_bundle {
     ($($members:ident),+) => {
         impl<
-            $($members: 'static),+,
+            $($members: Component),+,
         > Bundle for ($($members,)+) {
+            fn component_ids(components: &mut ComponentRegistry, ids: &mut Vec<ComponentId>) {
+                $(
+                    ids.push(components.register::<$members>());
+                )*
+            }
+
             #[allow(non_snake_case)]
-            fn build(self, world: &mut World, entity: EntityId) {
+            fn component_ptrs(&self, ptrs: &mut Vec<*const u8>) {
                 let ($($members,)*) = self;
                 $(
-                    world.insert(entity, $members);
+                    ptrs.push($members as *const $members as *const u8);
                 )*
             }
         }
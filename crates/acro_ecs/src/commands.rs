@@ -0,0 +1,210 @@
+use std::sync::Mutex;
+
+use crate::{
+    bundle::Bundle,
+    component::Component,
+    entity::EntityId,
+    systems::{SystemAccess, SystemParam},
+    world::World,
+};
+
+pub type Command = Box<dyn FnOnce(&mut World)>;
+
+/// Structural changes recorded by systems, applied in the order they were queued once the system
+/// that queued them finishes running.
+#[derive(Default)]
+pub struct CommandQueue {
+    commands: Mutex<Vec<Command>>,
+}
+
+impl std::fmt::Debug for CommandQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommandQueue")
+            .field("commands", &self.commands.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl CommandQueue {
+    pub fn push(&self, command: impl FnOnce(&mut World) + 'static) {
+        self.commands.lock().unwrap().push(Box::new(command));
+    }
+
+    pub fn take(&self) -> Vec<Command> {
+        std::mem::take(&mut *self.commands.lock().unwrap())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.lock().unwrap().is_empty()
+    }
+}
+
+pub struct Commands<'w> {
+    world: &'w World,
+}
+
+impl<'w> Commands<'w> {
+    pub fn new(world: &'w World) -> Self {
+        Self { world }
+    }
+
+    /// Queues an arbitrary operation on the world.
+    pub fn add(&mut self, command: impl FnOnce(&mut World) + 'static) {
+        self.world.commands.push(command);
+    }
+
+    /// Reserves an entity id immediately and spawns the entity when the commands are applied.
+    pub fn spawn<T: Bundle + 'static>(&mut self, bundle: T) -> EntityId {
+        let entity = self.world.reserve_entity();
+        self.add(move |world| bundle.build(world, entity));
+        entity
+    }
+
+    pub fn spawn_empty(&mut self) -> EntityId {
+        self.world.reserve_entity()
+    }
+
+    /// Inserts a component, doing nothing if the entity no longer exists.
+    pub fn insert<T: Component>(&mut self, entity: EntityId, component: T) {
+        self.add(move |world| {
+            // An earlier command may have despawned the entity
+            if world.entity_meta_opt(entity).is_some() {
+                world.insert(entity, component);
+            }
+        });
+    }
+
+    /// Removes a component from an entity, doing nothing if the entity no longer exists or does
+    /// not have the component.
+    pub fn remove<T: 'static>(&mut self, entity: EntityId) {
+        self.add(move |world| {
+            if world.get::<T>(entity).is_some() {
+                world.remove::<T>(entity);
+            }
+        });
+    }
+
+    pub fn despawn(&mut self, entity: EntityId) {
+        self.add(move |world| {
+            world.despawn(entity);
+        });
+    }
+
+    pub fn insert_resource<T: 'static>(&mut self, resource: T) {
+        self.add(move |world| world.insert_resource(resource));
+    }
+}
+
+impl SystemParam for Commands<'_> {
+    type Init = ();
+
+    fn init(_world: &World) {}
+
+    fn create(world: &World, _prepared: &mut Self::Init) -> Self {
+        // Same as Res, the world outlives the system run
+        unsafe { std::mem::transmute(Commands::new(world)) }
+    }
+
+    fn access(_world: &World, _prepared: &Self::Init, access: &mut SystemAccess) {
+        access.set_deferred();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        archetype::ArchetypeId, schedule::Stage, world::World, Application, EntityId, Query, Res,
+        SystemRunContext,
+    };
+
+    use super::Commands;
+
+    #[derive(Debug, PartialEq)]
+    struct Spawned(Vec<EntityId>);
+
+    #[test]
+    fn commands() {
+        let mut app = Application::new();
+
+        let existing = {
+            let mut world = app.world();
+            world.init_component::<u32>();
+            world.init_component::<bool>();
+
+            let to_despawn = world.spawn((1u32,));
+            let to_modify = world.spawn((2u32, true));
+            world.despawn(to_despawn);
+            to_modify
+        };
+
+        app.add_system(
+            Stage::Update,
+            [],
+            move |_ctx: SystemRunContext, mut commands: Commands| {
+                let first = commands.spawn((10u32,));
+                let second = commands.spawn((20u32, false));
+                assert_ne!(first, second);
+
+                commands.insert(existing, 3u32);
+                commands.remove::<bool>(existing);
+                commands.despawn(second);
+                commands.insert_resource(Spawned(vec![first, second]));
+            },
+        );
+
+        app.add_system(
+            Stage::PostUpdate,
+            [],
+            |ctx: SystemRunContext, query: Query<(EntityId, &u32)>, spawned: Res<Spawned>| {
+                let mut values = query.over(&ctx).collect::<Vec<_>>();
+                values.sort_by_key(|(_, value)| **value);
+
+                assert_eq!(values.len(), 2);
+                assert_eq!(*values[0].1, 3);
+                assert_eq!(values[1], (spawned.0[0], &10));
+            },
+        );
+
+        app.run_once();
+
+        let world = app.world();
+        assert_eq!(world.get::<bool>(existing), None);
+        let spawned = world.resource::<Spawned>();
+        assert!(world.entity_meta_opt(spawned.0[1]).is_none());
+    }
+
+    #[test]
+    fn reserve_despawn_flush() {
+        let mut world = World::new();
+        world.init_component::<u32>();
+
+        let freed = world.spawn((1u32,));
+        let existing = world.spawn((2u32,));
+        world.despawn(freed);
+
+        // The reservation takes the freed slot, despawning has to flush it before freeing another
+        let reserved = world.reserve_entity();
+        assert_eq!(reserved.index, freed.index);
+        assert!(world.despawn(existing));
+        world.apply_commands();
+        assert!(world.entity_meta_opt(reserved).is_some());
+
+        let spawned = world.spawn((3u32,));
+        assert_eq!(spawned.index, existing.index);
+        assert_eq!(world.get::<u32>(spawned), Some(&3));
+        assert_eq!(world.entity_meta(reserved).archetype_id, ArchetypeId::EMPTY);
+
+        // Reserved entities can be despawned before they are flushed
+        let reserved = world.reserve_entity();
+        assert!(world.despawn(reserved));
+        assert!(world.entity_meta_opt(reserved).is_none());
+
+        {
+            let mut commands = Commands::new(&world);
+            commands.despawn(spawned);
+            commands.insert(spawned, 4u32);
+        }
+        world.apply_commands();
+        assert!(world.entity_meta_opt(spawned).is_none());
+    }
+}
@@ -0,0 +1,42 @@
+use std::{rc::Rc, sync::Arc};
+
+use crate::registry::ComponentHooks;
+
+/// How the data of a component is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StorageType {
+    /// Stored in the archetype's table, next to the entity's other components.
+    #[default]
+    Table,
+}
+
+/// A type that can be stored on entities, usually implemented with `#[derive(Component)]`.
+///
+/// Components are registered, along with this metadata, the first time they are inserted. Common
+/// std types implement it without any metadata.
+pub trait Component: 'static {
+    const STORAGE: StorageType = StorageType::Table;
+
+    /// The name scripts use for the component.
+    const SCRIPT_NAME: Option<&'static str> = None;
+
+    fn hooks() -> ComponentHooks {
+        ComponentHooks::default()
+    }
+}
+
+macro_rules! impl_component {
+    ($($ty:ty),*) => {
+        $(impl Component for $ty {})*
+    };
+}
+
+impl_component!(bool, char, f32, f64, String, &'static str);
+impl_component!(u8, u16, u32, u64, u128, usize);
+impl_component!(i8, i16, i32, i64, i128, isize);
+
+impl<T: 'static> Component for Box<T> {}
+impl<T: 'static> Component for Rc<T> {}
+impl<T: 'static> Component for Arc<T> {}
+impl<T: 'static> Component for Vec<T> {}
+impl<T: 'static> Component for Option<T> {}
@@ -0,0 +1,106 @@
+use crate::world::World;
+
+/// A check evaluated right before a system runs. The system is skipped for that run when the
+/// condition returns false. Conditions are always evaluated on the main thread.
+pub struct RunCondition {
+    name: String,
+    condition: Box<dyn FnMut(&World) -> bool>,
+}
+
+impl std::fmt::Debug for RunCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RunCondition")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl RunCondition {
+    pub fn new(name: impl ToString, condition: impl FnMut(&World) -> bool + 'static) -> Self {
+        Self {
+            name: name.to_string(),
+            condition: Box::new(condition),
+        }
+    }
+
+    pub fn check(&mut self, world: &World) -> bool {
+        (self.condition)(world)
+    }
+
+    pub fn resource_exists<T: 'static>() -> Self {
+        Self::new(
+            format!("resource_exists<{}>", std::any::type_name::<T>()),
+            |world| world.resources().contains::<T>(),
+        )
+    }
+
+    /// Passes when the resource `T` exists and is equal to `value`, e.g. to run a system only in
+    /// a certain game state.
+    pub fn resource_equals<T: PartialEq + 'static>(value: T) -> Self {
+        Self::new(
+            format!("resource_equals<{}>", std::any::type_name::<T>()),
+            move |world| world.resources().contains::<T>() && *world.resource::<T>() == value,
+        )
+    }
+
+    /// Passes on every `n`th time the system's stage runs, starting with the first.
+    pub fn every_n_ticks(n: u32) -> Self {
+        assert!(n > 0, "run condition interval must be at least 1");
+
+        let mut count = 0;
+        Self::new(format!("every_n_ticks({n})"), move |_world| {
+            let should_run = count == 0;
+            count = (count + 1) % n;
+            should_run
+        })
+    }
+}
+
+impl std::ops::Not for RunCondition {
+    type Output = RunCondition;
+
+    fn not(mut self) -> Self::Output {
+        Self::new(format!("not({})", self.name), move |world| {
+            !self.check(world)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::World;
+
+    use super::RunCondition;
+
+    #[derive(Debug, PartialEq)]
+    enum GameState {
+        Menu,
+        Playing,
+    }
+
+    #[test]
+    fn conditions() {
+        let mut world = World::new();
+
+        let mut exists = RunCondition::resource_exists::<GameState>();
+        let mut missing = !RunCondition::resource_exists::<GameState>();
+        let mut playing = RunCondition::resource_equals(GameState::Playing);
+        assert!(!exists.check(&world));
+        assert!(missing.check(&world));
+        assert!(!playing.check(&world));
+
+        world.insert_resource(GameState::Menu);
+        assert!(exists.check(&world));
+        assert!(!missing.check(&world));
+        assert!(!playing.check(&world));
+
+        *world.resource_mut::<GameState>() = GameState::Playing;
+        assert!(playing.check(&world));
+
+        let mut every_third = RunCondition::every_n_ticks(3);
+        let results = (0..7)
+            .map(|_| every_third.check(&world))
+            .collect::<Vec<_>>();
+        assert_eq!(results, vec![true, false, false, true, false, false, true]);
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use serde::{Deserialize, Serialize};
 
 use crate::archetype::ArchetypeId;
@@ -18,6 +20,11 @@ impl EntityId {
 pub struct Entities {
     entities: Vec<EntityMeta>,
     free_list: Vec<u32>,
+    // Entities handed out through a shared reference (see `Entities::reserve`) that have not been
+    // placed into an archetype yet. Reserved free slots are taken from the end of `free_list`.
+    // The number of reserved free slots (high half) and new indices (low half) share one atomic so
+    // that reserving from several threads at once hands out every id exactly once.
+    reserved: AtomicU64,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -32,20 +39,103 @@ impl Entities {
         Self {
             entities: Vec::new(),
             free_list: Vec::new(),
+            reserved: AtomicU64::new(0),
         }
     }
 
     pub fn clear(&mut self) {
         self.entities.clear();
         self.free_list.clear();
+        *self.reserved.get_mut() = 0;
+    }
+
+    fn unpack_reserved(reserved: u64) -> (usize, u32) {
+        ((reserved >> 32) as usize, reserved as u32)
+    }
+
+    /// Reserves an entity id without spawning it. The entity is spawned into the empty archetype
+    /// the next time `flush_reserved` is called.
+    pub fn reserve(&self) -> EntityId {
+        let free_len = self.free_list.len();
+        let previous = self
+            .reserved
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |reserved| {
+                let (reserved_free, _) = Self::unpack_reserved(reserved);
+                Some(if reserved_free < free_len {
+                    reserved + (1 << 32)
+                } else {
+                    reserved + 1
+                })
+            })
+            .expect("reservation update never fails");
+
+        let (reserved_free, reserved_new) = Self::unpack_reserved(previous);
+        if reserved_free < free_len {
+            let index = self.free_list[free_len - 1 - reserved_free];
+            EntityId {
+                generation: self.entities[index as usize].generation,
+                index,
+            }
+        } else {
+            EntityId {
+                generation: 0,
+                index: self.entities.len() as u32 + reserved_new,
+            }
+        }
+    }
+
+    /// Spawns all reserved entities, in the order they were reserved. `place` is called with each
+    /// entity and returns its index in the empty archetype's table.
+    pub fn flush_reserved(&mut self, mut place: impl FnMut(EntityId) -> usize) {
+        let (reserved_free, reserved_new) =
+            Self::unpack_reserved(std::mem::take(self.reserved.get_mut()));
+
+        for _ in 0..reserved_free {
+            let index = self
+                .free_list
+                .pop()
+                .expect("reserved entity not in free list");
+            let generation = self.entities[index as usize].generation;
+            let table_index = place(EntityId { generation, index });
+            let meta = &mut self.entities[index as usize];
+            meta.archetype_id = ArchetypeId::EMPTY;
+            meta.table_index = table_index;
+        }
+
+        for _ in 0..reserved_new {
+            let index = self.entities.len() as u32;
+            let table_index = place(EntityId {
+                generation: 0,
+                index,
+            });
+            self.entities.push(EntityMeta {
+                generation: 0,
+                archetype_id: ArchetypeId::EMPTY,
+                table_index,
+            });
+        }
+    }
+
+    pub fn has_reserved(&self) -> bool {
+        self.reserved.load(Ordering::Relaxed) != 0
     }
 
     pub fn spawn(&mut self, empty_archetype_table_index: usize) -> EntityId {
+        self.spawn_in(ArchetypeId::EMPTY, empty_archetype_table_index)
+    }
+
+    /// Spawns an entity that is placed directly into `archetype_id`, at `table_index`.
+    pub fn spawn_in(&mut self, archetype_id: ArchetypeId, table_index: usize) -> EntityId {
+        debug_assert!(
+            !self.has_reserved(),
+            "reserved entities must be flushed before spawning"
+        );
+
         // If an entity has been freed, reuse that location instead of creating a new one
         if let Some(index) = self.free_list.pop() {
             let meta = &mut self.entities[index as usize];
-            meta.archetype_id = ArchetypeId::EMPTY;
-            meta.table_index = empty_archetype_table_index;
+            meta.archetype_id = archetype_id;
+            meta.table_index = table_index;
             EntityId {
                 generation: meta.generation,
                 index,
@@ -56,8 +146,8 @@ impl Entities {
             let index = self.entities.len() as u32;
             self.entities.push(EntityMeta {
                 generation,
-                archetype_id: ArchetypeId::EMPTY,
-                table_index: empty_archetype_table_index,
+                archetype_id,
+                table_index,
             });
             EntityId { generation, index }
         }
@@ -66,12 +156,18 @@ impl Entities {
     pub fn free(&mut self, id: EntityId) {
         let meta = &mut self.entities[id.index as usize];
         meta.generation = meta.generation.wrapping_add(1);
-        self.free_list.push(id.index);
+        // Freed entities don't live in any archetype. This also keeps reserved (but not yet
+        // flushed) entities from being treated as alive.
+        meta.archetype_id = ArchetypeId::INVALID;
+        // Outstanding reservations own the end of the free list, keep the slot out of their way
+        let (reserved_free, _) = Self::unpack_reserved(*self.reserved.get_mut());
+        let unreserved = self.free_list.len() - reserved_free;
+        self.free_list.insert(unreserved, id.index);
     }
 
     pub fn get(&self, id: EntityId) -> Option<&EntityMeta> {
         let meta = self.entities.get(id.index as usize)?;
-        if meta.generation == id.generation {
+        if meta.generation == id.generation && meta.archetype_id != ArchetypeId::INVALID {
             Some(meta)
         } else {
             None
@@ -80,7 +176,7 @@ impl Entities {
 
     pub fn get_mut(&mut self, id: EntityId) -> Option<&mut EntityMeta> {
         let meta = self.entities.get_mut(id.index as usize)?;
-        if meta.generation == id.generation {
+        if meta.generation == id.generation && meta.archetype_id != ArchetypeId::INVALID {
             Some(meta)
         } else {
             None
@@ -126,4 +222,61 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn reserve_and_flush() {
+        let mut entities = Entities::new();
+
+        let id1 = entities.spawn(0);
+        let _id2 = entities.spawn(1);
+        entities.free(id1);
+
+        // Reserving reuses freed slots first, then hands out new indices
+        let reserved1 = entities.reserve();
+        let reserved2 = entities.reserve();
+        assert_eq!(reserved1, EntityId::new(1, 0));
+        assert_eq!(reserved2, EntityId::new(0, 2));
+        assert_eq!(entities.get(reserved1), None);
+        assert_eq!(entities.get(reserved2), None);
+
+        let mut placed = vec![];
+        entities.flush_reserved(|entity| {
+            placed.push(entity);
+            placed.len() - 1
+        });
+        assert_eq!(placed, vec![reserved1, reserved2]);
+        assert!(!entities.has_reserved());
+
+        assert_eq!(entities.get(reserved1).unwrap().table_index, 0);
+        assert_eq!(entities.get(reserved2).unwrap().table_index, 1);
+
+        let id3 = entities.spawn(2);
+        assert_eq!(id3, EntityId::new(0, 3));
+    }
+
+    #[test]
+    fn free_while_reserved() {
+        let mut entities = Entities::new();
+
+        let id1 = entities.spawn(0);
+        let id2 = entities.spawn(1);
+        entities.free(id1);
+
+        let reserved = entities.reserve();
+        assert_eq!(reserved, EntityId::new(1, 0));
+
+        // Freeing before the flush must not hand the freed slot to the reservation
+        entities.free(id2);
+        let mut placed = vec![];
+        entities.flush_reserved(|entity| {
+            placed.push(entity);
+            0
+        });
+        assert_eq!(placed, vec![reserved]);
+        assert!(entities.get(reserved).is_some());
+        assert_eq!(entities.get(id2), None);
+
+        let id3 = entities.spawn(1);
+        assert_eq!(id3, EntityId::new(1, 1));
+    }
 }
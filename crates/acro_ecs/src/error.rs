@@ -0,0 +1,131 @@
+use std::fmt;
+
+use crate::entity::EntityId;
+
+/// The entity was never spawned or has been despawned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityNotFound(pub EntityId);
+
+impl fmt::Display for EntityNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "entity {:?} not found", self.0)
+    }
+}
+
+impl std::error::Error for EntityNotFound {}
+
+/// The component type has never been registered or inserted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentNotRegistered(pub &'static str);
+
+impl ComponentNotRegistered {
+    pub fn of<T: 'static>() -> Self {
+        Self(std::any::type_name::<T>())
+    }
+}
+
+impl fmt::Display for ComponentNotRegistered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "component {} not found", self.0)
+    }
+}
+
+impl std::error::Error for ComponentNotRegistered {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceMissing(pub &'static str);
+
+impl ResourceMissing {
+    pub fn of<T: 'static>() -> Self {
+        Self(std::any::type_name::<T>())
+    }
+}
+
+impl fmt::Display for ResourceMissing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "resource {} not found", self.0)
+    }
+}
+
+impl std::error::Error for ResourceMissing {}
+
+/// Why a component could not be accessed or removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentError {
+    EntityNotFound(EntityNotFound),
+    ComponentNotRegistered(ComponentNotRegistered),
+    /// The entity exists, but does not have the component.
+    MissingComponent {
+        entity: EntityId,
+        component: &'static str,
+    },
+}
+
+impl fmt::Display for ComponentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentError::EntityNotFound(error) => error.fmt(f),
+            ComponentError::ComponentNotRegistered(error) => error.fmt(f),
+            ComponentError::MissingComponent { entity, component } => {
+                write!(f, "entity {entity:?} does not have component {component}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ComponentError {}
+
+impl From<EntityNotFound> for ComponentError {
+    fn from(error: EntityNotFound) -> Self {
+        ComponentError::EntityNotFound(error)
+    }
+}
+
+impl From<ComponentNotRegistered> for ComponentError {
+    fn from(error: ComponentNotRegistered) -> Self {
+        ComponentError::ComponentNotRegistered(error)
+    }
+}
+
+/// Returned by `Query::try_single` when the query does not match exactly one entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuerySingleError {
+    None,
+    Multiple,
+}
+
+impl fmt::Display for QuerySingleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuerySingleError::None => write!(f, "query returned no results"),
+            QuerySingleError::Multiple => write!(f, "query returned more than one result"),
+        }
+    }
+}
+
+impl std::error::Error for QuerySingleError {}
+
+/// Returned by `Query::get_many`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryGetManyError {
+    /// The entity does not exist or does not match the query.
+    NoMatch(EntityId),
+    /// The entity was listed more than once, which is not allowed for queries with mutable
+    /// access.
+    AliasedMutability(EntityId),
+}
+
+impl fmt::Display for QueryGetManyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryGetManyError::NoMatch(entity) => {
+                write!(f, "entity {entity:?} does not match the query")
+            }
+            QueryGetManyError::AliasedMutability(entity) => {
+                write!(f, "entity {entity:?} was requested mutably more than once")
+            }
+        }
+    }
+}
+
+impl std::error::Error for QueryGetManyError {}
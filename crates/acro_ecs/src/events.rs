@@ -0,0 +1,359 @@
+use std::marker::PhantomData;
+
+use crate::{
+    entity::EntityId,
+    resource::{Res, ResMut},
+    systems::{ParallelSystemParam, SystemAccess, SystemParam, SystemRunContext},
+    world::World,
+};
+
+/// A double buffered queue of events. Events are kept around for two calls of `Events::update`
+/// (two frames), so every reader that runs once per frame sees each event exactly once,
+/// regardless of whether it runs before or after the writer.
+#[derive(Debug)]
+pub struct Events<T> {
+    previous: Vec<T>,
+    current: Vec<T>,
+    // Ids of the first event in each buffer. Event ids are assigned sequentially.
+    previous_start: usize,
+    current_start: usize,
+    event_count: usize,
+}
+
+impl<T> Default for Events<T> {
+    fn default() -> Self {
+        Self {
+            previous: vec![],
+            current: vec![],
+            previous_start: 0,
+            current_start: 0,
+            event_count: 0,
+        }
+    }
+}
+
+impl<T: 'static> Events<T> {
+    pub fn send(&mut self, event: T) {
+        self.current.push(event);
+        self.event_count += 1;
+    }
+
+    /// Drops the events from the previous frame and moves this frame's events into the previous
+    /// buffer.
+    pub fn update(&mut self) {
+        self.previous = std::mem::take(&mut self.current);
+        self.previous_start = self.current_start;
+        self.current_start = self.event_count;
+    }
+
+    pub fn update_system(_ctx: SystemRunContext, mut events: ResMut<Events<T>>) {
+        events.update();
+    }
+
+    /// Returns the events with an id greater than or equal to `cursor`.
+    pub fn events_since(&self, cursor: usize) -> impl Iterator<Item = &T> {
+        let previous_skip = cursor.saturating_sub(self.previous_start);
+        let current_skip = cursor.saturating_sub(self.current_start);
+
+        self.previous
+            .iter()
+            .skip(previous_skip)
+            .chain(self.current.iter().skip(current_skip))
+    }
+
+    pub fn event_count(&self) -> usize {
+        self.event_count
+    }
+
+    pub fn clear(&mut self) {
+        self.previous.clear();
+        self.current.clear();
+        self.previous_start = self.event_count;
+        self.current_start = self.event_count;
+    }
+}
+
+pub struct EventWriter<'w, T: 'static> {
+    events: ResMut<'w, Events<T>>,
+}
+
+impl<T: 'static> EventWriter<'_, T> {
+    pub fn send(&mut self, event: T) {
+        self.events.send(event);
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = T>) {
+        for event in events {
+            self.events.send(event);
+        }
+    }
+}
+
+impl<T: 'static> SystemParam for EventWriter<'_, T> {
+    type Init = ();
+
+    fn init(_world: &World) {}
+
+    fn create(world: &World, _prepared: &mut Self::Init) -> Self {
+        // Same as ResMut, the world outlives the system run
+        unsafe {
+            std::mem::transmute(EventWriter {
+                events: world.resources.get_mut::<Events<T>>(),
+            })
+        }
+    }
+
+    fn access(_world: &World, _prepared: &Self::Init, access: &mut SystemAccess) {
+        access.write_resource::<Events<T>>();
+    }
+}
+
+// SAFETY: Only touches the `Events<T>` resource, like `ResMut`.
+unsafe impl<T: Send + Sync + 'static> ParallelSystemParam for EventWriter<'_, T> {}
+
+pub struct EventReader<'w, T: 'static> {
+    events: Res<'w, Events<T>>,
+    // Id of the next event this reader has not seen yet. Stored in the system's parameters so
+    // every reader keeps track of its own position.
+    cursor: &'w mut usize,
+}
+
+impl<T: 'static> EventReader<'_, T> {
+    /// Returns all events that this reader has not seen yet.
+    pub fn read(&mut self) -> impl Iterator<Item = &T> {
+        let cursor = std::mem::replace(self.cursor, self.events.event_count);
+        self.events.events_since(cursor)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.events_since(*self.cursor).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Marks all events as read without iterating over them.
+    pub fn clear(&mut self) {
+        *self.cursor = self.events.event_count;
+    }
+}
+
+impl<T: 'static> SystemParam for EventReader<'_, T> {
+    type Init = usize;
+
+    fn init(_world: &World) -> Self::Init {
+        0
+    }
+
+    fn create(world: &World, prepared: &mut Self::Init) -> Self {
+        // Same as Res, the world and the system parameters outlive the system run
+        unsafe {
+            std::mem::transmute(EventReader {
+                events: world.resources.get::<Events<T>>(),
+                cursor: prepared,
+            })
+        }
+    }
+
+    fn access(_world: &World, _prepared: &Self::Init, access: &mut SystemAccess) {
+        access.read_resource::<Events<T>>();
+    }
+}
+
+// SAFETY: Only touches the `Events<T>` resource, like `Res`, and the cursor owned by the system.
+unsafe impl<T: Send + Sync + 'static> ParallelSystemParam for EventReader<'_, T> {}
+
+/// Reads the entities that had the component `T` removed, either through `World::remove` or by
+/// being despawned. Removals are kept around for two frames, like regular events.
+pub struct RemovedComponents<'w, T: 'static> {
+    events: Option<&'w Events<EntityId>>,
+    cursor: &'w mut usize,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: 'static> RemovedComponents<'_, T> {
+    /// Returns all entities with removals that this reader has not seen yet.
+    pub fn read(&mut self) -> impl Iterator<Item = EntityId> + '_ {
+        let events = self.events;
+        let cursor = std::mem::replace(
+            self.cursor,
+            events.map(|events| events.event_count).unwrap_or(0),
+        );
+        events
+            .into_iter()
+            .flat_map(move |events| events.events_since(cursor).copied())
+    }
+
+    pub fn len(&self) -> usize {
+        self.events
+            .map(|events| events.events_since(*self.cursor).count())
+            .unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: 'static> SystemParam for RemovedComponents<'_, T> {
+    type Init = usize;
+
+    fn init(_world: &World) -> Self::Init {
+        0
+    }
+
+    fn create(world: &World, prepared: &mut Self::Init) -> Self {
+        let component_id = world.get_component_info::<T>().id;
+        // Removals are only recorded while applying structural changes, which need a mutable
+        // borrow of the world, so the events can not change while the system runs.
+        unsafe {
+            std::mem::transmute(RemovedComponents::<T> {
+                events: world.archetypes.removed_components.get(&component_id),
+                cursor: prepared,
+                _phantom: PhantomData,
+            })
+        }
+    }
+
+    // Removals are only recorded while the world is mutably borrowed, so reading them never
+    // conflicts with other systems.
+    fn access(_world: &World, _prepared: &Self::Init, _access: &mut SystemAccess) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{schedule::Stage, Application, Commands, EntityId, ResMut, SystemRunContext};
+
+    use super::{EventReader, EventWriter, Events, RemovedComponents};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Ping(u32);
+
+    #[derive(Debug, Default)]
+    struct Received {
+        before_writer: Vec<u32>,
+        after_writer: Vec<u32>,
+    }
+
+    #[test]
+    fn double_buffering() {
+        let mut events = Events::<Ping>::default();
+        events.send(Ping(1));
+        events.update();
+        events.send(Ping(2));
+
+        assert_eq!(
+            events.events_since(0).copied().collect::<Vec<_>>(),
+            vec![Ping(1), Ping(2)]
+        );
+        assert_eq!(
+            events.events_since(1).copied().collect::<Vec<_>>(),
+            vec![Ping(2)]
+        );
+
+        events.update();
+        assert_eq!(
+            events.events_since(0).copied().collect::<Vec<_>>(),
+            vec![Ping(2)]
+        );
+
+        events.update();
+        assert_eq!(events.events_since(0).count(), 0);
+    }
+
+    #[test]
+    fn readers_and_writers() {
+        let mut app = Application::new();
+        app.add_event::<Ping>().insert_resource(Received::default());
+
+        app.add_system(
+            Stage::PreUpdate,
+            [],
+            |_ctx: SystemRunContext,
+             mut reader: EventReader<Ping>,
+             mut received: ResMut<Received>| {
+                let events = reader.read().map(|ping| ping.0).collect::<Vec<_>>();
+                received.before_writer.extend(events);
+            },
+        );
+
+        app.add_system(
+            Stage::Update,
+            [],
+            |_ctx: SystemRunContext, mut writer: EventWriter<Ping>| {
+                writer.send(Ping(1));
+                writer.send(Ping(2));
+            },
+        );
+
+        app.add_system(
+            Stage::PostUpdate,
+            [],
+            |_ctx: SystemRunContext,
+             mut reader: EventReader<Ping>,
+             mut received: ResMut<Received>| {
+                let events = reader.read().map(|ping| ping.0).collect::<Vec<_>>();
+                received.after_writer.extend(events);
+            },
+        );
+
+        app.run_once();
+        app.run_once();
+
+        let world = app.world();
+        let received = world.resource::<Received>();
+        assert_eq!(received.after_writer, vec![1, 2, 1, 2]);
+        // Readers that run before the writer see the events on the next frame
+        assert_eq!(received.before_writer, vec![1, 2]);
+    }
+
+    #[derive(Debug, Default)]
+    struct Removed(Vec<EntityId>);
+
+    #[test]
+    fn removed_components() {
+        let mut app = Application::new();
+        app.init_component::<u32>()
+            .init_component::<bool>()
+            .insert_resource(Removed::default());
+
+        let (removed, despawned, untouched) = {
+            let mut world = app.world();
+            (
+                world.spawn((1u32, true)),
+                world.spawn((2u32,)),
+                world.spawn((3u32,)),
+            )
+        };
+
+        app.add_system(
+            Stage::Update,
+            [],
+            move |_ctx: SystemRunContext, mut commands: Commands| {
+                commands.remove::<u32>(removed);
+                commands.despawn(despawned);
+            },
+        );
+
+        app.add_system(
+            Stage::PostUpdate,
+            [],
+            |_ctx: SystemRunContext,
+             mut removed_u32: RemovedComponents<u32>,
+             mut removed: ResMut<Removed>| {
+                removed.0.extend(removed_u32.read());
+            },
+        );
+
+        app.run_once();
+        app.run_once();
+
+        let world = app.world();
+        let removed_entities = &world.resource::<Removed>().0;
+        // Removing a missing component does not record anything on the second run
+        assert_eq!(removed_entities, &vec![removed, despawned]);
+        assert!(!removed_entities.contains(&untouched));
+        assert!(world.get::<bool>(removed).is_some());
+    }
+}
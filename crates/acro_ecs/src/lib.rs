@@ -1,29 +1,63 @@
 pub mod application;
 pub mod archetype;
 pub mod bundle;
+pub mod commands;
+pub mod component;
+pub mod condition;
 pub mod entity;
+pub mod error;
+pub mod events;
 pub mod plugin;
 pub mod pointer;
+pub mod profiling;
 pub mod query;
 pub mod registry;
 pub mod resource;
+pub mod runner;
 pub mod schedule;
+pub mod state;
 pub mod storage;
 pub mod systems;
+pub mod time;
 pub mod utils;
 pub mod world;
 
 pub use application::Application;
 pub use bundle::Bundle;
+pub use commands::Commands;
+pub use component::{Component, StorageType};
+pub use condition::RunCondition;
 pub use entity::EntityId;
-pub use plugin::Plugin;
+pub use error::{
+    ComponentError, ComponentNotRegistered, EntityNotFound, QueryGetManyError, QuerySingleError,
+    ResourceMissing,
+};
+pub use events::{EventReader, EventWriter, Events, RemovedComponents};
+pub use plugin::{Plugin, PluginGroup, PluginGroupBuilder};
 pub use pointer::change_detection::{Mut, Tick};
-pub use query::{Changed, Or, Query, With, Without};
-pub use registry::{ComponentId, ComponentRegistry, ComponentType};
+pub use profiling::{ChromeTrace, FrameStats, SystemStats, TimingStats};
+pub use query::{
+    Added, Changed, DynamicQuery, DynamicQueryBuilder, IncludeDisabled, Or, Query, With, Without,
+};
+pub use registry::{ComponentHook, ComponentHooks, ComponentId, ComponentRegistry, ComponentType};
 pub use resource::{Res, ResMut};
-pub use schedule::{Schedule, Stage, SystemSchedulingRequirement};
+pub use runner::{HeadlessRunner, RunMode, ScheduleRunnerPlugin};
+pub use schedule::{Schedule, Stage, SystemSchedulingRequirement, SystemSet};
+pub use state::{in_state, NextState, OnEnter, OnExit, State, States};
 pub use systems::SystemRunContext;
+pub use time::{Clock, FixedTimestep, ManualClock, SystemClock, Time};
 pub use world::World;
 
-#[derive(Debug)]
+pub use ecs_derive::{Bundle, Component};
+
+// Lets the derive macros, which refer to `acro_ecs`, be used inside this crate
+extern crate self as acro_ecs;
+
+#[derive(Debug, Component)]
 pub struct Name(pub String);
+
+/// Marks an entity as inactive. Queries skip disabled entities unless they use the
+/// [`IncludeDisabled`] filter or mention `Disabled` themselves, e.g. with `With<Disabled>`.
+/// Direct access through `World::get` is not affected.
+#[derive(Debug, Clone, Copy, Default, Component)]
+pub struct Disabled;
@@ -1,5 +1,209 @@
+use std::any::TypeId;
+
 use crate::application::Application;
 
-pub trait Plugin {
+pub trait Plugin: 'static {
     fn build(&mut self, app: &mut Application);
+
+    /// Identifies the plugin in dependency lists and error messages. Must be unique.
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Names of the plugins that have to be built before this one. The plugin is only built once
+    /// all of them have been added to the application.
+    fn dependencies(&self) -> Vec<&'static str> {
+        vec![]
+    }
+}
+
+/// A set of plugins that are added together, see [`Application::add_plugins`].
+pub trait PluginGroup {
+    fn build(self) -> PluginGroupBuilder;
+}
+
+struct PluginGroupEntry {
+    type_id: TypeId,
+    plugin: Box<dyn Plugin>,
+    enabled: bool,
+}
+
+#[derive(Default)]
+pub struct PluginGroupBuilder {
+    plugins: Vec<PluginGroupEntry>,
+}
+
+impl PluginGroupBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_plugin<P: Plugin>(mut self, plugin: P) -> Self {
+        self.plugins.push(PluginGroupEntry {
+            type_id: TypeId::of::<P>(),
+            plugin: Box::new(plugin),
+            enabled: true,
+        });
+        self
+    }
+
+    /// Replaces the group's instance of a plugin, e.g. to configure it differently.
+    pub fn set<P: Plugin>(mut self, plugin: P) -> Self {
+        self.entry_mut::<P>().plugin = Box::new(plugin);
+        self
+    }
+
+    pub fn enable<P: Plugin>(mut self) -> Self {
+        self.entry_mut::<P>().enabled = true;
+        self
+    }
+
+    pub fn disable<P: Plugin>(mut self) -> Self {
+        self.entry_mut::<P>().enabled = false;
+        self
+    }
+
+    pub fn is_enabled<P: Plugin>(&self) -> bool {
+        self.plugins
+            .iter()
+            .any(|entry| entry.type_id == TypeId::of::<P>() && entry.enabled)
+    }
+
+    fn entry_mut<P: Plugin>(&mut self) -> &mut PluginGroupEntry {
+        self.plugins
+            .iter_mut()
+            .find(|entry| entry.type_id == TypeId::of::<P>())
+            .unwrap_or_else(|| {
+                panic!(
+                    "plugin {} is not part of the group",
+                    std::any::type_name::<P>()
+                )
+            })
+    }
+
+    pub(crate) fn into_enabled_plugins(self) -> impl Iterator<Item = Box<dyn Plugin>> {
+        self.plugins
+            .into_iter()
+            .filter(|entry| entry.enabled)
+            .map(|entry| entry.plugin)
+    }
+}
+
+impl PluginGroup for PluginGroupBuilder {
+    fn build(self) -> PluginGroupBuilder {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::Application;
+
+    use super::{Plugin, PluginGroup, PluginGroupBuilder};
+
+    #[derive(Debug, Default)]
+    struct BuildOrder(Vec<&'static str>);
+
+    struct TestPlugin {
+        name: &'static str,
+        dependencies: Vec<&'static str>,
+    }
+
+    impl TestPlugin {
+        fn new(name: &'static str, dependencies: &[&'static str]) -> Self {
+            Self {
+                name,
+                dependencies: dependencies.to_vec(),
+            }
+        }
+    }
+
+    impl Plugin for TestPlugin {
+        fn build(&mut self, app: &mut Application) {
+            let name = self.name;
+            app.with_resource::<BuildOrder>(|mut order| order.0.push(name));
+        }
+
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn dependencies(&self) -> Vec<&'static str> {
+            self.dependencies.clone()
+        }
+    }
+
+    fn app_with_order() -> Application {
+        let mut app = Application::new();
+        app.insert_resource(BuildOrder::default());
+        app
+    }
+
+    #[test]
+    fn dependency_order() {
+        let mut app = app_with_order()
+            .add_plugin(TestPlugin::new("ui", &["render", "scripting"]))
+            .add_plugin(TestPlugin::new("render", &["assets"]))
+            .add_plugin(TestPlugin::new("assets", &[]))
+            .add_plugin(TestPlugin::new("scripting", &["assets"]));
+        app.finish_plugins();
+
+        assert_eq!(
+            app.world().resource::<BuildOrder>().0,
+            vec!["assets", "render", "scripting", "ui"]
+        );
+    }
+
+    #[test]
+    fn missing_dependency() {
+        let result = std::panic::catch_unwind(|| {
+            let mut app = app_with_order()
+                .add_plugin(TestPlugin::new("assets", &[]))
+                .add_plugin(TestPlugin::new("ui", &["render"]));
+            app.finish_plugins();
+        });
+
+        let message = result.expect_err("missing dependency should panic");
+        let message = message
+            .downcast_ref::<String>()
+            .expect("panic message should be a string");
+        assert!(message.contains("ui"));
+        assert!(message.contains("render"));
+    }
+
+    struct Group;
+
+    impl PluginGroup for Group {
+        fn build(self) -> PluginGroupBuilder {
+            PluginGroupBuilder::new()
+                .add_plugin(TestPlugin::new("assets", &[]))
+                .add_plugin(FlagPlugin(Rc::new(RefCell::new(false))))
+        }
+    }
+
+    struct FlagPlugin(Rc<RefCell<bool>>);
+
+    impl Plugin for FlagPlugin {
+        fn build(&mut self, _app: &mut Application) {
+            *self.0.borrow_mut() = true;
+        }
+    }
+
+    #[test]
+    fn plugin_groups() {
+        let flag = Rc::new(RefCell::new(false));
+        let group = Group.build().set(FlagPlugin(Rc::clone(&flag)));
+        assert!(group.is_enabled::<FlagPlugin>());
+
+        let mut app = app_with_order().add_plugins(group.disable::<FlagPlugin>());
+        app.finish_plugins();
+        assert_eq!(app.world().resource::<BuildOrder>().0, vec!["assets"]);
+        assert!(!*flag.borrow());
+
+        let mut app = app_with_order().add_plugins(Group.build().set(FlagPlugin(Rc::clone(&flag))));
+        app.finish_plugins();
+        assert!(*flag.borrow());
+    }
 }
@@ -22,9 +22,27 @@ impl Tick {
 
 #[derive(Debug, Default)]
 pub struct ChangeDetectionContext {
+    pub added_ticks: Vec<Tick>,
     pub changed_ticks: Vec<Tick>,
 }
 
+/// The change detection state of a single component, carried along when an entity moves between
+/// archetypes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentTicks {
+    pub added: Tick,
+    pub changed: Tick,
+}
+
+impl ComponentTicks {
+    pub fn new(tick: Tick) -> Self {
+        Self {
+            added: tick,
+            changed: tick,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Mut<'v, T> {
     current_tick: Tick,
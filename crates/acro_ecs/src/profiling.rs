@@ -0,0 +1,353 @@
+use std::{collections::VecDeque, path::PathBuf, time::Duration};
+
+use fnv::FnvHashMap;
+use serde::Serialize;
+use tracing::{error, info};
+use web_time::Instant;
+
+use crate::{
+    schedule::Stage,
+    systems::{SystemData, SystemId, SystemIndex},
+};
+
+/// Rolling timing statistics over the last [`TimingStats::WINDOW`] samples.
+#[derive(Debug, Clone, Default)]
+pub struct TimingStats {
+    samples: VecDeque<Duration>,
+}
+
+impl TimingStats {
+    pub const WINDOW: usize = 120;
+
+    pub fn record(&mut self, duration: Duration) {
+        if self.samples.len() == Self::WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(duration);
+    }
+
+    pub fn last(&self) -> Option<Duration> {
+        self.samples.back().copied()
+    }
+
+    pub fn min(&self) -> Duration {
+        self.samples.iter().min().copied().unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        self.samples.iter().max().copied().unwrap_or_default()
+    }
+
+    pub fn average(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+
+    /// The number of samples in the window.
+    pub fn count(&self) -> usize {
+        self.samples.len()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SystemStats {
+    pub id: SystemId,
+    pub index: SystemIndex,
+    pub name: String,
+    /// `None` for systems that run on state transitions.
+    pub stage: Option<Stage>,
+    pub timing: TimingStats,
+}
+
+/// A single run of a system, measured by the schedule.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SystemRun {
+    pub start: Instant,
+    pub duration: Duration,
+    // 0 for the main thread, the thread pool's workers start at 1
+    pub thread: usize,
+}
+
+/// How long frames, stages and systems take, updated by the schedule every frame. Stages and
+/// systems get a sample every time they run, so `Stage::FixedUpdate` can have several samples per
+/// frame, or none.
+///
+/// Times are measured with a monotonic clock, even when the schedule uses a different `Clock`.
+#[derive(Debug)]
+pub struct FrameStats {
+    frame: TimingStats,
+    stages: FnvHashMap<Stage, TimingStats>,
+    systems: FnvHashMap<SystemIndex, SystemStats>,
+    // Runs of the current frame, to find what made a slow frame slow
+    frame_systems: Vec<(SystemIndex, Duration)>,
+    frame_start: Instant,
+    trace: Option<TraceRecording>,
+}
+
+#[derive(Debug)]
+struct TraceRecording {
+    start: Instant,
+    events: Vec<TraceEvent>,
+    // Set by `trace_frames`, the trace is written once this reaches zero
+    remaining_frames: Option<(u32, PathBuf)>,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self {
+            frame: TimingStats::default(),
+            stages: FnvHashMap::default(),
+            systems: FnvHashMap::default(),
+            frame_systems: vec![],
+            frame_start: Instant::now(),
+            trace: None,
+        }
+    }
+}
+
+impl FrameStats {
+    /// Time spent in `Schedule::run_once`, for every frame.
+    pub fn frame(&self) -> &TimingStats {
+        &self.frame
+    }
+
+    pub fn stage(&self, stage: Stage) -> Option<&TimingStats> {
+        self.stages.get(&stage)
+    }
+
+    pub fn system(&self, index: SystemIndex) -> Option<&SystemStats> {
+        self.systems.get(&index)
+    }
+
+    /// Every system added from the same function has its own stats.
+    pub fn systems_with_id(&self, id: SystemId) -> impl Iterator<Item = &SystemStats> {
+        self.systems.values().filter(move |system| system.id == id)
+    }
+
+    pub fn systems(&self) -> impl Iterator<Item = &SystemStats> {
+        self.systems.values()
+    }
+
+    /// The systems with the highest average time, slowest first.
+    pub fn slowest_systems(&self, count: usize) -> Vec<&SystemStats> {
+        let mut systems = self.systems.values().collect::<Vec<_>>();
+        systems.sort_by_key(|system| std::cmp::Reverse(system.timing.average()));
+        systems.truncate(count);
+        systems
+    }
+
+    /// The systems that took the longest in the current (or last finished) frame, slowest first.
+    /// Systems that ran more than once are listed once per run.
+    pub fn slowest_in_frame(&self, count: usize) -> Vec<(&str, Duration)> {
+        let mut runs = self
+            .frame_systems
+            .iter()
+            .map(|(index, duration)| (self.systems[index].name.as_str(), *duration))
+            .collect::<Vec<_>>();
+        runs.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+        runs.truncate(count);
+        runs
+    }
+
+    /// Starts recording every frame, stage and system run into a trace, replacing the trace that
+    /// was being recorded.
+    pub fn start_trace(&mut self) {
+        self.trace = Some(TraceRecording {
+            start: Instant::now(),
+            events: vec![],
+            remaining_frames: None,
+        });
+    }
+
+    pub fn is_tracing(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    /// Stops recording and returns the trace, if one was being recorded.
+    pub fn stop_trace(&mut self) -> Option<ChromeTrace> {
+        self.trace.take().map(|trace| ChromeTrace {
+            trace_events: trace.events,
+        })
+    }
+
+    /// Records a trace of the next `frames` frames and writes it to `path` once they are done.
+    pub fn trace_frames(&mut self, frames: u32, path: impl Into<PathBuf>) {
+        self.start_trace();
+        if let Some(trace) = &mut self.trace {
+            trace.remaining_frames = Some((frames, path.into()));
+        }
+    }
+
+    pub(crate) fn begin_frame(&mut self) {
+        self.frame_start = Instant::now();
+        self.frame_systems.clear();
+    }
+
+    pub(crate) fn end_frame(&mut self) {
+        let duration = self.frame_start.elapsed();
+        self.frame.record(duration);
+        self.trace_event("frame", "frame", self.frame_start, duration, 0, None);
+
+        let finished = match &mut self.trace {
+            Some(TraceRecording {
+                remaining_frames: Some((frames, _)),
+                ..
+            }) => {
+                *frames = frames.saturating_sub(1);
+                *frames == 0
+            }
+            _ => false,
+        };
+        if !finished {
+            return;
+        }
+
+        let trace = self.trace.take().expect("trace should be recording");
+        let (_, path) = trace.remaining_frames.expect("trace should have a path");
+        let trace = ChromeTrace {
+            trace_events: trace.events,
+        };
+        match trace.save(&path) {
+            Ok(()) => info!("saved frame trace to {}", path.display()),
+            Err(err) => error!("failed to save frame trace to {}: {err}", path.display()),
+        }
+    }
+
+    pub(crate) fn record_stage(&mut self, stage: Stage, start: Instant) {
+        let duration = start.elapsed();
+        self.stages.entry(stage).or_default().record(duration);
+        self.trace_event(&format!("{stage:?}"), "stage", start, duration, 0, None);
+    }
+
+    pub(crate) fn record_system(
+        &mut self,
+        system: &SystemData,
+        stage: Option<Stage>,
+        run: SystemRun,
+    ) {
+        self.systems
+            .entry(system.index)
+            .or_insert_with(|| SystemStats {
+                id: system.id,
+                index: system.index,
+                name: system.name.clone(),
+                stage,
+                timing: TimingStats::default(),
+            })
+            .timing
+            .record(run.duration);
+        self.frame_systems.push((system.index, run.duration));
+        self.trace_event(
+            &system.name,
+            "system",
+            run.start,
+            run.duration,
+            run.thread,
+            stage,
+        );
+    }
+
+    fn trace_event(
+        &mut self,
+        name: &str,
+        category: &'static str,
+        start: Instant,
+        duration: Duration,
+        thread: usize,
+        stage: Option<Stage>,
+    ) {
+        let Some(trace) = &mut self.trace else {
+            return;
+        };
+
+        trace.events.push(TraceEvent {
+            name: name.to_string(),
+            cat: category,
+            ph: "X",
+            ts: start.saturating_duration_since(trace.start).as_secs_f64() * 1_000_000.0,
+            dur: duration.as_secs_f64() * 1_000_000.0,
+            pid: 0,
+            tid: thread,
+            args: TraceEventArgs {
+                stage: stage.map(|stage| format!("{stage:?}")),
+            },
+        });
+    }
+}
+
+/// A recording of frames in the Chrome trace event format, which can be opened with
+/// `chrome://tracing` or Perfetto.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChromeTrace {
+    trace_events: Vec<TraceEvent>,
+}
+
+impl ChromeTrace {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("failed to serialize trace")
+    }
+
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    /// The number of recorded frames, stage runs and system runs.
+    pub fn len(&self) -> usize {
+        self.trace_events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trace_events.is_empty()
+    }
+}
+
+// Complete ("X") events, timestamps and durations are in microseconds
+#[derive(Debug, Clone, Serialize)]
+struct TraceEvent {
+    name: String,
+    cat: &'static str,
+    ph: &'static str,
+    ts: f64,
+    dur: f64,
+    pid: u32,
+    tid: usize,
+    args: TraceEventArgs,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TraceEventArgs {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stage: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::TimingStats;
+
+    #[test]
+    fn rolling_stats() {
+        let mut stats = TimingStats::default();
+        assert_eq!(stats.average(), Duration::ZERO);
+
+        for millis in [4, 2, 6] {
+            stats.record(Duration::from_millis(millis));
+        }
+        assert_eq!(stats.min(), Duration::from_millis(2));
+        assert_eq!(stats.max(), Duration::from_millis(6));
+        assert_eq!(stats.average(), Duration::from_millis(4));
+        assert_eq!(stats.last(), Some(Duration::from_millis(6)));
+
+        // Old samples fall out of the window
+        for _ in 0..TimingStats::WINDOW {
+            stats.record(Duration::from_millis(1));
+        }
+        assert_eq!(stats.count(), TimingStats::WINDOW);
+        assert_eq!(stats.max(), Duration::from_millis(1));
+    }
+}
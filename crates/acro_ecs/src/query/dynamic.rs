@@ -0,0 +1,424 @@
+use std::{cell::RefCell, ptr::NonNull, rc::Rc};
+
+use atomic_refcell::AtomicRefCell;
+
+use crate::{
+    archetype::{Archetype, ArchetypeId, Column},
+    entity::EntityId,
+    registry::ComponentId,
+    systems::{IntoSystemRunContext, SystemAccess, SystemRunContext},
+    world::World,
+};
+
+use super::info::{QueryComponentInfo, QueryInfo};
+
+#[derive(Debug, Clone, Copy)]
+enum DynamicTerm {
+    Required { id: ComponentId, mutable: bool },
+    Optional { id: ComponentId, mutable: bool },
+}
+
+/// Builds a [`DynamicQuery`] from component ids known only at runtime, e.g. by an inspector or
+/// a script.
+#[derive(Debug, Default, Clone)]
+pub struct DynamicQueryBuilder {
+    terms: Vec<DynamicTerm>,
+    with: Vec<ComponentId>,
+    without: Vec<ComponentId>,
+    changed: Vec<ComponentId>,
+    added: Vec<ComponentId>,
+    include_disabled: bool,
+}
+
+impl DynamicQueryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fetches a component every matched entity has.
+    pub fn required(mut self, id: ComponentId) -> Self {
+        self.terms
+            .push(DynamicTerm::Required { id, mutable: false });
+        self
+    }
+
+    /// Same as [`DynamicQueryBuilder::required`], but the component is marked as changed when
+    /// fetched, since writes through the pointer can't be tracked.
+    pub fn required_mut(mut self, id: ComponentId) -> Self {
+        self.terms.push(DynamicTerm::Required { id, mutable: true });
+        self
+    }
+
+    /// Fetches a component if the entity has it, without affecting which entities match.
+    pub fn optional(mut self, id: ComponentId) -> Self {
+        self.terms
+            .push(DynamicTerm::Optional { id, mutable: false });
+        self
+    }
+
+    pub fn optional_mut(mut self, id: ComponentId) -> Self {
+        self.terms.push(DynamicTerm::Optional { id, mutable: true });
+        self
+    }
+
+    /// Only matches entities that have the component, without fetching it.
+    pub fn with(mut self, id: ComponentId) -> Self {
+        self.with.push(id);
+        self
+    }
+
+    pub fn without(mut self, id: ComponentId) -> Self {
+        self.without.push(id);
+        self
+    }
+
+    /// Only matches entities whose component changed since the system last ran.
+    pub fn changed(mut self, id: ComponentId) -> Self {
+        self.changed.push(id);
+        self
+    }
+
+    /// Only matches entities whose component was added since the system last ran.
+    pub fn added(mut self, id: ComponentId) -> Self {
+        self.added.push(id);
+        self
+    }
+
+    /// Matches disabled entities as well, like the `IncludeDisabled` filter.
+    pub fn include_disabled(mut self) -> Self {
+        self.include_disabled = true;
+        self
+    }
+
+    pub fn build(self, world: &World) -> DynamicQuery {
+        let components = self
+            .terms
+            .iter()
+            .map(|term| match *term {
+                DynamicTerm::Required { id, mutable } => {
+                    let info = world.get_component_info_by_id(id).clone();
+                    if mutable {
+                        QueryComponentInfo::BorrowedMut(info)
+                    } else {
+                        QueryComponentInfo::Borrowed(info)
+                    }
+                }
+                DynamicTerm::Optional { id, mutable } => {
+                    let info = world.get_component_info_by_id(id).clone();
+                    if mutable {
+                        QueryComponentInfo::OptionBorrowMut(info)
+                    } else {
+                        QueryComponentInfo::OptionBorrow(info)
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        let mutable =
+            self.terms
+                .iter()
+                .map(|term| match *term {
+                    DynamicTerm::Required { mutable, .. }
+                    | DynamicTerm::Optional { mutable, .. } => mutable,
+                })
+                .collect();
+
+        // Entities without the components the change filters look at can never pass them
+        let mut with_ids = self.with;
+        with_ids.extend(self.changed.iter().chain(&self.added).copied());
+
+        let info = QueryInfo {
+            archetypes_generation: RefCell::new(world.archetypes.generation),
+            archetypes: RefCell::new(vec![]),
+            component_ids: components
+                .iter()
+                .map(|component| component.component_info().id)
+                .collect(),
+            components,
+            with_ids,
+            without_ids: self.without,
+            include_disabled: self.include_disabled,
+        };
+        info.recompute_archetypes::<()>(world);
+
+        DynamicQuery {
+            info,
+            mutable,
+            changed: self.changed,
+            added: self.added,
+        }
+    }
+}
+
+/// A query over component ids instead of Rust types. Yields each matched entity along with
+/// pointers to its components, in the order they were added to the builder.
+#[derive(Debug)]
+pub struct DynamicQuery {
+    info: QueryInfo,
+    mutable: Vec<bool>,
+    changed: Vec<ComponentId>,
+    added: Vec<ComponentId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicQueryItem {
+    pub entity: EntityId,
+    /// The fetched components, `None` for optional components the entity does not have. The
+    /// pointers are valid until the world is next changed structurally.
+    pub components: Vec<Option<NonNull<u8>>>,
+}
+
+impl DynamicQuery {
+    pub fn builder() -> DynamicQueryBuilder {
+        DynamicQueryBuilder::new()
+    }
+
+    pub fn over<'w, 'q>(&'q self, ctx: impl IntoSystemRunContext<'w>) -> DynamicQueryIter<'w, 'q> {
+        let ctx = ctx.into_system_run_context();
+        self.info.check_archetypes::<()>(ctx.world);
+        DynamicQueryIter::new(ctx, self)
+    }
+
+    pub fn get<'w>(
+        &self,
+        ctx: impl IntoSystemRunContext<'w>,
+        entity: EntityId,
+    ) -> Option<DynamicQueryItem> {
+        let ctx = ctx.into_system_run_context();
+        self.info.check_archetypes::<()>(ctx.world);
+
+        let meta = ctx.world.entity_meta_opt(entity)?;
+        if !self.info.archetypes.borrow().contains(&meta.archetype_id) {
+            return None;
+        }
+
+        let archetype = ctx.world.archetypes.get_archetype(meta.archetype_id)?;
+        let columns = ArchetypeColumns::new(self, &archetype.borrow());
+        columns.fetch(&ctx, self, entity, meta.table_index)
+    }
+
+    /// Records the components the query reads and writes, for scheduling systems that use it.
+    pub fn add_access(&self, access: &mut SystemAccess) {
+        self.info.add_access(access);
+        for &id in self.changed.iter().chain(&self.added) {
+            access.read_component(id);
+        }
+    }
+}
+
+/// The columns of the archetype that is currently being iterated over.
+struct ArchetypeColumns {
+    components: Vec<Option<Rc<Column>>>,
+    changed: Vec<Option<Rc<Column>>>,
+    added: Vec<Option<Rc<Column>>>,
+}
+
+impl ArchetypeColumns {
+    fn new(query: &DynamicQuery, archetype: &Archetype) -> Self {
+        Self {
+            components: archetype.get_columns(&query.info.component_ids),
+            changed: archetype.get_columns(&query.changed),
+            added: archetype.get_columns(&query.added),
+        }
+    }
+
+    fn fetch(
+        &self,
+        ctx: &SystemRunContext,
+        query: &DynamicQuery,
+        entity: EntityId,
+        index: usize,
+    ) -> Option<DynamicQueryItem> {
+        let is_changed = self.changed.iter().all(|column| {
+            column.as_ref().is_some_and(|column| {
+                column
+                    .get_changed_tick(index)
+                    .is_newer_than(&ctx.last_run_tick)
+            })
+        });
+        let is_added = self.added.iter().all(|column| {
+            column.as_ref().is_some_and(|column| {
+                column
+                    .get_added_tick(index)
+                    .is_newer_than(&ctx.last_run_tick)
+            })
+        });
+        if !is_changed || !is_added {
+            return None;
+        }
+
+        let components = self
+            .components
+            .iter()
+            .zip(&query.mutable)
+            .map(|(column, &mutable)| {
+                column.as_ref().and_then(|column| unsafe {
+                    if mutable {
+                        (&mut (*column.change_detection.get()).changed_ticks)[index] = ctx.tick;
+                    }
+                    (*column.data.get()).get_ptr(index)
+                })
+            })
+            .collect();
+
+        Some(DynamicQueryItem { entity, components })
+    }
+}
+
+pub struct DynamicQueryIter<'w, 'q> {
+    ctx: SystemRunContext<'w>,
+    query: &'q DynamicQuery,
+    archetypes: Vec<ArchetypeId>,
+    archetype_index: usize,
+    entity_index: usize,
+    current_archetype: Option<&'w AtomicRefCell<Archetype>>,
+    columns: Option<ArchetypeColumns>,
+}
+
+impl<'w, 'q> DynamicQueryIter<'w, 'q> {
+    fn new(ctx: SystemRunContext<'w>, query: &'q DynamicQuery) -> Self {
+        let mut iter = Self {
+            ctx,
+            query,
+            archetypes: query.info.archetypes.borrow().clone(),
+            archetype_index: 0,
+            entity_index: 0,
+            current_archetype: None,
+            columns: None,
+        };
+        iter.load_archetype();
+        iter
+    }
+
+    fn load_archetype(&mut self) {
+        self.entity_index = 0;
+        self.current_archetype = self.archetypes.get(self.archetype_index).map(|&id| {
+            self.ctx
+                .world
+                .archetypes
+                .get_archetype(id)
+                .expect("query archetype not found")
+        });
+        self.columns = self
+            .current_archetype
+            .map(|archetype| ArchetypeColumns::new(self.query, &archetype.borrow()));
+    }
+}
+
+impl Iterator for DynamicQueryIter<'_, '_> {
+    type Item = DynamicQueryItem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let archetype = self.current_archetype?.borrow();
+
+            if self.entity_index == archetype.entities.len() {
+                drop(archetype);
+                self.archetype_index += 1;
+                self.load_archetype();
+                continue;
+            }
+
+            let index = self.entity_index;
+            self.entity_index += 1;
+
+            let columns = self.columns.as_ref().expect("columns not loaded");
+            if let Some(item) =
+                columns.fetch(&self.ctx, self.query, archetype.entities[index], index)
+            {
+                return Some(item);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_unordered::assert_eq_unordered;
+
+    use crate::{
+        pointer::change_detection::Tick, systems::SystemRunContext, world::World, EntityId,
+    };
+
+    use super::DynamicQuery;
+
+    fn read_u32(item: &super::DynamicQueryItem, index: usize) -> Option<u32> {
+        item.components[index].map(|ptr| unsafe { *ptr.cast::<u32>().as_ref() })
+    }
+
+    #[test]
+    fn dynamic_query() {
+        let mut world = World::new();
+        let health = world.init_component::<u32>().id;
+        let armor = world.init_component::<u64>().id;
+        let dead = world.init_component::<bool>().id;
+
+        let entity_1 = world.spawn((10u32,));
+        let entity_2 = world.spawn((20u32, 5u64));
+        let entity_3 = world.spawn((30u32, true));
+        world.spawn((1u64,));
+
+        let query = DynamicQuery::builder()
+            .required(health)
+            .optional(armor)
+            .without(dead)
+            .build(&world);
+
+        let items = query
+            .over(&world)
+            .map(|item| {
+                let armor = item.components[1].map(|ptr| unsafe { *ptr.cast::<u64>().as_ref() });
+                (item.entity, read_u32(&item, 0), armor)
+            })
+            .collect::<Vec<_>>();
+        assert_eq_unordered!(
+            items,
+            vec![(entity_1, Some(10), None), (entity_2, Some(20), Some(5))]
+        );
+
+        // New archetypes are picked up
+        world.init_component::<char>();
+        let entity_4 = world.spawn((40u32, 'a'));
+        let entities = query
+            .over(&world)
+            .map(|item| item.entity)
+            .collect::<Vec<EntityId>>();
+        assert_eq_unordered!(entities, vec![entity_1, entity_2, entity_4]);
+
+        assert!(query.get(&world, entity_3).is_none());
+        assert_eq!(read_u32(&query.get(&world, entity_2).unwrap(), 0), Some(20));
+    }
+
+    #[test]
+    fn dynamic_query_change_ticks() {
+        let mut world = World::new();
+        let health = world.init_component::<u32>().id;
+
+        world.set_change_tick(Tick::new(1));
+        let entity_1 = world.spawn((10u32,));
+        let entity_2 = world.spawn((20u32,));
+
+        let changed = DynamicQuery::builder()
+            .required(health)
+            .changed(health)
+            .build(&world);
+        let writer = DynamicQuery::builder().required_mut(health).build(&world);
+
+        let ctx = |world| SystemRunContext {
+            world,
+            tick: Tick::new(3),
+            last_run_tick: Tick::new(2),
+        };
+
+        assert_eq!(changed.over(ctx(&world)).count(), 0);
+
+        // Fetching through a mutable term marks the component as changed
+        let item = writer.get(ctx(&world), entity_2).unwrap();
+        unsafe { *item.components[0].unwrap().cast::<u32>().as_mut() = 25 };
+
+        let items = changed.over(ctx(&world)).collect::<Vec<_>>();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].entity, entity_2);
+        assert_eq!(read_u32(&items[0], 0), Some(25));
+        assert!(changed.get(ctx(&world), entity_1).is_none());
+    }
+}
@@ -1,10 +1,13 @@
-use std::{any::Any, cell::Ref, fmt::Debug, marker::PhantomData, rc::Rc};
+use std::{any::Any, fmt::Debug, marker::PhantomData, rc::Rc};
+
+use atomic_refcell::AtomicRef;
 
 use crate::{
     archetype::{Archetype, Column},
     registry::ComponentId,
-    systems::SystemRunContext,
+    systems::{SystemAccess, SystemRunContext},
     world::World,
+    Disabled,
 };
 
 use super::info::get_full_component_info;
@@ -22,8 +25,8 @@ pub trait QueryFilter {
 
     fn filter_archetype<'a>(
         _world: &World,
-        components: impl Iterator<Item = Ref<'a, Archetype>> + Clone,
-    ) -> impl Iterator<Item = Ref<'a, Archetype>> + Clone {
+        components: impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone,
+    ) -> impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone {
         components
     }
 
@@ -32,6 +35,18 @@ pub trait QueryFilter {
     fn filter_test(_init: &Self::Init, _ctx: &SystemRunContext, _entity_index: usize) -> bool {
         Self::IS_STRICTLY_ARCHETYPAL
     }
+
+    // Filters that look at component data (or its change ticks) need to declare reads of it.
+    fn access(_world: &World, _access: &mut SystemAccess) {}
+
+    // Entities with `Disabled` are skipped unless a filter asks for them.
+    fn includes_disabled() -> bool {
+        false
+    }
+}
+
+fn is_disabled<T: Any>() -> bool {
+    std::any::TypeId::of::<T>() == std::any::TypeId::of::<Disabled>()
 }
 
 impl QueryFilter for () {
@@ -44,8 +59,8 @@ impl QueryFilter for () {
 
     fn filter_archetype<'a>(
         _world: &World,
-        components: impl Iterator<Item = Ref<'a, Archetype>> + Clone,
-    ) -> impl Iterator<Item = Ref<'a, Archetype>> + Clone {
+        components: impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone,
+    ) -> impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone {
         components
     }
 }
@@ -63,8 +78,8 @@ impl QueryFilter for Nothing {
 
     fn filter_archetype<'a>(
         _world: &World,
-        _components: impl Iterator<Item = Ref<'a, Archetype>> + Clone,
-    ) -> impl Iterator<Item = Ref<'a, Archetype>> + Clone {
+        _components: impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone,
+    ) -> impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone {
         std::iter::empty()
     }
 
@@ -90,13 +105,16 @@ where
 
     fn filter_archetype<'a>(
         world: &World,
-        components: impl Iterator<Item = Ref<'a, Archetype>> + Clone,
-    ) -> impl Iterator<Item = Ref<'a, Archetype>> + Clone {
+        components: impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone,
+    ) -> impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone {
         let full = get_full_component_info::<&T>(world);
         let component_info = full.component_info().clone();
 
         components.filter(move |archetype| archetype.components.contains(component_info.id))
     }
+    fn includes_disabled() -> bool {
+        is_disabled::<T>()
+    }
 }
 
 #[derive(Debug)]
@@ -116,8 +134,8 @@ where
 
     fn filter_archetype<'a>(
         world: &World,
-        components: impl Iterator<Item = Ref<'a, Archetype>> + Clone,
-    ) -> impl Iterator<Item = Ref<'a, Archetype>> + Clone {
+        components: impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone,
+    ) -> impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone {
         let full = get_full_component_info::<&T>(world);
         let component_info = full.component_info().clone();
 
@@ -158,8 +176,8 @@ macro_rules! impl_to_filter_info_and {
 
             fn filter_archetype<'a>(
                 world: &World,
-                components: impl Iterator<Item = Ref<'a, Archetype>> + Clone,
-            ) -> impl Iterator<Item = Ref<'a, Archetype>> + Clone {
+                components: impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone,
+            ) -> impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone {
                 expand_filter_archetype!(world, components, $($members),+)
             }
 
@@ -168,6 +186,14 @@ macro_rules! impl_to_filter_info_and {
                 let ($($members,)*) = init;
                 $($members::filter_test($members, _ctx, _entity_index) &&)+ true
             }
+
+            fn access(world: &World, access: &mut SystemAccess) {
+                $($members::access(world, access);)*
+            }
+
+            fn includes_disabled() -> bool {
+                $($members::includes_disabled() ||)+ false
+            }
         }
     }
 }
@@ -269,8 +295,8 @@ where
 
     fn filter_archetype<'a>(
         world: &World,
-        components: impl Iterator<Item = Ref<'a, Archetype>> + Clone,
-    ) -> impl Iterator<Item = Ref<'a, Archetype>> + Clone {
+        components: impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone,
+    ) -> impl Iterator<Item = AtomicRef<'a, Archetype>> + Clone {
         T1::filter_archetype(world, components.clone())
             .chain(T2::filter_archetype(world, components.clone()))
             .chain(T3::filter_archetype(world, components.clone()))
@@ -291,6 +317,45 @@ where
             || T7::filter_test(&init.6, ctx, entity_index)
             || T8::filter_test(&init.7, ctx, entity_index)
     }
+
+    fn access(world: &World, access: &mut SystemAccess) {
+        T1::access(world, access);
+        T2::access(world, access);
+        T3::access(world, access);
+        T4::access(world, access);
+        T5::access(world, access);
+        T6::access(world, access);
+        T7::access(world, access);
+        T8::access(world, access);
+    }
+
+    fn includes_disabled() -> bool {
+        T1::includes_disabled()
+            || T2::includes_disabled()
+            || T3::includes_disabled()
+            || T4::includes_disabled()
+            || T5::includes_disabled()
+            || T6::includes_disabled()
+            || T7::includes_disabled()
+            || T8::includes_disabled()
+    }
+}
+
+/// Matches disabled entities as well as enabled ones, see [`Disabled`].
+#[derive(Debug)]
+pub struct IncludeDisabled;
+
+impl QueryFilter for IncludeDisabled {
+    type Init = ();
+
+    const IS_STRICTLY_ARCHETYPAL: bool = true;
+
+    fn init(_world: &World) -> Self::Init {}
+    fn update_columns(_init: &mut Self::Init, _new_archetype: &Archetype) {}
+
+    fn includes_disabled() -> bool {
+        true
+    }
 }
 
 pub struct Changed<T> {
@@ -334,6 +399,54 @@ impl<T: 'static> QueryFilter for Changed<T> {
             })
             .unwrap_or(false)
     }
+
+    fn access(world: &World, access: &mut SystemAccess) {
+        access.read_component(world.get_component_info::<T>().id);
+    }
+
+    fn includes_disabled() -> bool {
+        is_disabled::<T>()
+    }
+}
+
+pub struct Added<T> {
+    _phantom: PhantomData<T>,
+}
+
+impl<T: 'static> QueryFilter for Added<T> {
+    type Init = ChangeInit;
+
+    const IS_STRICTLY_ARCHETYPAL: bool = false;
+
+    fn init(world: &World) -> Self::Init {
+        ChangeInit {
+            component_id: world.get_component_info::<T>().id,
+            column: None,
+        }
+    }
+
+    fn update_columns(init: &mut Self::Init, new_archetype: &Archetype) {
+        init.column = new_archetype.get_column(init.component_id);
+    }
+
+    fn filter_test(init: &Self::Init, ctx: &SystemRunContext, entity_index: usize) -> bool {
+        init.column
+            .as_ref()
+            .map(|column| {
+                column
+                    .get_added_tick(entity_index)
+                    .is_newer_than(&ctx.last_run_tick)
+            })
+            .unwrap_or(false)
+    }
+
+    fn access(world: &World, access: &mut SystemAccess) {
+        access.read_component(world.get_component_info::<T>().id);
+    }
+
+    fn includes_disabled() -> bool {
+        is_disabled::<T>()
+    }
 }
 
 #[cfg(test)]
@@ -344,9 +457,13 @@ mod test {
     use crate::{
         entity::EntityId,
         pointer::change_detection::Tick,
-        query::filters::{Changed, Or, Without},
+        query::{
+            filters::{Added, Changed, IncludeDisabled, Or, Without},
+            DynamicQuery,
+        },
         systems::SystemRunContext,
         world::World,
+        Disabled,
     };
 
     use super::With;
@@ -513,4 +630,106 @@ mod test {
             &vec![entity1, entity2, entity3]
         );
     }
+
+    #[test]
+    fn added_filter() {
+        let mut world = World::new();
+        world.init_component::<u32>();
+        world.init_component::<bool>();
+
+        let entity1 = world.spawn((42u32,));
+
+        world.set_change_tick(Tick::new(3));
+        let entity2 = world.spawn((12u32,));
+        // Moving the entity to another archetype keeps the added tick of its u32
+        world.insert(entity1, true);
+
+        let query = world.query::<&mut u32, ()>();
+        for mut value in query.over(SystemRunContext {
+            world: &world,
+            tick: Tick::new(4),
+            last_run_tick: Tick::new(0),
+        }) {
+            *value += 1;
+        }
+
+        let added_u32 = world.query::<EntityId, Added<u32>>();
+        assert_eq_unordered!(
+            &added_u32
+                .over(SystemRunContext {
+                    world: &world,
+                    tick: Tick::new(5),
+                    last_run_tick: Tick::new(2),
+                })
+                .collect::<Vec<_>>(),
+            &vec![entity2]
+        );
+
+        let added_bool = world.query::<EntityId, Added<bool>>();
+        assert_eq_unordered!(
+            &added_bool
+                .over(SystemRunContext {
+                    world: &world,
+                    tick: Tick::new(5),
+                    last_run_tick: Tick::new(2),
+                })
+                .collect::<Vec<_>>(),
+            &vec![entity1]
+        );
+
+        let changed_u32 = world.query::<EntityId, Changed<u32>>();
+        assert_eq_unordered!(
+            &changed_u32
+                .over(SystemRunContext {
+                    world: &world,
+                    tick: Tick::new(5),
+                    last_run_tick: Tick::new(3),
+                })
+                .collect::<Vec<_>>(),
+            &vec![entity1, entity2]
+        );
+    }
+
+    #[test]
+    fn disabled_entities() {
+        let mut world = World::new();
+        world.init_component::<u32>();
+
+        let entity1 = world.spawn((1u32,));
+        let entity2 = world.spawn((2u32, Disabled));
+        let entity3 = world.spawn((3u32,));
+        world.insert(entity3, Disabled);
+
+        let query = world.query::<EntityId, With<u32>>();
+        assert_eq!(query.over(&world).collect::<Vec<_>>(), vec![entity1]);
+        assert!(query.get(&world, entity2).is_none());
+
+        let query = world.query::<EntityId, (With<u32>, IncludeDisabled)>();
+        assert_eq_unordered!(
+            query.over(&world).collect::<Vec<_>>(),
+            vec![entity1, entity2, entity3]
+        );
+
+        let query = world.query::<EntityId, With<Disabled>>();
+        assert_eq_unordered!(
+            query.over(&world).collect::<Vec<_>>(),
+            vec![entity2, entity3]
+        );
+
+        let query = world.query::<(EntityId, Option<&Disabled>), ()>();
+        assert_eq!(query.over(&world).count(), 3);
+
+        world.remove::<Disabled>(entity3);
+        let query = world.query::<&u32, ()>();
+        assert_eq_unordered!(query.over(&world).copied().collect::<Vec<_>>(), vec![1, 3]);
+
+        let u32_id = world.get_component_info::<u32>().id;
+        let query = DynamicQuery::builder().required(u32_id).build(&world);
+        assert_eq!(query.over(&world).count(), 2);
+        let query = DynamicQuery::builder()
+            .required(u32_id)
+            .include_disabled()
+            .build(&world);
+        assert_eq!(query.over(&world).count(), 3);
+    }
 }
@@ -5,9 +5,11 @@ use tracing::warn;
 
 use crate::{
     archetype::{Archetype, ArchetypeId},
+    entity::EntityId,
     registry::{ComponentGroup, ComponentId, ComponentInfo},
-    systems::SystemRunContext,
+    systems::{SystemAccess, SystemRunContext},
     world::World,
+    Disabled,
 };
 
 use super::{
@@ -22,13 +24,77 @@ pub struct QueryInfo {
     pub(super) archetypes: RefCell<Vec<ArchetypeId>>,
     pub(super) components: Vec<QueryComponentInfo>,
     pub(super) component_ids: Vec<ComponentId>,
+    // Filters on component ids, for components without a Rust type
+    pub(super) with_ids: Vec<ComponentId>,
+    pub(super) without_ids: Vec<ComponentId>,
+    // Whether entities with `Disabled` are matched even if the query does not mention it
+    pub(super) include_disabled: bool,
 }
 
 impl QueryInfo {
     pub fn recompute_archetypes<F: QueryFilter>(&self, world: &World) {
-        let archetypes = find_archetypes::<F>(world, &self.components);
+        // Queries that fetch or filter on `Disabled` want disabled entities
+        let disabled_id = world
+            .try_get_component_info::<Disabled>()
+            .ok()
+            .map(|info| info.id)
+            .filter(|id| {
+                !self.include_disabled
+                    && !self.component_ids.contains(id)
+                    && !self.with_ids.contains(id)
+            });
+
+        let archetypes = find_archetypes::<F>(world, &self.components)
+            .into_iter()
+            .filter(|&id| {
+                let archetype = world
+                    .archetypes
+                    .get_archetype(id)
+                    .expect("archetype does not exist")
+                    .borrow();
+                self.with_ids
+                    .iter()
+                    .all(|&id| archetype.components.contains(id))
+                    && !self
+                        .without_ids
+                        .iter()
+                        .any(|&id| archetype.components.contains(id))
+                    && !disabled_id.is_some_and(|id| archetype.components.contains(id))
+            })
+            .collect();
         *self.archetypes.borrow_mut() = archetypes;
     }
+
+    /// Recomputes the matching archetypes if new archetypes were created since the last check.
+    pub fn check_archetypes<F: QueryFilter>(&self, world: &World) {
+        if *self.archetypes_generation.borrow() < world.archetypes.generation {
+            self.recompute_archetypes::<F>(world);
+            *self.archetypes_generation.borrow_mut() = world.archetypes.generation;
+        }
+    }
+
+    /// Returns true if the query can change any of the components it fetches.
+    pub fn has_mutable_access(&self) -> bool {
+        self.components.iter().any(|component| {
+            matches!(
+                component,
+                QueryComponentInfo::BorrowedMut(_) | QueryComponentInfo::OptionBorrowMut(_)
+            )
+        })
+    }
+
+    pub fn add_access(&self, access: &mut SystemAccess) {
+        for component in &self.components {
+            match component {
+                QueryComponentInfo::EntityId => {}
+                QueryComponentInfo::Borrowed(info) | QueryComponentInfo::OptionBorrow(info) => {
+                    access.read_component(info.id)
+                }
+                QueryComponentInfo::BorrowedMut(info)
+                | QueryComponentInfo::OptionBorrowMut(info) => access.write_component(info.id),
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -81,6 +147,30 @@ pub trait ToQueryInfo {
     ) -> Self::Output;
 }
 
+/// Queries without mutable access, whose results for the same entity can be alive at the same
+/// time.
+pub trait ReadOnlyQuery: ToQueryInfo {}
+
+impl<T: 'static> ReadOnlyQuery for &T {}
+impl<T: 'static> ReadOnlyQuery for Option<&T> {}
+impl ReadOnlyQuery for EntityId {}
+
+macro_rules! impl_read_only_query {
+    ($($members:ident),+) => {
+        impl<
+            $($members: ReadOnlyQuery + QueryInfoUtils + QueryTransform<InputOrCreate = $members>),*
+        > ReadOnlyQuery for ($($members,)*) {}
+    }
+}
+
+impl_read_only_query!(T1, T2);
+impl_read_only_query!(T1, T2, T3);
+impl_read_only_query!(T1, T2, T3, T4);
+impl_read_only_query!(T1, T2, T3, T4, T5);
+impl_read_only_query!(T1, T2, T3, T4, T5, T6);
+impl_read_only_query!(T1, T2, T3, T4, T5, T6, T7);
+impl_read_only_query!(T1, T2, T3, T4, T5, T6, T7, T8);
+
 pub fn get_full_component_info<T: QueryInfoUtils>(world: &World) -> QueryComponentInfo {
     match (T::BORROW, T::FETCH) {
         (_, QueryFetchType::EntityId) => return QueryComponentInfo::EntityId,
@@ -167,15 +257,20 @@ macro_rules! impl_to_query_info {
 
             fn to_query_info<F: QueryFilter>(world: &World) -> QueryInfo {
                 let components = vec![$(get_full_component_info::<$members>(world),)*];
-                QueryInfo {
+                let info = QueryInfo {
                     archetypes_generation: RefCell::new(world.archetypes.generation),
-                    archetypes: RefCell::new(find_archetypes::<F>(world, &components)),
+                    archetypes: RefCell::new(vec![]),
                     component_ids: components.iter()
                         .filter(|c| c.is_component())
                         .map(|c| c.component_info().id)
                         .collect(),
                     components,
-                }
+                    with_ids: vec![],
+                    without_ids: vec![],
+                    include_disabled: F::includes_disabled(),
+                };
+                info.recompute_archetypes::<F>(world);
+                info
             }
 
             #[inline]
@@ -218,16 +313,21 @@ impl<'w, T1: QueryInfoUtils + QueryTransform<InputOrCreate = T1>> ToQueryInfo fo
 
     fn to_query_info<F: QueryFilter>(world: &World) -> QueryInfo {
         let components = vec![get_full_component_info::<T1>(world)];
-        QueryInfo {
+        let info = QueryInfo {
             archetypes_generation: RefCell::new(world.archetypes.generation),
-            archetypes: RefCell::new(find_archetypes::<F>(world, &components)),
+            archetypes: RefCell::new(vec![]),
             component_ids: components
                 .iter()
                 .filter(|c| c.is_component())
                 .map(|c| c.component_info().id)
                 .collect(),
             components,
-        }
+            with_ids: vec![],
+            without_ids: vec![],
+            include_disabled: F::includes_disabled(),
+        };
+        info.recompute_archetypes::<F>(world);
+        info
     }
 
     #[inline]
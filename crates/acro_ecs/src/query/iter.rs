@@ -1,17 +1,20 @@
-use std::{any::Any, cell::RefCell, rc::Rc};
+use std::{any::Any, rc::Rc};
+
+use atomic_refcell::AtomicRefCell;
 
 use crate::{
     archetype::{Archetype, ArchetypeId, Column},
+    entity::EntityId,
     systems::SystemRunContext,
 };
 
-use super::{filters::QueryFilter, Query, ToQueryInfo};
+use super::{filters::QueryFilter, Query, ReadOnlyQuery, ToQueryInfo};
 
 #[derive(Debug)]
 struct QueryState<'w> {
     pub current_entity_index: usize,
     pub current_archetype_index: usize,
-    pub current_archetype: &'w RefCell<Archetype>,
+    pub current_archetype: &'w AtomicRefCell<Archetype>,
     pub columns: Vec<Option<Rc<Column>>>,
     pub filter_init: Box<dyn Any>,
 }
@@ -65,83 +68,102 @@ where
     }
 }
 
-impl<'w, T, F> Iterator for QueryIter<'w, '_, T, F>
+impl<'w, T, F> QueryIter<'w, '_, T, F>
 where
     T: ToQueryInfo,
     F: QueryFilter + 'static,
 {
-    type Item = <T as ToQueryInfo>::Output;
+    /// Moves to the next entity that passes the filters, returning its index in the current
+    /// archetype.
+    fn advance(&mut self) -> Option<usize> {
+        loop {
+            let archetype_len = self.state.current_archetype.borrow().entities.len();
 
-    #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        let archetype = self.state.current_archetype.borrow();
-
-        // If the current archetype has ended, loop through the next archetype
-        if self.state.current_entity_index == archetype.entities.len() {
-            // If we are at the last archetype, return None to end the iterator
-            // In the case that there are no archetypes for the query to iterate over, this will
-            // also return None
-            if self.state.current_archetype_index
-                == self
-                    .query
-                    .info
+            // If the current archetype has ended, loop through the next archetype
+            if self.state.current_entity_index == archetype_len {
+                // If we are at the last archetype, return None to end the iterator
+                // In the case that there are no archetypes for the query to iterate over, this
+                // will also return None
+                if self.state.current_archetype_index
+                    == self
+                        .query
+                        .info
+                        .archetypes
+                        .borrow()
+                        .len()
+                        .checked_sub(1)
+                        .unwrap_or(0)
+                {
+                    return None;
+                }
+
+                // Move to the next archetype
+                self.state.current_archetype_index += 1;
+                self.state.current_archetype = self
+                    .ctx
+                    .world
                     .archetypes
-                    .borrow()
-                    .len()
-                    .checked_sub(1)
-                    .unwrap_or(0)
-            {
-                return None;
-            }
+                    .get_archetype(
+                        self.query.info.archetypes.borrow()[self.state.current_archetype_index],
+                    )
+                    .expect("query archetype not found");
 
-            // Move to the next archetype
-            self.state.current_archetype_index += 1;
-            self.state.current_archetype = self
-                .ctx
-                .world
-                .archetypes
-                .get_archetype(
-                    self.query.info.archetypes.borrow()[self.state.current_archetype_index],
-                )
-                .expect("query archetype not found");
-
-            // Update the columns information to be from the new archetype
-            self.state.columns = self
-                .state
-                .current_archetype
-                .borrow()
-                .get_columns(&self.query.info.component_ids);
-
-            F::update_columns(
-                &mut self.state.filter_init.downcast_mut().unwrap(),
-                &self.state.current_archetype.borrow(),
-            );
-
-            self.state.current_entity_index = 0;
-
-            return self.next();
-        };
+                // Update the columns information to be from the new archetype
+                self.state.columns = self
+                    .state
+                    .current_archetype
+                    .borrow()
+                    .get_columns(&self.query.info.component_ids);
 
-        let index = self.state.current_entity_index;
-        let current_columns = &self.state.columns;
+                F::update_columns(
+                    &mut self.state.filter_init.downcast_mut().unwrap(),
+                    &self.state.current_archetype.borrow(),
+                );
 
-        let does_filter_pass = if F::IS_STRICTLY_ARCHETYPAL {
-            true
-        } else {
-            let filter_init = self.state.filter_init.downcast_ref::<F::Init>().unwrap();
-            F::filter_test(filter_init, &self.ctx, self.state.current_entity_index)
-        };
+                self.state.current_entity_index = 0;
+                continue;
+            }
 
-        if !does_filter_pass {
+            let index = self.state.current_entity_index;
             self.state.current_entity_index += 1;
-            return self.next();
+
+            let does_filter_pass = if F::IS_STRICTLY_ARCHETYPAL {
+                true
+            } else {
+                let filter_init = self.state.filter_init.downcast_ref::<F::Init>().unwrap();
+                F::filter_test(filter_init, &self.ctx, index)
+            };
+
+            if does_filter_pass {
+                return Some(index);
+            }
         }
+    }
+
+    /// Returns the next matching entity without fetching its components.
+    pub(super) fn next_entity(&mut self) -> Option<EntityId> {
+        let index = self.advance()?;
+        Some(self.state.current_archetype.borrow().entities[index])
+    }
+}
+
+impl<'w, T, F> Iterator for QueryIter<'w, '_, T, F>
+where
+    T: ToQueryInfo,
+    F: QueryFilter + 'static,
+{
+    type Item = <T as ToQueryInfo>::Output;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.advance()?;
+        let current_columns = &self.state.columns;
 
-        let ret = Some(unsafe {
+        Some(unsafe {
             T::from_parts(
                 &self.ctx,
                 &*self.state.current_archetype.borrow(),
-                self.state.current_entity_index,
+                index,
                 self.query
                     .info
                     .component_ids
@@ -156,10 +178,79 @@ where
                         )
                     }),
             )
-        });
+        })
+    }
+}
+
+/// Iterates over every combination of `K` distinct entities matching a query, see
+/// [`Query::iter_combinations`].
+pub struct QueryCombinationIter<'w, 'q, T, F, const K: usize>
+where
+    T: ReadOnlyQuery,
+    F: QueryFilter,
+{
+    ctx: SystemRunContext<'w>,
+    query: &'q Query<T, F>,
+    entities: Vec<EntityId>,
+    indices: Option<[usize; K]>,
+}
+
+impl<'w, 'q, T, F, const K: usize> QueryCombinationIter<'w, 'q, T, F, K>
+where
+    T: ReadOnlyQuery,
+    F: QueryFilter + 'static,
+{
+    pub(super) fn new(ctx: SystemRunContext<'w>, query: &'q Query<T, F>) -> Self {
+        let mut iter = QueryIter::new(ctx.clone(), query);
+        let entities = std::iter::from_fn(|| iter.next_entity()).collect();
 
-        self.state.current_entity_index += 1;
+        Self {
+            ctx,
+            query,
+            entities,
+            indices: None,
+        }
+    }
+
+    /// Moves the indices to the next combination, in lexicographic order.
+    fn advance(&mut self) -> Option<[usize; K]> {
+        let len = self.entities.len();
+        if K == 0 || K > len {
+            return None;
+        }
+
+        let Some(indices) = &mut self.indices else {
+            let first = std::array::from_fn(|i| i);
+            self.indices = Some(first);
+            return Some(first);
+        };
+
+        // Find the last index that can still move forward, and reset the ones after it
+        let i = (0..K).rev().find(|&i| indices[i] < len - K + i)?;
+        indices[i] += 1;
+        for j in i + 1..K {
+            indices[j] = indices[j - 1] + 1;
+        }
+
+        Some(*indices)
+    }
+}
+
+impl<'w, T, F, const K: usize> Iterator for QueryCombinationIter<'w, '_, T, F, K>
+where
+    T: ReadOnlyQuery,
+    F: QueryFilter + 'static,
+{
+    type Item = [<T as ToQueryInfo>::Output; K];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let indices = self.advance()?;
+        let entities = indices.map(|index| self.entities[index]);
 
-        ret
+        Some(
+            self.query
+                .get_many(self.ctx.clone(), entities)
+                .unwrap_or_else(|error| panic!("{error}")),
+        )
     }
 }
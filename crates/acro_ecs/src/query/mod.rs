@@ -1,17 +1,22 @@
+mod dynamic;
 mod filters;
 mod info;
 mod iter;
 mod transform;
 mod utils;
 
-pub use filters::{Changed, Or, QueryFilter, With, Without};
-pub use info::{QueryInfo, ToQueryInfo};
+pub use dynamic::{DynamicQuery, DynamicQueryBuilder, DynamicQueryItem, DynamicQueryIter};
+pub use filters::{Added, Changed, IncludeDisabled, Or, QueryFilter, With, Without};
+pub use info::{QueryInfo, ReadOnlyQuery, ToQueryInfo};
+pub use iter::QueryCombinationIter;
 use tracing::info;
 
 use std::{fmt::Debug, marker::PhantomData, rc::Rc};
 
 use crate::{
     entity::EntityId,
+    error::{QueryGetManyError, QuerySingleError},
+    registry::ComponentId,
     systems::{IntoSystemRunContext, SystemRunContext},
     world::World,
 };
@@ -42,13 +47,31 @@ where
         }
     }
 
+    /// Only matches entities that have the component. Unlike `With<T>`, this works for dynamic
+    /// components, which have no Rust type.
+    pub fn with_id(mut self, world: &World, component_id: ComponentId) -> Self {
+        self.info_mut().with_ids.push(component_id);
+        self.info.recompute_archetypes::<F>(world);
+        self
+    }
+
+    /// Only matches entities that do not have the component, see [`Query::with_id`].
+    pub fn without_id(mut self, world: &World, component_id: ComponentId) -> Self {
+        self.info_mut().without_ids.push(component_id);
+        self.info.recompute_archetypes::<F>(world);
+        self
+    }
+
+    fn info_mut(&mut self) -> &mut QueryInfo {
+        Rc::get_mut(&mut self.info)
+            .expect("query filters can't be changed once the query is shared")
+    }
+
     pub fn check_archetypes(&self, world: &World) {
-        if *self.info.archetypes_generation.borrow() < world.archetypes.generation {
-            self.info.recompute_archetypes::<F>(world);
-            *self.info.archetypes_generation.borrow_mut() = world.archetypes.generation;
-        }
+        self.info.check_archetypes::<F>(world);
     }
 
+    /// Returns the first result of the query, if there is one.
     pub fn get_single<'w>(
         &self,
         ctx: impl IntoSystemRunContext<'w>,
@@ -56,8 +79,23 @@ where
         self.over(ctx).next()
     }
 
+    /// Returns the only result of the query, panicking if there are none or more than one.
     pub fn single<'w>(&self, ctx: impl IntoSystemRunContext<'w>) -> <T as ToQueryInfo>::Output {
-        self.get_single(ctx).expect("query returned no results")
+        self.try_single(ctx)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_single<'w>(
+        &self,
+        ctx: impl IntoSystemRunContext<'w>,
+    ) -> Result<<T as ToQueryInfo>::Output, QuerySingleError> {
+        let mut results = self.over(ctx);
+        let single = results.next().ok_or(QuerySingleError::None)?;
+        if results.next().is_some() {
+            return Err(QuerySingleError::Multiple);
+        }
+
+        Ok(single)
     }
 
     pub fn over<'w, 'q>(&'q self, ctx: impl IntoSystemRunContext<'w>) -> QueryIter<'w, 'q, T, F> {
@@ -66,14 +104,38 @@ where
         QueryIter::new(ctx, self)
     }
 
+    /// Iterates over every combination of `K` different entities matching the query, e.g. every
+    /// pair with `iter_combinations::<2>()`. Each combination is only returned once, regardless
+    /// of order.
+    ///
+    /// Only available for read-only queries, since an entity shows up in several combinations
+    /// and the iterator can't stop their items from being alive at the same time.
+    pub fn iter_combinations<'w, 'q, const K: usize>(
+        &'q self,
+        ctx: impl IntoSystemRunContext<'w>,
+    ) -> QueryCombinationIter<'w, 'q, T, F, K>
+    where
+        T: ReadOnlyQuery,
+    {
+        let ctx = ctx.into_system_run_context();
+        self.check_archetypes(ctx.world);
+        QueryCombinationIter::new(ctx, self)
+    }
+
+    /// Returns the result for an entity, or `None` if it does not exist or its components do not
+    /// match the query.
     pub fn get<'w>(
         &self,
         ctx: impl IntoSystemRunContext<'w>,
         entity_id: EntityId,
     ) -> Option<<T as ToQueryInfo>::Output> {
         let ctx = ctx.into_system_run_context();
-        let entity_meta = ctx.world.entity_meta(entity_id);
+        self.check_archetypes(ctx.world);
+        let entity_meta = ctx.world.entity_meta_opt(entity_id)?;
         let archetype_id = entity_meta.archetype_id;
+        if !self.info.archetypes.borrow().contains(&archetype_id) {
+            return None;
+        }
         ctx.world
             .archetypes
             .get_archetype(archetype_id)
@@ -106,12 +168,47 @@ where
                 ret
             })
     }
+
+    /// Returns the results for several entities at once. Fails if any of them does not match the
+    /// query, or if the query has mutable access and an entity is listed more than once.
+    pub fn get_many<'w, const N: usize>(
+        &self,
+        ctx: impl IntoSystemRunContext<'w>,
+        entities: [EntityId; N],
+    ) -> Result<[<T as ToQueryInfo>::Output; N], QueryGetManyError> {
+        if self.info.has_mutable_access() {
+            for (i, entity) in entities.iter().enumerate() {
+                if entities[..i].contains(entity) {
+                    return Err(QueryGetManyError::AliasedMutability(*entity));
+                }
+            }
+        }
+
+        let ctx = ctx.into_system_run_context();
+        let results = entities
+            .iter()
+            .map(|&entity| {
+                self.get(ctx.clone(), entity)
+                    .ok_or(QueryGetManyError::NoMatch(entity))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match results.try_into() {
+            Ok(results) => Ok(results),
+            Err(_) => unreachable!("one result per entity"),
+        }
+    }
 }
 #[cfg(test)]
 mod tests {
     use assert_unordered::assert_eq_unordered;
 
-    use crate::{entity::EntityId, query::info::QueryComponentInfo, world::World};
+    use crate::{
+        entity::EntityId,
+        error::{QueryGetManyError, QuerySingleError},
+        query::{info::QueryComponentInfo, Without},
+        world::World,
+    };
 
     #[test]
     fn query() {
@@ -192,6 +289,90 @@ mod tests {
         assert_eq_unordered!(data1, vec![(entity1, &42u32), (entity2, &12u32)]);
     }
 
+    #[test]
+    fn query_get_many() {
+        let mut world = World::new();
+        world.init_component::<u32>();
+        world.init_component::<bool>();
+
+        let entity1 = world.spawn((1u32, true));
+        let entity2 = world.spawn((2u32,));
+        let entity3 = world.spawn((true,));
+
+        let query = world.query::<&mut u32, ()>();
+        let [mut a, mut b] = query.get_many(&world, [entity1, entity2]).unwrap();
+        std::mem::swap(&mut *a, &mut *b);
+        assert_eq!(world.get::<u32>(entity1), Some(&2));
+        assert_eq!(world.get::<u32>(entity2), Some(&1));
+
+        assert!(matches!(
+            query.get_many(&world, [entity1, entity1]),
+            Err(QueryGetManyError::AliasedMutability(entity)) if entity == entity1
+        ));
+        assert!(matches!(
+            query.get_many(&world, [entity1, entity3]),
+            Err(QueryGetManyError::NoMatch(entity)) if entity == entity3
+        ));
+        assert!(query.get(&world, entity3).is_none());
+
+        // Listing an entity twice is fine without mutable access
+        let query = world.query::<&u32, ()>();
+        assert_eq!(query.get_many(&world, [entity1, entity1]), Ok([&2, &2]));
+    }
+
+    #[test]
+    fn query_iter_combinations() {
+        let mut world = World::new();
+        world.init_component::<u32>();
+        world.init_component::<bool>();
+
+        for i in 0..4u32 {
+            world.spawn((i,));
+        }
+        world.spawn((4u32, true));
+
+        let query = world.query::<&u32, ()>();
+        let pairs = query
+            .iter_combinations::<2>(&world)
+            .map(|[a, b]| (*a.min(b), *a.max(b)))
+            .collect::<Vec<_>>();
+        assert_eq!(pairs.len(), 10);
+        assert_eq_unordered!(
+            pairs,
+            (0..5)
+                .flat_map(|a| (a + 1..5).map(move |b| (a, b)))
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(query.iter_combinations::<5>(&world).count(), 1);
+        assert_eq!(query.iter_combinations::<6>(&world).count(), 0);
+
+        let query = world.query::<&u32, Without<bool>>();
+        assert_eq!(query.iter_combinations::<2>(&world).count(), 6);
+    }
+
+    #[test]
+    fn query_single() {
+        let mut world = World::new();
+        world.init_component::<u32>();
+        world.init_component::<bool>();
+
+        let query = world.query::<&u32, ()>();
+        assert_eq!(query.try_single(&world), Err(QuerySingleError::None));
+
+        world.spawn((1u32, true));
+        assert_eq!(query.try_single(&world), Ok(&1));
+
+        world.spawn((2u32,));
+        assert_eq!(query.try_single(&world), Err(QuerySingleError::Multiple));
+        assert!(query.get_single(&world).is_some());
+
+        // Despawned entities are not found instead of panicking
+        let entity = world.spawn((3u32,));
+        world.despawn(entity);
+        assert_eq!(query.get(&world, entity), None);
+    }
+
     #[test]
     fn query_with_options() {
         let mut world = World::new();
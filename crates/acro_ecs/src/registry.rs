@@ -8,25 +8,77 @@ use std::{
 use fnv::FnvHashMap;
 use serde::{Deserialize, Serialize};
 
-use crate::{storage::anyvec::Dropper, Name};
+use crate::{
+    component::{Component, StorageType},
+    entity::EntityId,
+    storage::anyvec::Dropper,
+    world::World,
+    Name,
+};
 
 #[derive(Debug, Default)]
 pub struct ComponentRegistry {
     current_id: u32,
     native_components: FnvHashMap<TypeId, ComponentId>,
+    dynamic_components: FnvHashMap<String, ComponentId>,
     components: FnvHashMap<ComponentId, ComponentInfo>,
+    hooks: FnvHashMap<ComponentId, ComponentHooks>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ComponentId(pub u32);
 
+pub type ComponentHook = fn(&mut World, EntityId);
+
+/// Functions called when a component is added to or removed from an entity, so resources tied to
+/// the component (GPU buffers, script instances) can be created and freed.
+///
+/// Hooks get full access to the world, but should not add or remove the component they are called
+/// for on the same entity. Queue those changes with `Commands` instead.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ComponentHooks {
+    pub(crate) on_add: Option<ComponentHook>,
+    pub(crate) on_insert: Option<ComponentHook>,
+    pub(crate) on_remove: Option<ComponentHook>,
+}
+
+impl ComponentHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Called after the component is inserted on an entity that did not have it.
+    pub fn on_add(mut self, hook: ComponentHook) -> Self {
+        self.on_add = Some(hook);
+        self
+    }
+
+    /// Called after every insert, including ones replacing an existing value. Runs after `on_add`.
+    pub fn on_insert(mut self, hook: ComponentHook) -> Self {
+        self.on_insert = Some(hook);
+        self
+    }
+
+    /// Called before the component is removed or its entity is despawned, while it can still be
+    /// read. Not called when the value is replaced by another insert.
+    pub fn on_remove(mut self, hook: ComponentHook) -> Self {
+        self.on_remove = Some(hook);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.on_add.is_none() && self.on_insert.is_none() && self.on_remove.is_none()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ComponentInfo {
     pub id: ComponentId,
     pub component_type: ComponentType,
+    pub storage: StorageType,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone)]
 pub enum ComponentType {
     Native {
         name: String,
@@ -34,6 +86,80 @@ pub enum ComponentType {
         type_id: TypeId,
         dropper: Dropper,
     },
+    /// A component defined at runtime (e.g. by a script) with no Rust type backing it. Its data
+    /// is only accessible as raw bytes.
+    Dynamic {
+        name: String,
+        layout: Layout,
+        dropper: Dropper,
+    },
+}
+
+// The dropper is left out on purpose, function pointers to the same function aren't guaranteed
+// to compare equal.
+impl PartialEq for ComponentType {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                ComponentType::Native {
+                    name,
+                    layout,
+                    type_id,
+                    ..
+                },
+                ComponentType::Native {
+                    name: other_name,
+                    layout: other_layout,
+                    type_id: other_type_id,
+                    ..
+                },
+            ) => name == other_name && layout == other_layout && type_id == other_type_id,
+            (
+                ComponentType::Dynamic { name, layout, .. },
+                ComponentType::Dynamic {
+                    name: other_name,
+                    layout: other_layout,
+                    ..
+                },
+            ) => name == other_name && layout == other_layout,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ComponentType {}
+
+impl Hash for ComponentType {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        self.name().hash(state);
+        self.layout().hash(state);
+        if let ComponentType::Native { type_id, .. } = self {
+            type_id.hash(state);
+        }
+    }
+}
+
+impl ComponentType {
+    pub fn name(&self) -> &str {
+        match self {
+            ComponentType::Native { name, .. } | ComponentType::Dynamic { name, .. } => name,
+        }
+    }
+
+    pub fn layout(&self) -> Layout {
+        match self {
+            ComponentType::Native { layout, .. } | ComponentType::Dynamic { layout, .. } => *layout,
+        }
+    }
+
+    pub fn dropper(&self) -> Dropper {
+        match self {
+            ComponentType::Native { dropper, .. } | ComponentType::Dynamic { dropper, .. } => {
+                *dropper
+            }
+        }
+    }
 }
 
 impl ComponentRegistry {
@@ -41,10 +167,12 @@ impl ComponentRegistry {
         let mut registry = Self {
             current_id: 0,
             native_components: HashMap::default(),
+            dynamic_components: HashMap::default(),
             components: HashMap::default(),
+            hooks: HashMap::default(),
         };
 
-        registry.init_rust_type::<Name>();
+        registry.register::<Name>();
 
         registry
     }
@@ -65,7 +193,13 @@ impl ComponentRegistry {
             .and_then(|id| self.components.get(id))
     }
 
-    pub fn init_rust_type<T: 'static>(&mut self) -> &ComponentInfo {
+    /// Registers a Rust type as a component along with the metadata from its `Component` impl.
+    /// Registering the same type again returns the existing id.
+    pub fn register<T: Component>(&mut self) -> ComponentId {
+        if let Some(&id) = self.native_components.get(&TypeId::of::<T>()) {
+            return id;
+        }
+
         let id = self.next_id();
 
         self.components.insert(
@@ -80,14 +214,59 @@ impl ComponentRegistry {
                         std::ptr::drop_in_place(ptr.as_ptr() as *mut T);
                     }),
                 },
+                storage: T::STORAGE,
             },
         );
-
         self.native_components.insert(TypeId::of::<T>(), id);
 
-        self.components
-            .get(&id)
-            .expect("unable to find component info")
+        let hooks = T::hooks();
+        if !hooks.is_empty() {
+            self.hooks.insert(id, hooks);
+        }
+
+        id
+    }
+
+    /// Registers a component that is defined at runtime. Registering the same name again returns
+    /// the existing id, as long as the layout matches.
+    pub fn register_dynamic(
+        &mut self,
+        name: impl ToString,
+        layout: Layout,
+        dropper: Dropper,
+    ) -> ComponentId {
+        let name = name.to_string();
+        if let Some(info) = self.get_dynamic(&name) {
+            assert_eq!(
+                info.component_type.layout(),
+                layout,
+                "dynamic component {name} was already registered with a different layout"
+            );
+            return info.id;
+        }
+
+        let id = self.next_id();
+        self.components.insert(
+            id,
+            ComponentInfo {
+                id,
+                component_type: ComponentType::Dynamic {
+                    name: name.clone(),
+                    layout,
+                    dropper,
+                },
+                storage: StorageType::Table,
+            },
+        );
+        self.dynamic_components.insert(name, id);
+
+        id
+    }
+
+    pub fn get_dynamic(&self, name: &str) -> Option<&ComponentInfo> {
+        self.dynamic_components
+            .get(name)
+            .and_then(|id| self.components.get(id))
     }
 
     /// Get the component info for a given component id, panicking if the component is not found
@@ -102,6 +281,20 @@ impl ComponentRegistry {
         self.components.get(&component_id)
     }
 
+    /// Replaces the hooks of a component.
+    pub fn set_hooks(&mut self, component_id: ComponentId, hooks: ComponentHooks) {
+        assert!(
+            self.components.contains_key(&component_id),
+            "component not found"
+        );
+        self.hooks.insert(component_id, hooks);
+    }
+
+    #[inline]
+    pub fn hooks(&self, component_id: ComponentId) -> ComponentHooks {
+        self.hooks.get(&component_id).copied().unwrap_or_default()
+    }
+
     pub fn create_group(&self, component_ids: HashSet<ComponentId>) -> ComponentGroup {
         let data = self
             .components
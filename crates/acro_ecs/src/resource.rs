@@ -1,18 +1,22 @@
 use std::{
     any::{Any, TypeId},
-    cell::{Ref, RefCell, RefMut},
     ops::{Deref, DerefMut},
 };
 
-use fnv::FnvHashMap;
+use atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
+use fnv::{FnvHashMap, FnvHashSet};
+
+use crate::error::ResourceMissing;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ResourceId(pub usize);
 
 #[derive(Debug)]
 pub struct ResourceRegistry {
-    data: Vec<RefCell<Box<dyn Any>>>,
+    data: Vec<AtomicRefCell<Box<dyn Any>>>,
     types: FnvHashMap<TypeId, ResourceId>,
+    // Resources that can only be accessed from the main thread
+    non_send: FnvHashSet<TypeId>,
 }
 
 impl ResourceRegistry {
@@ -20,47 +24,70 @@ impl ResourceRegistry {
         Self {
             data: vec![],
             types: FnvHashMap::default(),
+            non_send: FnvHashSet::default(),
         }
     }
 
     pub fn insert<T: 'static>(&mut self, resource: T) -> ResourceId {
         let id = ResourceId(self.data.len());
-        self.data.push(RefCell::new(Box::new(resource)));
+        self.data.push(AtomicRefCell::new(Box::new(resource)));
         self.types.insert(TypeId::of::<T>(), id);
         id
     }
 
-    pub fn get<T: 'static>(&self) -> Res<T> {
-        Res {
-            inner: Ref::map(
-                self.data[self
-                    .types
-                    .get(&TypeId::of::<T>())
-                    .unwrap_or_else(|| panic!("resource {} not found", std::any::type_name::<T>()))
-                    .0]
-                    .borrow(),
-                |r| r.downcast_ref().unwrap(),
-            ),
-        }
+    /// Inserts a resource that is not safe to access from other threads. Systems that use it are
+    /// always run on the main thread.
+    pub fn insert_non_send<T: 'static>(&mut self, resource: T) -> ResourceId {
+        self.non_send.insert(TypeId::of::<T>());
+        self.insert(resource)
     }
 
-    pub fn get_mut<T: 'static>(&self) -> ResMut<T> {
-        ResMut {
-            inner: RefMut::map(
-                self.data[self
-                    .types
-                    .get(&TypeId::of::<T>())
-                    .unwrap_or_else(|| panic!("resource {} not found", std::any::type_name::<T>()))
-                    .0]
-                    .borrow_mut(),
-                |r| r.downcast_mut().unwrap(),
-            ),
-        }
+    pub fn is_non_send(&self, id: TypeId) -> bool {
+        self.non_send.contains(&id)
+    }
+
+    pub fn non_send_count(&self) -> usize {
+        self.non_send.len()
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.types.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn get<T: 'static>(&self) -> Res<'_, T> {
+        self.try_get::<T>()
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn get_mut<T: 'static>(&self) -> ResMut<'_, T> {
+        self.try_get_mut::<T>()
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_get<T: 'static>(&self) -> Result<Res<'_, T>, ResourceMissing> {
+        let id = self.id::<T>()?;
+        Ok(Res {
+            inner: AtomicRef::map(self.data[id.0].borrow(), |r| r.downcast_ref().unwrap()),
+        })
+    }
+
+    pub fn try_get_mut<T: 'static>(&self) -> Result<ResMut<'_, T>, ResourceMissing> {
+        let id = self.id::<T>()?;
+        Ok(ResMut {
+            inner: AtomicRefMut::map(self.data[id.0].borrow_mut(), |r| r.downcast_mut().unwrap()),
+        })
+    }
+
+    fn id<T: 'static>(&self) -> Result<ResourceId, ResourceMissing> {
+        self.types
+            .get(&TypeId::of::<T>())
+            .copied()
+            .ok_or_else(ResourceMissing::of::<T>)
     }
 }
 
 pub struct Res<'b, T> {
-    pub(crate) inner: Ref<'b, T>,
+    pub(crate) inner: AtomicRef<'b, T>,
 }
 
 impl<T> Deref for Res<'_, T> {
@@ -72,7 +99,7 @@ impl<T> Deref for Res<'_, T> {
 }
 
 pub struct ResMut<'b, T> {
-    inner: RefMut<'b, T>,
+    inner: AtomicRefMut<'b, T>,
 }
 
 impl<T> Deref for ResMut<'_, T> {
@@ -0,0 +1,279 @@
+use std::{
+    cell::RefMut,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    application::Application,
+    plugin::Plugin,
+    time::{FixedTimestep, ManualClock},
+    world::World,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum RunMode {
+    /// Runs forever, waiting between frames so they start at most `frames_per_second` times a
+    /// second. Runs as fast as possible when `None`.
+    Loop { frames_per_second: Option<f64> },
+    /// Runs the given number of frames and returns.
+    Ticks(u64),
+}
+
+/// Runs the application without a window, for servers and tests.
+#[derive(Debug, Clone, Copy)]
+pub struct ScheduleRunnerPlugin {
+    pub mode: RunMode,
+}
+
+impl ScheduleRunnerPlugin {
+    pub fn run_loop(frames_per_second: f64) -> Self {
+        Self {
+            mode: RunMode::Loop {
+                frames_per_second: Some(frames_per_second),
+            },
+        }
+    }
+
+    pub fn run_ticks(ticks: u64) -> Self {
+        Self {
+            mode: RunMode::Ticks(ticks),
+        }
+    }
+}
+
+impl Plugin for ScheduleRunnerPlugin {
+    fn build(&mut self, app: &mut Application) {
+        let mode = self.mode;
+
+        app.set_runner(move |mut app| match mode {
+            RunMode::Loop { frames_per_second } => {
+                let frame_time = frames_per_second.map(|fps| Duration::from_secs_f64(1.0 / fps));
+
+                loop {
+                    let start = Instant::now();
+                    app.run_once();
+
+                    if let Some(remaining) =
+                        frame_time.and_then(|frame_time| frame_time.checked_sub(start.elapsed()))
+                    {
+                        std::thread::sleep(remaining);
+                    }
+                }
+            }
+            RunMode::Ticks(ticks) => {
+                for _ in 0..ticks {
+                    app.run_once();
+                }
+            }
+        });
+    }
+}
+
+/// Drives an application frame by frame with a manual clock, so tests can step the schedule
+/// deterministically and inspect the world in between.
+pub struct HeadlessRunner {
+    app: Application,
+    clock: ManualClock,
+    frame_time: Duration,
+}
+
+impl HeadlessRunner {
+    /// Takes over the application's clock. Each step advances it by the fixed timestep, so every
+    /// step runs `Stage::FixedUpdate` exactly once unless the frame time is changed.
+    pub fn new(mut app: Application) -> Self {
+        app.finish_plugins();
+
+        let clock = ManualClock::new();
+        app.set_clock(clock.clone());
+
+        let frame_time = {
+            let world = app.world();
+            if world.resources().contains::<FixedTimestep>() {
+                world.resource::<FixedTimestep>().timestep()
+            } else {
+                FixedTimestep::default().timestep()
+            }
+        };
+
+        Self {
+            app,
+            clock,
+            frame_time,
+        }
+    }
+
+    pub fn with_frame_time(mut self, frame_time: Duration) -> Self {
+        self.frame_time = frame_time;
+        self
+    }
+
+    pub fn frame_time(&self) -> Duration {
+        self.frame_time
+    }
+
+    pub fn clock(&self) -> &ManualClock {
+        &self.clock
+    }
+
+    pub fn app(&mut self) -> &mut Application {
+        &mut self.app
+    }
+
+    pub fn world(&self) -> RefMut<'_, World> {
+        self.app.world()
+    }
+
+    /// Advances the clock by one frame and runs the schedule once.
+    pub fn step(&mut self) {
+        self.clock.advance(self.frame_time);
+        self.app.run_once();
+    }
+
+    pub fn step_n(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.step();
+        }
+    }
+
+    /// Advances the clock by `delta` and runs the schedule once, for frames of unusual length.
+    pub fn step_by(&mut self, delta: Duration) {
+        self.clock.advance(delta);
+        self.app.run_once();
+    }
+
+    pub fn into_app(self) -> Application {
+        self.app
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        schedule::Stage, Application, Changed, EntityId, Query, ResMut, SystemRunContext, Time,
+    };
+
+    use super::{HeadlessRunner, ScheduleRunnerPlugin};
+
+    #[derive(Debug, Default)]
+    struct Steps {
+        update: u32,
+        fixed_update: u32,
+        render: u32,
+    }
+
+    fn counting_app() -> Application {
+        let mut app = Application::new();
+        app.insert_resource(Steps::default())
+            .add_system(
+                Stage::Update,
+                [],
+                |_ctx: SystemRunContext, mut steps: ResMut<Steps>| {
+                    steps.update += 1;
+                },
+            )
+            .add_system(
+                Stage::FixedUpdate,
+                [],
+                |_ctx: SystemRunContext, mut steps: ResMut<Steps>| {
+                    steps.fixed_update += 1;
+                },
+            )
+            .add_system(
+                Stage::Render,
+                [],
+                |_ctx: SystemRunContext, mut steps: ResMut<Steps>| {
+                    steps.render += 1;
+                },
+            );
+        app
+    }
+
+    #[test]
+    fn step_frames() {
+        let mut runner = HeadlessRunner::new(counting_app());
+
+        runner.step_n(10);
+        {
+            let world = runner.world();
+            let steps = world.resource::<Steps>();
+            assert_eq!(steps.update, 10);
+            assert_eq!(steps.fixed_update, 10);
+            assert_eq!(steps.render, 10);
+            assert_eq!(world.resource::<Time>().frame_count(), 10);
+            assert_eq!(world.resource::<Time>().elapsed(), runner.frame_time() * 10);
+        }
+
+        // A long frame catches up on the missed fixed steps
+        runner.step_by(runner.frame_time() * 3);
+        // A short frame does not run a fixed step
+        runner.step_by(Duration::ZERO);
+
+        let world = runner.world();
+        let steps = world.resource::<Steps>();
+        assert_eq!(steps.update, 12);
+        assert_eq!(steps.fixed_update, 13);
+    }
+
+    #[test]
+    fn render_once_per_step() {
+        let mut runner = HeadlessRunner::new(counting_app());
+
+        for frame in 1..=5 {
+            runner.step();
+            assert_eq!(runner.world().resource::<Steps>().render, frame);
+        }
+
+        // Frames shorter than the render interval skip rendering until enough time has passed
+        let half_frame = runner.frame_time() / 2;
+        runner.step_by(half_frame);
+        assert_eq!(runner.world().resource::<Steps>().render, 5);
+        runner.step_by(runner.frame_time() - half_frame);
+        assert_eq!(runner.world().resource::<Steps>().render, 6);
+    }
+
+    #[derive(Debug, Default)]
+    struct SeenChanges(Vec<EntityId>);
+
+    #[test]
+    fn changes_between_steps() {
+        let mut app = Application::new();
+        app.init_component::<u32>()
+            .insert_resource(SeenChanges::default())
+            .add_system(
+                Stage::Update,
+                [],
+                |ctx: SystemRunContext,
+                 changed: Query<EntityId, Changed<u32>>,
+                 mut seen: ResMut<SeenChanges>| {
+                    seen.0.extend(changed.over(&ctx));
+                },
+            );
+
+        let mut runner = HeadlessRunner::new(app);
+        let entity = {
+            let mut world = runner.world();
+            world.spawn((1u32,))
+        };
+        runner.step_n(2);
+        assert_eq!(runner.world().resource::<SeenChanges>().0, vec![entity]);
+
+        // Edits made from outside the schedule show up in the next frame
+        *runner.world().get_mut::<u32>(entity).unwrap() = 2;
+        runner.step();
+        assert_eq!(
+            runner.world().resource::<SeenChanges>().0,
+            vec![entity, entity]
+        );
+    }
+
+    #[test]
+    fn run_ticks() {
+        let app = counting_app().add_plugin(ScheduleRunnerPlugin::run_ticks(5));
+        let world = app.get_world_handle();
+        app.run();
+
+        assert_eq!(world.borrow().resource::<Steps>().update, 5);
+    }
+}
@@ -1,21 +1,32 @@
-use std::cell::RefCell;
+use std::{any::TypeId, cell::RefCell, sync::Mutex};
 
 use chrono::{DateTime, TimeDelta, Utc};
 use fnv::FnvHashMap;
-use tracing::{error, info, warn};
+use tracing::{error, info, info_span, warn};
+use web_time::Instant;
 
 use crate::{
+    condition::RunCondition,
     pointer::change_detection::Tick,
+    profiling::{FrameStats, SystemRun},
+    state::{State, StateSystems, StateTransition, StateTransitionSystems},
     systems::{SystemData, SystemId, SystemRunContext},
+    time::{Clock, FixedTimestep, SystemClock, Time},
     utils::TimeDeltaExt,
     world::World,
 };
 
-// TODO: Make a more sophisticated (multithreaded) scheduling system
 #[derive(Debug)]
 pub struct Schedule {
     pub current_tick: Tick,
     pub stages: FnvHashMap<Stage, Vec<SystemData>>,
+    // Cached groups of systems (indices into `stages`) that can run at the same time
+    batches: FnvHashMap<Stage, StageBatches>,
+    parallel: bool,
+    // OnEnter/OnExit systems of every state type, in the order the states were added
+    state_transitions: Vec<(TypeId, Box<dyn StateTransitionSystems>)>,
+    clock: Box<dyn Clock>,
+    last_run: Option<DateTime<Utc>>,
     last_render_run: DateTime<Utc>,
     render_interval: TimeDelta,
     time_accumulator: TimeDelta,
@@ -33,124 +44,291 @@ pub enum Stage {
     PostRender,
 }
 
+/// A named group of systems that other systems can order themselves against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SystemSet(pub &'static str);
+
 #[derive(Debug)]
 pub enum SystemSchedulingRequirement {
     RunBefore(SystemId),
     RunAfter(SystemId),
+    InSet(SystemSet),
+    RunBeforeSet(SystemSet),
+    RunAfterSet(SystemSet),
+    /// Skips the system whenever the condition fails.
+    RunIf(RunCondition),
+    /// Runs the system on the main thread, with no other systems running at the same time. Systems
+    /// that take a `SystemRunContext` always run like this.
+    RunOnMainThread,
+}
+
+#[derive(Debug)]
+struct StageBatches {
+    // Batches depend on which resources are main thread only, so they are recomputed when
+    // non-Send resources are inserted.
+    non_send_count: usize,
+    batches: Vec<Vec<usize>>,
+}
+
+/// Moves pointers to systems and the world into the thread pool.
+struct SendPtr<T>(T);
+
+// SAFETY: Only batches of more than one system are sent, and those only contain systems whose
+// access is not exclusive. Such systems are built from `Send + Sync` functions with `Send`
+// parameter state, and only reach the world through `ParallelSystemParam`s, which stick to the
+// thread-safe parts of it. Their declared accesses don't conflict, so they never alias.
+unsafe impl<T> Send for SendPtr<T> {}
+
+impl<T> SendPtr<T> {
+    // Taking `self` makes closures capture the whole wrapper instead of the non-Send field
+    fn into_inner(self) -> T {
+        self.0
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum ScheduleError {
     ContradictorySchedulingRequirements,
+    StateNotAdded,
 }
 
 impl Schedule {
     pub fn new() -> Self {
-        // TODO: Make this configurable
-        const FRAMES_PER_SECOND: f64 = 60.0;
+        const DEFAULT_FRAMES_PER_SECOND: f64 = 60.0;
 
         Self {
             current_tick: Tick::new(1),
             stages: Default::default(),
+            batches: Default::default(),
+            parallel: false,
+            state_transitions: vec![],
+            clock: Box::new(SystemClock),
+            last_run: None,
             last_render_run: Utc::now(),
-            render_interval: TimeDelta::from_std(std::time::Duration::from_secs_f64(
-                1.0 / FRAMES_PER_SECOND,
-            ))
-            .expect("invalid render interval"),
+            render_interval: render_interval(DEFAULT_FRAMES_PER_SECOND),
             time_accumulator: TimeDelta::zero(),
             time_count: 0,
         }
     }
 
-    pub fn add_system(&mut self, stage: Stage, system: SystemData) -> Result<(), ScheduleError> {
-        let stage_data = self.stages.entry(stage).or_default();
+    /// Limits how often the render stages run. A limit of zero or less (or NaN) renders on every
+    /// run. Does not affect the fixed timestep, which is configured through the `FixedTimestep`
+    /// resource.
+    pub fn set_frame_rate_limit(&mut self, frames_per_second: f64) {
+        self.render_interval = render_interval(frames_per_second);
+    }
 
-        // Find the last system that should run before this one
-        let lower_index = system
-            .scheduling_requirements
-            .iter()
-            .filter_map(|req| match req {
-                SystemSchedulingRequirement::RunAfter(id) => Some(id),
-                _ => None,
-            })
-            .map(|id| stage_data.iter().position(|s| s.id == *id))
-            .max()
-            .flatten();
+    /// Replaces the clock that frame times are measured with. Time is counted from when the clock
+    /// is set, so the next frame's delta is however far the clock moved in between.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        let now = clock.now();
+        self.clock = Box::new(clock);
+        self.last_run = Some(now);
+        self.last_render_run = now;
+    }
 
-        // Find the first system that should run after this one
-        let upper_index = system
-            .scheduling_requirements
-            .iter()
-            .filter_map(|req| match req {
-                SystemSchedulingRequirement::RunBefore(id) => Some(id),
-                _ => None,
-            })
-            .map(|id| stage_data.iter().position(|s| s.id == *id))
-            .min()
-            .flatten();
-
-        let index = match (lower_index, upper_index) {
-            (Some(lower), Some(upper)) => {
-                if lower > upper {
-                    return Err(ScheduleError::ContradictorySchedulingRequirements);
-                }
+    /// Enables or disables running non-conflicting systems on a thread pool. Off by default, and
+    /// has no effect on the web, where threads are not available.
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel && cfg!(not(target_arch = "wasm32"));
+    }
 
-                lower + 1
-            }
-            (Some(lower), None) => lower + 1,
-            (None, Some(upper)) => upper,
-            (None, None) => {
-                stage_data.push(system);
-                return Ok(());
-            }
-        };
+    pub fn add_system(&mut self, stage: Stage, system: SystemData) -> Result<(), ScheduleError> {
+        self.batches.remove(&stage);
+        insert_ordered(self.stages.entry(stage).or_default(), system)
+    }
+
+    /// Starts handling transitions of the state `S`. The `States<S>` and `NextState<S>` resources
+    /// must be in the world before the schedule runs.
+    pub fn add_state<S: State>(&mut self) {
+        let type_id = TypeId::of::<S>();
+        if !self.state_transitions.iter().any(|(id, _)| *id == type_id) {
+            self.state_transitions
+                .push((type_id, Box::new(StateSystems::<S>::default())));
+        }
+    }
 
-        stage_data.insert(index, system);
+    pub fn add_state_system<S: State>(
+        &mut self,
+        transition: StateTransition<S>,
+        system: SystemData,
+    ) -> Result<(), ScheduleError> {
+        let (_, state_systems) = self
+            .state_transitions
+            .iter_mut()
+            .find(|(id, _)| *id == TypeId::of::<S>())
+            .ok_or(ScheduleError::StateNotAdded)?;
 
-        Ok(())
+        state_systems
+            .as_any_mut()
+            .downcast_mut::<StateSystems<S>>()
+            .expect("state systems have the wrong type")
+            .add_system(transition, system)
+    }
+
+    /// Switches every state with a pending `NextState`, running its OnExit and OnEnter systems.
+    pub fn apply_state_transitions(&mut self, world: &RefCell<World>) {
+        for (_, state_systems) in &mut self.state_transitions {
+            state_systems.apply(world, &mut self.current_tick);
+        }
     }
 
     pub fn run_stage(&mut self, stage: Stage, world: &RefCell<World>) {
-        let systems = match self.stages.get_mut(&stage) {
+        let Self {
+            stages,
+            batches,
+            current_tick,
+            parallel,
+            ..
+        } = self;
+
+        let systems = match stages.get_mut(&stage) {
             Some(systems) => systems,
             None => return,
         };
 
-        for system in systems.iter_mut() {
-            self.current_tick = self.current_tick.next();
+        let _span = info_span!("stage", ?stage).entered();
+        let stage_start = Instant::now();
 
-            let result = (system.run)(
-                SystemRunContext {
-                    world: &*world.borrow(),
-                    tick: self.current_tick,
-                    last_run_tick: system.last_run_tick,
-                },
-                system.parameters.as_mut(),
-            );
+        let run_serially;
+        let batches = if *parallel {
+            let non_send_count = world.borrow().resources.non_send_count();
+            let stage_batches = batches.entry(stage).or_insert_with(|| StageBatches {
+                non_send_count: usize::MAX,
+                batches: vec![],
+            });
+            if stage_batches.non_send_count != non_send_count {
+                stage_batches.batches = compute_batches(systems, &world.borrow());
+                stage_batches.non_send_count = non_send_count;
+            }
+
+            &stage_batches.batches
+        } else {
+            run_serially = (0..systems.len()).map(|index| vec![index]).collect();
+            &run_serially
+        };
 
-            world.borrow_mut().check_swap();
+        for batch in batches {
+            let runs = {
+                let world = world.borrow();
 
-            match result {
-                Ok(()) => {}
-                Err(err) => {
-                    error!("system {} failed: {:?}", system.name, err);
+                let batch = batch
+                    .iter()
+                    .copied()
+                    .filter(|&index| should_run(&mut systems[index], &world))
+                    .collect::<Vec<_>>();
+                if batch.is_empty() {
+                    continue;
                 }
-            }
 
-            system.last_run_tick = self.current_tick;
+                if let [index] = batch[..] {
+                    *current_tick = current_tick.next();
+                    vec![(
+                        index,
+                        run_system(&mut systems[index], &world, *current_tick),
+                    )]
+                } else {
+                    assert!(
+                        batch
+                            .iter()
+                            .all(|&index| !systems[index].access.is_exclusive()),
+                        "exclusive system batched with other systems"
+                    );
 
-            // world.borrow().archetypes.print_debug();
+                    let world_ptr = &*world as *const World;
+                    let tasks = systems
+                        .iter_mut()
+                        .enumerate()
+                        .filter(|(index, _)| batch.contains(index))
+                        .map(|(index, system)| {
+                            *current_tick = current_tick.next();
+                            SendPtr((index, system as *mut SystemData, world_ptr, *current_tick))
+                        })
+                        .collect::<Vec<_>>();
+
+                    let runs = Mutex::new(Vec::with_capacity(tasks.len()));
+                    let run_task = |task: SendPtr<(usize, *mut SystemData, *const World, Tick)>| {
+                        let (index, system, world, tick) = task.into_inner();
+                        let run = unsafe { run_system(&mut *system, &*world, tick) };
+                        runs.lock().unwrap().push((index, run));
+                    };
+
+                    rayon::in_place_scope(|scope| {
+                        let mut tasks = tasks.into_iter();
+                        let first = tasks.next();
+                        for task in tasks {
+                            let run_task = &run_task;
+                            scope.spawn(move |_| run_task(task));
+                        }
+
+                        // Run one of the systems here instead of only waiting for the others
+                        if let Some(task) = first {
+                            run_task(task);
+                        }
+                    });
+                    runs.into_inner().unwrap()
+                }
+            };
+
+            let mut world = world.borrow_mut();
+            if let Ok(mut stats) = world.try_resource_mut::<FrameStats>() {
+                for (index, run) in runs {
+                    let system = &systems[index];
+                    stats.record_system(system, Some(stage), run);
+                }
+            }
+            world.set_change_tick(*current_tick);
+            world.apply_commands();
+            world.check_swap();
+        }
+
+        if let Ok(mut stats) = world.borrow().try_resource_mut::<FrameStats>() {
+            stats.record_stage(stage, stage_start);
         }
     }
 
     pub fn run_once(&mut self, world: &RefCell<World>) {
-        let start = chrono::Utc::now();
+        let start = self.clock.now();
         let time_since_last_render = start.signed_duration_since(self.last_render_run);
-        let should_render = time_since_last_render > self.render_interval;
+        let should_render = time_since_last_render >= self.render_interval;
+
+        let delta = self
+            .last_run
+            .map(|last_run| start.signed_duration_since(last_run))
+            .and_then(|delta| delta.to_std().ok())
+            .unwrap_or_default();
+        self.last_run = Some(start);
+
+        {
+            let mut world = world.borrow_mut();
+            world.archetypes.update_removed_components();
+
+            if !world.resources().contains::<FixedTimestep>() {
+                world.insert_resource(FixedTimestep::default());
+            }
+            if !world.resources().contains::<Time>() {
+                world.insert_resource(Time::default());
+            }
+            if !world.resources().contains::<FrameStats>() {
+                world.insert_resource(FrameStats::default());
+            }
+            world.resource_mut::<FrameStats>().begin_frame();
+        }
+
+        let fixed_steps = {
+            let world = world.borrow();
+            let mut time = world.resource_mut::<Time>();
+            let mut fixed_timestep = world.resource_mut::<FixedTimestep>();
+            time.update(delta);
+            time.set_fixed_delta(fixed_timestep.timestep());
+            // Fixed steps follow the virtual clock, so they stop while paused
+            fixed_timestep.expend(time.delta())
+        };
 
         self.run_stage(Stage::PreUpdate, world);
+        self.apply_state_transitions(world);
         self.run_stage(Stage::Update, world);
-        if should_render {
+        for _ in 0..fixed_steps {
             self.run_stage(Stage::FixedUpdate, world);
         }
         self.run_stage(Stage::PostUpdate, world);
@@ -162,7 +340,7 @@ impl Schedule {
             self.run_stage(Stage::Render, world);
             self.run_stage(Stage::PostRender, world);
 
-            let elapsed = Utc::now().signed_duration_since(start);
+            let elapsed = self.clock.now().signed_duration_since(start);
 
             self.time_accumulator += elapsed;
             self.time_count += 1;
@@ -186,14 +364,29 @@ impl Schedule {
                 self.time_count = 0;
             }
 
-            if elapsed > self.render_interval {
+            if !self.render_interval.is_zero() && elapsed > self.render_interval {
+                let slowest = world
+                    .borrow()
+                    .resource::<FrameStats>()
+                    .slowest_in_frame(3)
+                    .into_iter()
+                    .map(|(name, duration)| format!("{name} ({duration:?})"))
+                    .collect::<Vec<_>>();
                 warn!(
-                    "frame took too long: {elapsed} (target = {render_interval})",
+                    "frame took too long: {elapsed} (target = {render_interval}), slowest systems: {slowest}",
                     elapsed = elapsed.pretty(),
-                    render_interval = self.render_interval.pretty()
+                    render_interval = self.render_interval.pretty(),
+                    slowest = slowest.join(", ")
                 );
             }
         }
+
+        world.borrow().resource_mut::<FrameStats>().end_frame();
+
+        // Changes made between frames need a tick newer than every system's last run, otherwise
+        // the last system to run would never see them
+        self.current_tick = self.current_tick.next();
+        world.borrow_mut().set_change_tick(self.current_tick);
     }
 
     pub fn get_systems(&self, stage: Stage) -> &[SystemData] {
@@ -203,26 +396,222 @@ impl Schedule {
     }
 }
 
+fn render_interval(frames_per_second: f64) -> TimeDelta {
+    if frames_per_second.is_nan() || frames_per_second <= 0.0 {
+        return TimeDelta::zero();
+    }
+
+    TimeDelta::from_std(std::time::Duration::from_secs_f64(1.0 / frames_per_second))
+        .expect("invalid render interval")
+}
+
+/// Inserts a system into a list of systems, respecting its ordering requirements.
+pub(crate) fn insert_ordered(
+    systems: &mut Vec<SystemData>,
+    system: SystemData,
+) -> Result<(), ScheduleError> {
+    // Find the last system that should run before this one
+    let lower_index = systems
+        .iter()
+        .rposition(|other| must_run_after(&system, other));
+
+    // Find the first system that should run after this one
+    let upper_index = systems
+        .iter()
+        .position(|other| must_run_after(other, &system));
+
+    let index = match (lower_index, upper_index) {
+        (Some(lower), Some(upper)) => {
+            if lower >= upper {
+                return Err(ScheduleError::ContradictorySchedulingRequirements);
+            }
+
+            lower + 1
+        }
+        (Some(lower), None) => lower + 1,
+        (None, Some(upper)) => upper,
+        (None, None) => {
+            systems.push(system);
+            return Ok(());
+        }
+    };
+
+    systems.insert(index, system);
+
+    Ok(())
+}
+
+/// Runs the systems one after another on the current thread, applying commands after each one.
+pub(crate) fn run_systems_serially(
+    systems: &mut [SystemData],
+    world: &RefCell<World>,
+    current_tick: &mut Tick,
+) {
+    for system in systems {
+        let run = {
+            let world = world.borrow();
+            if !should_run(system, &world) {
+                continue;
+            }
+
+            *current_tick = current_tick.next();
+            run_system(system, &world, *current_tick)
+        };
+
+        let mut world = world.borrow_mut();
+        if let Ok(mut stats) = world.try_resource_mut::<FrameStats>() {
+            stats.record_system(system, None, run);
+        }
+        world.set_change_tick(*current_tick);
+        world.apply_commands();
+        world.check_swap();
+    }
+}
+
+fn run_system(system: &mut SystemData, world: &World, tick: Tick) -> SystemRun {
+    let _span = info_span!("system", name = system.name.as_str()).entered();
+    let start = Instant::now();
+
+    let result = (system.run)(
+        SystemRunContext {
+            world,
+            tick,
+            last_run_tick: system.last_run_tick,
+        },
+        system.parameters.as_mut(),
+    );
+
+    if let Err(err) = result {
+        error!("system {} failed: {:?}", system.name, err);
+    }
+
+    system.last_run_tick = tick;
+
+    SystemRun {
+        start,
+        duration: start.elapsed(),
+        thread: rayon::current_thread_index().map_or(0, |index| index + 1),
+    }
+}
+
+fn runs_on_main_thread(system: &SystemData, world: &World) -> bool {
+    system.access.is_main_thread(world)
+        || system
+            .scheduling_requirements
+            .iter()
+            .any(|req| matches!(req, SystemSchedulingRequirement::RunOnMainThread))
+}
+
+fn is_in_set(system: &SystemData, set: SystemSet) -> bool {
+    system
+        .scheduling_requirements
+        .iter()
+        .any(|req| matches!(req, SystemSchedulingRequirement::InSet(s) if *s == set))
+}
+
+/// Returns true if either system requires `system` to run after `other`.
+fn must_run_after(system: &SystemData, other: &SystemData) -> bool {
+    let after = system.scheduling_requirements.iter().any(|req| match req {
+        SystemSchedulingRequirement::RunAfter(id) => *id == other.id,
+        SystemSchedulingRequirement::RunAfterSet(set) => is_in_set(other, *set),
+        _ => false,
+    });
+    let before = other.scheduling_requirements.iter().any(|req| match req {
+        SystemSchedulingRequirement::RunBefore(id) => *id == system.id,
+        SystemSchedulingRequirement::RunBeforeSet(set) => is_in_set(system, *set),
+        _ => false,
+    });
+
+    after || before
+}
+
+/// Evaluates all of the system's run conditions, returning true if they all pass.
+fn should_run(system: &mut SystemData, world: &World) -> bool {
+    system
+        .scheduling_requirements
+        .iter_mut()
+        .fold(true, |should_run, req| match req {
+            // Every condition is checked so stateful conditions stay in sync
+            SystemSchedulingRequirement::RunIf(condition) => condition.check(world) && should_run,
+            _ => should_run,
+        })
+}
+
+/// Groups the systems of a stage into batches that run one after another, where all systems in a
+/// batch can run at the same time. A system is placed in a later batch than every system before
+/// it in the stage that it conflicts with or has an ordering requirement with.
+fn compute_batches(systems: &[SystemData], world: &World) -> Vec<Vec<usize>> {
+    let main_thread = systems
+        .iter()
+        .map(|system| runs_on_main_thread(system, world))
+        .collect::<Vec<_>>();
+
+    let mut levels = Vec::<usize>::with_capacity(systems.len());
+    for (index, system) in systems.iter().enumerate() {
+        let level = systems[..index]
+            .iter()
+            .enumerate()
+            .filter(|&(before_index, before)| {
+                main_thread[before_index]
+                    || main_thread[index]
+                    || before.access.is_deferred()
+                    || !before.access.is_compatible(&system.access)
+                    || must_run_after(before, system)
+                    || must_run_after(system, before)
+            })
+            .map(|(before_index, _)| levels[before_index] + 1)
+            .max()
+            .unwrap_or(0);
+
+        levels.push(level);
+    }
+
+    let mut batches = vec![vec![]; levels.iter().max().map_or(0, |level| level + 1)];
+    for (index, level) in levels.into_iter().enumerate() {
+        batches[level].push(index);
+    }
+
+    batches
+}
+
 #[cfg(test)]
 mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        thread::ThreadId,
+        time::{Duration, Instant},
+    };
+
+    use chrono::TimeDelta;
+
     use crate::{
+        condition::RunCondition,
         pointer::change_detection::Tick,
-        systems::{SystemData, SystemId},
+        systems::{SystemAccess, SystemData, SystemId, SystemIndex},
+        Application, Res, ResMut, SystemRunContext, World,
     };
 
-    use super::{Schedule, Stage, SystemSchedulingRequirement};
+    use super::{
+        compute_batches, render_interval, Schedule, Stage, SystemSchedulingRequirement, SystemSet,
+    };
 
     fn create_faux_system(
         id: usize,
         name: impl ToString,
         scheduling_requirements: impl IntoIterator<Item = SystemSchedulingRequirement>,
     ) -> SystemData {
+        // Faux systems don't touch the world, so they can be batched like parameter-only systems
+        let mut access = SystemAccess::default();
+        access.set_parameters_only();
+
         SystemData {
             id: SystemId::Faux(id),
+            index: SystemIndex::next(),
             name: name.to_string(),
             run: Box::new(move |_, _| Ok(())),
             last_run_tick: Tick::new(0),
             parameters: Box::new(()),
+            access,
             scheduling_requirements: scheduling_requirements.into_iter().collect(),
         }
     }
@@ -302,4 +691,378 @@ mod tests {
             )
             .expect_err("add system 7 should have failed because of contradictory requirements");
     }
+
+    #[test]
+    fn schedule_system_sets() {
+        use SystemId as S;
+        use SystemSchedulingRequirement::*;
+
+        const INPUT: SystemSet = SystemSet("input");
+        const PHYSICS: SystemSet = SystemSet("physics");
+
+        let mut schedule = Schedule::new();
+        schedule
+            .add_system(Stage::Update, create_faux_system(1, "unrelated", []))
+            .expect("add system 1 failed");
+        schedule
+            .add_system(
+                Stage::Update,
+                create_faux_system(2, "integrate", [InSet(PHYSICS), RunAfterSet(INPUT)]),
+            )
+            .expect("add system 2 failed");
+        // Joins the set after a system was ordered against it
+        schedule
+            .add_system(
+                Stage::Update,
+                create_faux_system(3, "keyboard", [InSet(INPUT)]),
+            )
+            .expect("add system 3 failed");
+        schedule
+            .add_system(
+                Stage::Update,
+                create_faux_system(4, "camera", [RunAfterSet(PHYSICS)]),
+            )
+            .expect("add system 4 failed");
+        schedule
+            .add_system(
+                Stage::Update,
+                create_faux_system(5, "late input", [RunAfterSet(PHYSICS), RunBeforeSet(INPUT)]),
+            )
+            .expect_err("add system 5 should have failed because physics runs after input");
+
+        assert_eq!(
+            get_system_ids(schedule.get_systems(Stage::Update)),
+            &[S::Faux(1), S::Faux(3), S::Faux(2), S::Faux(4)]
+        );
+    }
+
+    #[test]
+    fn run_conditions() {
+        use SystemSchedulingRequirement::*;
+
+        let mut app = Application::new();
+        app.insert_resource(Sums::default());
+
+        app.add_system(
+            Stage::Update,
+            [RunIf(RunCondition::resource_exists::<Counter>())],
+            |_ctx: SystemRunContext, mut counter: ResMut<Counter>| {
+                counter.0 += 1;
+            },
+        )
+        .add_system(
+            Stage::Update,
+            [RunIf(RunCondition::every_n_ticks(2))],
+            |_ctx: SystemRunContext, mut sums: ResMut<Sums>| {
+                sums.0.push(0);
+            },
+        );
+
+        app.run_once();
+        app.run_once();
+        app.insert_resource(Counter::default());
+        app.run_once();
+        app.run_once();
+        app.run_once();
+
+        let world = app.world();
+        assert_eq!(world.resource::<Counter>().0, 3);
+        assert_eq!(world.resource::<Sums>().0.len(), 3);
+    }
+
+    #[test]
+    fn unlimited_frame_rate() {
+        for frames_per_second in [0.0, -30.0, f64::NAN] {
+            assert_eq!(render_interval(frames_per_second), TimeDelta::zero());
+        }
+        assert_eq!(render_interval(4.0), TimeDelta::milliseconds(250));
+
+        let mut schedule = Schedule::new();
+        schedule.set_frame_rate_limit(0.0);
+        assert_eq!(schedule.render_interval, TimeDelta::zero());
+    }
+
+    fn with_access(mut system: SystemData, build: impl FnOnce(&mut SystemAccess)) -> SystemData {
+        build(&mut system.access);
+        system
+    }
+
+    #[test]
+    fn batch_systems() {
+        use SystemSchedulingRequirement::*;
+
+        let mut world = World::new();
+        let number = world.init_component::<u32>().id;
+        let flag = world.init_component::<bool>().id;
+
+        let systems = [
+            with_access(create_faux_system(1, "read number", []), |access| {
+                access.read_component(number)
+            }),
+            with_access(create_faux_system(2, "write flag", []), |access| {
+                access.write_component(flag)
+            }),
+            with_access(create_faux_system(3, "write number", []), |access| {
+                access.write_component(number)
+            }),
+            create_faux_system(4, "main thread", [RunOnMainThread]),
+            with_access(create_faux_system(5, "read flag", []), |access| {
+                access.read_component(flag)
+            }),
+            with_access(create_faux_system(6, "commands", []), |access| {
+                access.set_deferred()
+            }),
+            create_faux_system(7, "after commands", []),
+            create_faux_system(8, "ordered", [RunAfter(SystemId::Faux(7))]),
+        ];
+
+        assert_eq!(
+            compute_batches(&systems, &world),
+            vec![vec![0, 1], vec![2], vec![3], vec![4, 5], vec![6], vec![7]]
+        );
+    }
+
+    #[test]
+    fn batch_exclusive_systems() {
+        let world = World::new();
+
+        let mut exclusive = create_faux_system(2, "exclusive", []);
+        exclusive.access = SystemAccess::default();
+
+        let systems = [
+            create_faux_system(1, "one", []),
+            exclusive,
+            create_faux_system(3, "three", []),
+            create_faux_system(4, "four", []),
+        ];
+
+        assert_eq!(
+            compute_batches(&systems, &world),
+            vec![vec![0], vec![1], vec![2, 3]]
+        );
+    }
+
+    #[derive(Debug, Default)]
+    struct Counter(u32);
+
+    #[derive(Debug, Default)]
+    struct OtherCounter(u32);
+
+    #[derive(Debug, Default)]
+    struct Sums(Vec<u32>);
+
+    #[derive(Debug)]
+    struct MainThread(Option<ThreadId>);
+
+    #[test]
+    fn run_systems_in_parallel() {
+        let mut app = Application::new();
+        app.set_parallel_execution(true)
+            .insert_resource(Counter::default())
+            .insert_resource(OtherCounter::default())
+            .insert_resource(Sums::default())
+            .insert_non_send_resource(MainThread(None));
+
+        app.add_system(Stage::Update, [], |mut counter: ResMut<Counter>| {
+            counter.0 += 1;
+        })
+        .add_system(Stage::Update, [], |mut counter: ResMut<OtherCounter>| {
+            counter.0 += 2;
+        })
+        .add_system(
+            Stage::Update,
+            [],
+            |counter: Res<Counter>, other_counter: Res<OtherCounter>, mut sums: ResMut<Sums>| {
+                sums.0.push(counter.0 + other_counter.0);
+            },
+        )
+        .add_system(
+            Stage::Update,
+            [],
+            |_ctx: SystemRunContext, mut main_thread: ResMut<MainThread>| {
+                main_thread.0 = Some(std::thread::current().id());
+            },
+        );
+
+        for _ in 0..3 {
+            app.run_once();
+        }
+
+        let world = app.world();
+        assert_eq!(world.resource::<Sums>().0, vec![3, 6, 9]);
+        assert_eq!(
+            world.resource::<MainThread>().0,
+            Some(std::thread::current().id())
+        );
+    }
+
+    #[derive(Debug, Default)]
+    struct Rendezvous {
+        arrived: AtomicUsize,
+        met: AtomicUsize,
+    }
+
+    impl Rendezvous {
+        // Waits (for a while) until both systems are running at the same time
+        fn meet(&self) {
+            self.arrived.fetch_add(1, Ordering::SeqCst);
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while Instant::now() < deadline {
+                if self.arrived.load(Ordering::SeqCst) == 2 {
+                    self.met.fetch_add(1, Ordering::SeqCst);
+                    return;
+                }
+                std::thread::yield_now();
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_systems_overlap() {
+        let mut app = Application::new();
+        app.set_parallel_execution(true)
+            .insert_resource(Counter::default())
+            .insert_resource(OtherCounter::default())
+            .insert_resource(Rendezvous::default());
+
+        app.add_system(
+            Stage::Update,
+            [],
+            |rendezvous: Res<Rendezvous>, mut counter: ResMut<Counter>| {
+                rendezvous.meet();
+                counter.0 += 1;
+            },
+        )
+        .add_system(
+            Stage::Update,
+            [],
+            |rendezvous: Res<Rendezvous>, mut counter: ResMut<OtherCounter>| {
+                rendezvous.meet();
+                counter.0 += 1;
+            },
+        );
+
+        app.run_once();
+
+        let world = app.world();
+        assert_eq!(world.resource::<Rendezvous>().met.load(Ordering::SeqCst), 2);
+        assert_eq!(world.resource::<Counter>().0, 1);
+        assert_eq!(world.resource::<OtherCounter>().0, 1);
+    }
+
+    #[test]
+    fn context_systems_run_alone() {
+        let mut app = Application::new();
+        app.set_parallel_execution(true)
+            .insert_resource(Counter::default())
+            .insert_resource(OtherCounter::default());
+
+        // Neither system declares a conflicting parameter, but both reach into the world through
+        // the context
+        app.add_system(Stage::Update, [], |ctx: SystemRunContext| {
+            ctx.world.resource_mut::<Counter>().0 += 1;
+            ctx.world.resource_mut::<OtherCounter>().0 += 1;
+        })
+        .add_system(Stage::Update, [], |ctx: SystemRunContext| {
+            ctx.world.resource_mut::<OtherCounter>().0 *= 10;
+        });
+
+        app.run_once();
+
+        let world = app.world();
+        assert_eq!(world.resource::<Counter>().0, 1);
+        assert_eq!(world.resource::<OtherCounter>().0, 10);
+    }
+
+    #[test]
+    fn frame_stats() {
+        use crate::FrameStats;
+
+        fn slow_system(_ctx: SystemRunContext, mut counter: ResMut<Counter>) {
+            std::thread::sleep(Duration::from_millis(2));
+            counter.0 += 1;
+        }
+
+        let mut app = Application::new();
+        app.set_parallel_execution(true)
+            .insert_resource(Counter::default())
+            .insert_resource(OtherCounter::default())
+            .insert_resource(FrameStats::default())
+            .with_resource::<FrameStats>(|mut stats| stats.start_trace())
+            .add_system(Stage::Update, [], slow_system)
+            .add_system(
+                Stage::Update,
+                [],
+                |_ctx: SystemRunContext, mut counter: ResMut<OtherCounter>| {
+                    counter.0 += 1;
+                },
+            );
+
+        for _ in 0..3 {
+            app.run_once();
+        }
+
+        let world = app.world();
+        let mut stats = world.resource_mut::<FrameStats>();
+        assert_eq!(stats.frame().count(), 3);
+        assert_eq!(
+            stats.stage(Stage::Update).map(|stage| stage.count()),
+            Some(3)
+        );
+        assert!(stats.stage(Stage::Render).is_none());
+
+        let slow = stats
+            .systems_with_id(SystemId::Native(std::any::Any::type_id(&slow_system)))
+            .next()
+            .expect("system should have stats");
+        assert_eq!(slow.stage, Some(Stage::Update));
+        assert_eq!(slow.timing.count(), 3);
+        assert!(slow.timing.min() >= Duration::from_millis(2));
+        assert_eq!(stats.slowest_systems(1)[0].name, slow.name);
+        assert_eq!(stats.slowest_in_frame(1)[0].0, slow.name);
+
+        // 3 frames, 3 stage runs and 6 system runs
+        let trace = stats.stop_trace().expect("trace should be recording");
+        assert_eq!(trace.len(), 12);
+        assert!(trace.to_json().starts_with(r#"{"traceEvents":[{"#));
+        assert!(!stats.is_tracing());
+    }
+
+    #[test]
+    fn frame_stats_per_system() {
+        use crate::FrameStats;
+
+        fn count(mut counter: ResMut<Counter>) {
+            counter.0 += 1;
+        }
+
+        let mut app = Application::new();
+        app.insert_resource(Counter::default())
+            .insert_resource(FrameStats::default())
+            .add_system(Stage::Update, [], count)
+            .add_system(Stage::Update, [], count)
+            .add_system(Stage::PostUpdate, [], count);
+
+        app.run_once();
+
+        let world = app.world();
+        assert_eq!(world.resource::<Counter>().0, 3);
+
+        // The same function added three times is three systems
+        let stats = world.resource::<FrameStats>();
+        let id = SystemId::Native(std::any::Any::type_id(&count));
+        let systems = stats.systems_with_id(id).collect::<Vec<_>>();
+        assert_eq!(systems.len(), 3);
+        assert!(systems.iter().all(|system| system.timing.count() == 1));
+        let update = systems
+            .iter()
+            .filter(|system| system.stage == Some(Stage::Update))
+            .collect::<Vec<_>>();
+        assert_eq!(update.len(), 2);
+        assert_ne!(update[0].index, update[1].index);
+        assert_eq!(
+            stats.system(update[0].index).map(|system| system.stage),
+            Some(Some(Stage::Update))
+        );
+    }
 }
@@ -0,0 +1,267 @@
+use std::{any::Any, cell::RefCell, fmt::Debug};
+
+use crate::{
+    condition::RunCondition,
+    pointer::change_detection::Tick,
+    schedule::{insert_ordered, run_systems_serially, ScheduleError},
+    systems::SystemData,
+    world::World,
+};
+
+/// A value that can be used as an application state, usually a fieldless enum.
+pub trait State: Debug + Clone + PartialEq + 'static {}
+
+impl<T: Debug + Clone + PartialEq + 'static> State for T {}
+
+/// The current value of the state `S`. Changed through [`NextState`], transitions are applied
+/// once per frame after `Stage::PreUpdate`.
+#[derive(Debug)]
+pub struct States<S: State> {
+    current: S,
+    previous: Option<S>,
+}
+
+impl<S: State> States<S> {
+    pub fn new(initial: S) -> Self {
+        Self {
+            current: initial,
+            previous: None,
+        }
+    }
+
+    pub fn get(&self) -> &S {
+        &self.current
+    }
+
+    /// The state before the last transition.
+    pub fn previous(&self) -> Option<&S> {
+        self.previous.as_ref()
+    }
+}
+
+/// The state to switch to on the next transition. Setting it more than once in a frame only keeps
+/// the last value.
+#[derive(Debug)]
+pub struct NextState<S: State> {
+    next: Option<S>,
+}
+
+impl<S: State> Default for NextState<S> {
+    fn default() -> Self {
+        Self { next: None }
+    }
+}
+
+impl<S: State> NextState<S> {
+    pub fn set(&mut self, state: S) {
+        self.next = Some(state);
+    }
+
+    pub fn pending(&self) -> Option<&S> {
+        self.next.as_ref()
+    }
+}
+
+/// Systems added with this run once when the state is entered.
+#[derive(Debug, Clone)]
+pub struct OnEnter<S>(pub S);
+
+/// Systems added with this run once when the state is exited.
+#[derive(Debug, Clone)]
+pub struct OnExit<S>(pub S);
+
+#[derive(Debug, Clone)]
+pub enum StateTransition<S> {
+    Enter(S),
+    Exit(S),
+}
+
+impl<S> From<OnEnter<S>> for StateTransition<S> {
+    fn from(on_enter: OnEnter<S>) -> Self {
+        StateTransition::Enter(on_enter.0)
+    }
+}
+
+impl<S> From<OnExit<S>> for StateTransition<S> {
+    fn from(on_exit: OnExit<S>) -> Self {
+        StateTransition::Exit(on_exit.0)
+    }
+}
+
+/// Passes while the state `S` is equal to `state`.
+pub fn in_state<S: State>(state: S) -> RunCondition {
+    RunCondition::new(format!("in_state({state:?})"), move |world| {
+        world.resources().contains::<States<S>>() && *world.resource::<States<S>>().get() == state
+    })
+}
+
+/// Type erased [`StateSystems`], so the schedule can hold the systems of every state type.
+pub(crate) trait StateTransitionSystems: Debug {
+    fn apply(&mut self, world: &RefCell<World>, current_tick: &mut Tick);
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// The OnEnter and OnExit systems of a single state type.
+#[derive(Debug)]
+pub(crate) struct StateSystems<S: State> {
+    entered_initial: bool,
+    on_enter: Vec<(S, Vec<SystemData>)>,
+    on_exit: Vec<(S, Vec<SystemData>)>,
+}
+
+impl<S: State> Default for StateSystems<S> {
+    fn default() -> Self {
+        Self {
+            entered_initial: false,
+            on_enter: vec![],
+            on_exit: vec![],
+        }
+    }
+}
+
+impl<S: State> StateSystems<S> {
+    pub fn add_system(
+        &mut self,
+        transition: StateTransition<S>,
+        system: SystemData,
+    ) -> Result<(), ScheduleError> {
+        let (collections, state) = match transition {
+            StateTransition::Enter(state) => (&mut self.on_enter, state),
+            StateTransition::Exit(state) => (&mut self.on_exit, state),
+        };
+
+        let index = match collections.iter().position(|(s, _)| *s == state) {
+            Some(index) => index,
+            None => {
+                collections.push((state, vec![]));
+                collections.len() - 1
+            }
+        };
+
+        insert_ordered(&mut collections[index].1, system)
+    }
+
+    fn run(
+        collections: &mut [(S, Vec<SystemData>)],
+        state: &S,
+        world: &RefCell<World>,
+        current_tick: &mut Tick,
+    ) {
+        if let Some((_, systems)) = collections.iter_mut().find(|(s, _)| s == state) {
+            run_systems_serially(systems, world, current_tick);
+        }
+    }
+}
+
+impl<S: State> StateTransitionSystems for StateSystems<S> {
+    fn apply(&mut self, world: &RefCell<World>, current_tick: &mut Tick) {
+        if !self.entered_initial {
+            self.entered_initial = true;
+            let initial = world.borrow().resource::<States<S>>().get().clone();
+            Self::run(&mut self.on_enter, &initial, world, current_tick);
+        }
+
+        let Some(next) = world.borrow().resource_mut::<NextState<S>>().next.take() else {
+            return;
+        };
+
+        let previous = {
+            let world = world.borrow();
+            let mut states = world.resource_mut::<States<S>>();
+            if states.current == next {
+                return;
+            }
+
+            let previous = std::mem::replace(&mut states.current, next.clone());
+            states.previous = Some(previous.clone());
+            previous
+        };
+
+        Self::run(&mut self.on_exit, &previous, world, current_tick);
+        Self::run(&mut self.on_enter, &next, world, current_tick);
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        schedule::{Stage, SystemSchedulingRequirement},
+        Application, Commands, ResMut, SystemRunContext,
+    };
+
+    use super::{in_state, NextState, OnEnter, OnExit, States};
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum GameState {
+        Menu,
+        Playing,
+        Paused,
+    }
+
+    #[derive(Debug, Default)]
+    struct Log(Vec<String>);
+
+    fn log(message: &'static str) -> impl Fn(SystemRunContext, ResMut<Log>) {
+        move |_ctx, mut log| log.0.push(message.to_string())
+    }
+
+    #[test]
+    fn transitions() {
+        let mut app = Application::new();
+        app.init_component::<u32>()
+            .insert_resource(Log::default())
+            .add_state(GameState::Menu)
+            .add_state_system(OnEnter(GameState::Menu), [], log("enter menu"))
+            .add_state_system(OnExit(GameState::Menu), [], log("exit menu"))
+            .add_state_system(
+                OnEnter(GameState::Playing),
+                [],
+                |_ctx: SystemRunContext, mut commands: Commands| {
+                    commands.spawn((1u32,));
+                },
+            )
+            .add_state_system(OnEnter(GameState::Playing), [], log("enter playing"))
+            .add_state_system(OnExit(GameState::Playing), [], log("exit playing"))
+            .add_system(
+                Stage::Update,
+                [SystemSchedulingRequirement::RunIf(in_state(
+                    GameState::Playing,
+                ))],
+                log("update playing"),
+            );
+
+        app.run_once();
+        app.with_resource::<NextState<GameState>>(|mut next| next.set(GameState::Playing));
+        app.run_once();
+        app.run_once();
+        // Setting the current state again does not cause a transition
+        app.with_resource::<NextState<GameState>>(|mut next| next.set(GameState::Playing));
+        app.run_once();
+        app.with_resource::<NextState<GameState>>(|mut next| next.set(GameState::Paused));
+        app.run_once();
+
+        let world = app.world();
+        assert_eq!(
+            world.resource::<Log>().0,
+            vec![
+                "enter menu",
+                "exit menu",
+                "enter playing",
+                "update playing",
+                "update playing",
+                "update playing",
+                "exit playing",
+            ]
+        );
+
+        let states = world.resource::<States<GameState>>();
+        assert_eq!(states.get(), &GameState::Paused);
+        assert_eq!(states.previous(), Some(&GameState::Playing));
+        // Commands from transition systems are applied right away
+        assert_eq!(world.query::<&u32, ()>().over(&*world).count(), 1);
+    }
+}
@@ -61,6 +61,8 @@ impl AnyVec {
         debug_assert!(self.layout.size() == std::mem::size_of::<T>());
         debug_assert!(self.layout.align() == std::mem::align_of::<T>());
         self.push_from_ptr(std::mem::transmute(&item as *const T));
+        // Ownership of `item` has been moved into the vector
+        std::mem::forget(item);
     }
 
     /// Caller must ensure that the type of data `ptr` is the same as the type of the vector.
@@ -127,6 +129,17 @@ impl AnyVec {
         }
     }
 
+    /// Drops the element at `index` and moves the last element into its place.
+    pub unsafe fn swap_remove_and_drop(&mut self, index: usize) -> SwapRemoveResult {
+        assert!(index < self.len(), "index out of bounds");
+
+        if let Some(drop_fn) = self.dropper {
+            drop_fn(self.get_ptr(index).expect("index out of bounds"));
+        }
+
+        self.swap_remove(index)
+    }
+
     #[inline]
     pub fn get_ptr(&self, index: usize) -> Option<NonNull<u8>> {
         if index >= self.length {
@@ -280,6 +293,26 @@ mod tests {
         assert_eq!(v.capacity(), 8);
     }
 
+    #[test]
+    fn swap_remove_and_drop() {
+        use std::rc::Rc;
+
+        let value = Rc::new(());
+        let mut v = AnyVec::new_of::<Rc<()>>(2);
+        unsafe {
+            v.push(Rc::clone(&value));
+            v.push(Rc::clone(&value));
+            assert_eq!(Rc::strong_count(&value), 3);
+
+            v.swap_remove_and_drop(0);
+            assert_eq!(v.len(), 1);
+            assert_eq!(Rc::strong_count(&value), 2);
+        };
+
+        drop(v);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
     #[test]
     fn zero_sized() {
         #[derive(Debug, PartialEq)]
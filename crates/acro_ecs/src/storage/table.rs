@@ -4,7 +4,9 @@ use fnv::FnvHashMap;
 
 use crate::{
     archetype::Column,
-    registry::{ComponentGroup, ComponentId, ComponentType},
+    component::StorageType,
+    pointer::change_detection::ComponentTicks,
+    registry::{ComponentGroup, ComponentId},
 };
 
 use super::anyvec::AnyVec;
@@ -22,15 +24,15 @@ impl Table {
             length: 0,
             columns: components
                 .iter()
-                .map(|info| match info.component_type {
-                    ComponentType::Native {
-                        layout, dropper, ..
-                    } => (
-                        info.id,
-                        Rc::new(Column::new(UnsafeCell::new(AnyVec::new(
-                            layout, dropper, 1,
-                        )))),
-                    ),
+                .map(|info| {
+                    let layout = info.component_type.layout();
+                    let dropper = info.component_type.dropper();
+                    let column = match info.storage {
+                        StorageType::Table => {
+                            Column::new(UnsafeCell::new(AnyVec::new(layout, dropper, 1)))
+                        }
+                    };
+                    (info.id, Rc::new(column))
                 })
                 .collect(),
             components,
@@ -39,27 +41,47 @@ impl Table {
 
     pub unsafe fn push_row(
         &mut self,
-        component_data: impl Iterator<Item = (ComponentId, *const u8)>,
+        component_data: impl Iterator<Item = (ComponentId, *const u8, ComponentTicks)>,
     ) {
         self.length += 1;
-        for (component_id, data) in component_data {
+        for (component_id, data, ticks) in component_data {
             let column = self
                 .columns
                 .get_mut(&component_id)
                 .expect("column not found");
-            // unsafe { (*column.data.get()).push_from_ptr(data) };
-            unsafe { (*column).push_from_ptr(data) };
+            unsafe { (*column).push_from_ptr(data, ticks) };
+        }
+    }
+
+    /// Reserves space for at least `additional` more rows.
+    pub fn reserve(&mut self, additional: usize) {
+        for column in self.columns.values() {
+            unsafe { column.reserve(additional) };
         }
     }
 
     /// Swap removes a row from the table, returning the index of the element which replaced the
     /// removed element if it's not the last element.
     pub fn remove_row(&mut self, index: usize) -> Option<usize> {
+        self.remove_row_inner(index, false)
+    }
+
+    /// Same as [`Table::remove_row`], but the removed components are dropped instead of being
+    /// left for the caller to move out.
+    pub fn drop_row(&mut self, index: usize) -> Option<usize> {
+        self.remove_row_inner(index, true)
+    }
+
+    fn remove_row_inner(&mut self, index: usize, drop: bool) -> Option<usize> {
         let last_index = self.length - 1;
 
         for column in self.columns.values_mut() {
             unsafe {
-                (*column).remove(index);
+                if drop {
+                    (*column).remove_and_drop(index);
+                } else {
+                    (*column).remove(index);
+                }
             }
         }
 
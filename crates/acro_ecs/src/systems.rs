@@ -1,9 +1,13 @@
 use std::{
     any::{Any, TypeId},
     error::Error,
+    marker::PhantomData,
     rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
+use fnv::FnvHashSet;
+
 use crate::{
     pointer::change_detection::Tick,
     query::{Query, QueryFilter, QueryInfo, ToQueryInfo},
@@ -58,7 +62,7 @@ impl SystemRunContext<'_> {
                 .archetypes
                 .get_archetype(meta.archetype_id)
                 .expect("archetype not found")
-                .borrow_mut()
+                .borrow()
                 .get_column(component_id)
                 .ok_or(NotifyChangeError::ComponentDeleted)?
                 .change_detection
@@ -104,12 +108,26 @@ pub enum SystemId {
     Faux(usize),
 }
 
+/// Tells apart every system that was added, unlike [`SystemId`], which is the same for systems
+/// added from the same function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SystemIndex(usize);
+
+impl SystemIndex {
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
 pub struct SystemData {
     pub id: SystemId,
+    pub index: SystemIndex,
     pub name: String,
     pub run: SystemFn,
     pub last_run_tick: Tick,
     pub parameters: Box<dyn Any>,
+    pub access: SystemAccess,
     pub scheduling_requirements: Vec<SystemSchedulingRequirement>,
 }
 
@@ -120,18 +138,119 @@ impl std::fmt::Debug for SystemData {
             .field("run", &(self.run.as_ref() as *const _))
             .field("last_run_tick", &self.last_run_tick)
             .field("parameters", &self.parameters)
+            .field("access", &self.access)
             .field("scheduling_requirements", &self.scheduling_requirements)
             .finish()
     }
 }
 
+/// The components and resources a system reads and writes. Systems in the same stage with
+/// compatible accesses can run at the same time.
+///
+/// Systems that take a `SystemRunContext` can reach the whole world through it, so their declared
+/// accesses are not the full story. They are exclusive: they conflict with every other system
+/// and run alone on the main thread. Only systems built purely from `ParallelSystemParam`s are
+/// marked as `parameters_only` and can share a batch.
+#[derive(Debug, Default, Clone)]
+pub struct SystemAccess {
+    component_reads: FnvHashSet<ComponentId>,
+    component_writes: FnvHashSet<ComponentId>,
+    resource_reads: FnvHashSet<TypeId>,
+    resource_writes: FnvHashSet<TypeId>,
+    // Set when the system queues changes that are applied after it runs, so systems scheduled
+    // after it need to wait for those changes.
+    deferred: bool,
+    main_thread: bool,
+    parameters_only: bool,
+}
+
+impl SystemAccess {
+    pub fn read_component(&mut self, id: ComponentId) {
+        self.component_reads.insert(id);
+    }
+
+    pub fn write_component(&mut self, id: ComponentId) {
+        self.component_writes.insert(id);
+    }
+
+    pub fn read_resource<T: 'static>(&mut self) {
+        self.resource_reads.insert(TypeId::of::<T>());
+    }
+
+    pub fn write_resource<T: 'static>(&mut self) {
+        self.resource_writes.insert(TypeId::of::<T>());
+    }
+
+    pub fn set_deferred(&mut self) {
+        self.deferred = true;
+    }
+
+    pub fn set_main_thread(&mut self) {
+        self.main_thread = true;
+    }
+
+    /// Marks the system as only touching the world through its parameters. The scheduler moves
+    /// such systems into the thread pool, so this must only be set for `Send + Sync` systems
+    /// whose parameters all implement `ParallelSystemParam`.
+    pub(crate) fn set_parameters_only(&mut self) {
+        self.parameters_only = true;
+    }
+
+    /// Whether the system may access anything in the world, and so can't share a batch with any
+    /// other system.
+    pub fn is_exclusive(&self) -> bool {
+        !self.parameters_only
+    }
+
+    pub fn is_deferred(&self) -> bool {
+        self.deferred
+    }
+
+    /// Whether the system must run on the main thread. This is the case when it was explicitly
+    /// requested, or when the system uses a resource inserted with `insert_non_send_resource`.
+    pub fn is_main_thread(&self, world: &World) -> bool {
+        self.is_exclusive()
+            || self.main_thread
+            || self
+                .resource_reads
+                .iter()
+                .chain(self.resource_writes.iter())
+                .any(|&id| world.resources.is_non_send(id))
+    }
+
+    /// Returns true if systems with these accesses can run at the same time.
+    pub fn is_compatible(&self, other: &SystemAccess) -> bool {
+        !self.is_exclusive()
+            && !other.is_exclusive()
+            && self.component_writes.is_disjoint(&other.component_reads)
+            && self.component_writes.is_disjoint(&other.component_writes)
+            && other.component_writes.is_disjoint(&self.component_reads)
+            && self.resource_writes.is_disjoint(&other.resource_reads)
+            && self.resource_writes.is_disjoint(&other.resource_writes)
+            && other.resource_writes.is_disjoint(&self.resource_reads)
+    }
+}
+
 pub trait SystemParam {
     type Init: Any;
 
     fn init(world: &World) -> Self::Init;
     fn create(world: &World, prepared: &mut Self::Init) -> Self;
+    /// Records the components and resources this parameter gives the system access to.
+    fn access(world: &World, prepared: &Self::Init, access: &mut SystemAccess);
 }
 
+/// A system parameter that can be created and used on a thread pool worker while other systems
+/// run.
+///
+/// # Safety
+///
+/// `SystemParam::create` and the returned parameter may only touch parts of the world that are
+/// safe to share between threads, everything they hand out must be `Send`, and the parameter's
+/// `Init` state must be `Send`. Conflicting use of the same data has to be declared through
+/// `SystemParam::access`.
+pub unsafe trait ParallelSystemParam: SystemParam {}
+
 impl<TData, TFilters> SystemParam for Query<TData, TFilters>
 where
     TData: ToQueryInfo,
@@ -149,6 +268,11 @@ where
             _phantom: Default::default(),
         }
     }
+
+    fn access(world: &World, prepared: &Self::Init, access: &mut SystemAccess) {
+        prepared.add_access(access);
+        TFilters::access(world, access);
+    }
 }
 
 impl<T: 'static> SystemParam for Res<'_, T> {
@@ -160,8 +284,16 @@ impl<T: 'static> SystemParam for Res<'_, T> {
         // TODO: I'm 99% sure this is safe, but I don't want to fight the borrow checker right now
         unsafe { std::mem::transmute(world.resources.get::<T>()) }
     }
+
+    fn access(_world: &World, _prepared: &Self::Init, access: &mut SystemAccess) {
+        access.read_resource::<T>();
+    }
 }
 
+// SAFETY: Resource borrows are tracked atomically, and the bounds keep non-thread-safe resources
+// on the main thread.
+unsafe impl<T: Send + Sync + 'static> ParallelSystemParam for Res<'_, T> {}
+
 impl<T: 'static> SystemParam for ResMut<'_, T> {
     type Init = ();
 
@@ -171,10 +303,53 @@ impl<T: 'static> SystemParam for ResMut<'_, T> {
         // TODO: EEEEEEEEEEE same here
         unsafe { std::mem::transmute(world.resources.get_mut::<T>()) }
     }
+
+    fn access(_world: &World, _prepared: &Self::Init, access: &mut SystemAccess) {
+        access.write_resource::<T>();
+    }
 }
 
+// SAFETY: See `Res`.
+unsafe impl<T: Send + Sync + 'static> ParallelSystemParam for ResMut<'_, T> {}
+
+/// Gives the system `None` instead of panicking when the resource does not exist.
+impl<T: 'static> SystemParam for Option<Res<'_, T>> {
+    type Init = ();
+
+    fn init(_world: &World) {}
+
+    fn create(world: &World, _prepared: &mut Self::Init) -> Self {
+        unsafe { std::mem::transmute(world.resources.try_get::<T>().ok()) }
+    }
+
+    fn access(_world: &World, _prepared: &Self::Init, access: &mut SystemAccess) {
+        access.read_resource::<T>();
+    }
+}
+
+impl<T: 'static> SystemParam for Option<ResMut<'_, T>> {
+    type Init = ();
+
+    fn init(_world: &World) {}
+
+    fn create(world: &World, _prepared: &mut Self::Init) -> Self {
+        unsafe { std::mem::transmute(world.resources.try_get_mut::<T>().ok()) }
+    }
+
+    fn access(_world: &World, _prepared: &Self::Init, access: &mut SystemAccess) {
+        access.write_resource::<T>();
+    }
+}
+
+// SAFETY: See `Res`.
+unsafe impl<T: Send + Sync + 'static> ParallelSystemParam for Option<Res<'_, T>> {}
+
+// SAFETY: See `Res`.
+unsafe impl<T: Send + Sync + 'static> ParallelSystemParam for Option<ResMut<'_, T>> {}
+
 pub trait IntoSystem<P> {
     fn init(world: &World) -> Box<dyn Any>;
+    fn access(world: &World, parameters: &dyn Any) -> SystemAccess;
     fn into_system(self) -> SystemFn;
 }
 
@@ -205,6 +380,10 @@ where
         Box::new(())
     }
 
+    fn access(_world: &World, _parameters: &dyn Any) -> SystemAccess {
+        SystemAccess::default()
+    }
+
     fn into_system(self) -> SystemFn {
         Box::new(move |context, _| self(context).into_system_result())
     }
@@ -220,6 +399,13 @@ where
         Box::new(P1::init(world))
     }
 
+    fn access(world: &World, parameters: &dyn Any) -> SystemAccess {
+        let parameters = parameters.downcast_ref::<P1::Init>().unwrap();
+        let mut access = SystemAccess::default();
+        P1::access(world, parameters, &mut access);
+        access
+    }
+
     fn into_system(self) -> SystemFn {
         Box::new(move |context, parameters| {
             let parameters = parameters.downcast_mut::<P1::Init>().unwrap();
@@ -240,6 +426,14 @@ macro_rules! impl_into_system {
                 Box::new(($($members::init(world),)*))
             }
 
+            #[allow(non_snake_case)]
+            fn access(world: &World, parameters: &dyn Any) -> SystemAccess {
+                let ($($members,)*) = parameters.downcast_ref::<($($members::Init,)*)>().unwrap();
+                let mut access = SystemAccess::default();
+                $($members::access(world, $members, &mut access);)*
+                access
+            }
+
             #[allow(non_snake_case)]
             fn into_system(self) -> SystemFn {
                 Box::new(move |context, parameters| {
@@ -260,9 +454,57 @@ impl_into_system!(P1, P2, P3, P4, P5, P6);
 impl_into_system!(P1, P2, P3, P4, P5, P6, P7);
 impl_into_system!(P1, P2, P3, P4, P5, P6, P7, P8);
 
+/// Marks systems that only take parameters and no `SystemRunContext`, which lets them run on the
+/// thread pool.
+pub struct ParametersOnly<P>(PhantomData<P>);
+
+macro_rules! impl_into_parallel_system {
+    ($($members:ident),+) => {
+        impl<
+            F: Fn($($members),+) -> R + Send + Sync + 'static,
+            R: IntoSystemResult + 'static,
+            $($members: ParallelSystemParam),*
+        > IntoSystem<ParametersOnly<($($members,)+)>> for F
+        where
+            $($members::Init: Send),*
+        {
+            fn init(world: &World) -> Box<dyn Any> {
+                Box::new(($($members::init(world),)*))
+            }
+
+            #[allow(non_snake_case)]
+            fn access(world: &World, parameters: &dyn Any) -> SystemAccess {
+                let ($($members,)*) = parameters.downcast_ref::<($($members::Init,)*)>().unwrap();
+                let mut access = SystemAccess::default();
+                $($members::access(world, $members, &mut access);)*
+                access.set_parameters_only();
+                access
+            }
+
+            #[allow(non_snake_case)]
+            fn into_system(self) -> SystemFn {
+                Box::new(move |context, parameters| {
+                    let ($($members,)*) = parameters.downcast_mut::<($($members::Init,)*)>().unwrap();
+                    let world = context.world;
+                    self($($members::create(world, $members),)*).into_system_result()
+                })
+            }
+        }
+    }
+}
+
+impl_into_parallel_system!(P1);
+impl_into_parallel_system!(P1, P2);
+impl_into_parallel_system!(P1, P2, P3);
+impl_into_parallel_system!(P1, P2, P3, P4);
+impl_into_parallel_system!(P1, P2, P3, P4, P5);
+impl_into_parallel_system!(P1, P2, P3, P4, P5, P6);
+impl_into_parallel_system!(P1, P2, P3, P4, P5, P6, P7);
+impl_into_parallel_system!(P1, P2, P3, P4, P5, P6, P7, P8);
+
 #[cfg(test)]
 mod tests {
-    use crate::{query::Query, systems::SystemRunContext};
+    use crate::{query::Query, systems::SystemRunContext, Application, Res, ResMut, Stage};
 
     use super::IntoSystem;
 
@@ -275,4 +517,27 @@ mod tests {
 
         let _ = system.into_system();
     }
+
+    #[test]
+    fn optional_resources() {
+        #[derive(Debug, Default)]
+        struct Counter(u32);
+
+        struct Missing;
+
+        let mut app = Application::new();
+        app.insert_resource(Counter::default()).add_system(
+            Stage::Update,
+            [],
+            |_ctx: SystemRunContext,
+             missing: Option<Res<Missing>>,
+             counter: Option<ResMut<Counter>>| {
+                assert!(missing.is_none());
+                counter.expect("counter should exist").0 += 1;
+            },
+        );
+        app.run_once();
+
+        assert_eq!(app.world().resource::<Counter>().0, 1);
+    }
 }
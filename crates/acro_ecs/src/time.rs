@@ -0,0 +1,319 @@
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use tracing::warn;
+
+/// Where the schedule reads the current time from.
+pub trait Clock: std::fmt::Debug {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to, for stepping the schedule deterministically in tests.
+/// Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Rc<Cell<DateTime<Utc>>>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self {
+            now: Rc::new(Cell::new(DateTime::UNIX_EPOCH)),
+        }
+    }
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, delta: Duration) {
+        let delta = TimeDelta::from_std(delta).expect("clock advanced too far");
+        self.now.set(self.now.get() + delta);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now.get()
+    }
+}
+
+/// Frame timing, updated once per frame before `Stage::PreUpdate`.
+///
+/// The real clock always follows wall time. The virtual clock is the one gameplay should use: it
+/// is scaled by the time scale and stops while paused.
+#[derive(Debug, Clone)]
+pub struct Time {
+    real_delta: Duration,
+    real_elapsed: Duration,
+    delta: Duration,
+    elapsed: Duration,
+    fixed_delta: Duration,
+    time_scale: f64,
+    paused: bool,
+    pending_step: Duration,
+    frame_count: u64,
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self {
+            real_delta: Duration::ZERO,
+            real_elapsed: Duration::ZERO,
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            fixed_delta: FixedTimestep::default().timestep(),
+            time_scale: 1.0,
+            paused: false,
+            pending_step: Duration::ZERO,
+            frame_count: 0,
+        }
+    }
+}
+
+impl Time {
+    /// Advances both clocks by the real time that passed since the last frame.
+    pub fn update(&mut self, real_delta: Duration) {
+        self.real_delta = real_delta;
+        self.real_elapsed += real_delta;
+
+        self.delta = if self.paused {
+            std::mem::take(&mut self.pending_step)
+        } else {
+            real_delta.mul_f64(self.time_scale)
+        };
+        self.elapsed += self.delta;
+
+        self.frame_count += 1;
+    }
+
+    /// Time since the last frame on the virtual clock.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// Total time on the virtual clock.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn elapsed_seconds(&self) -> f64 {
+        self.elapsed.as_secs_f64()
+    }
+
+    pub fn real_delta(&self) -> Duration {
+        self.real_delta
+    }
+
+    pub fn real_elapsed(&self) -> Duration {
+        self.real_elapsed
+    }
+
+    /// The duration of one `Stage::FixedUpdate` step.
+    pub fn fixed_delta(&self) -> Duration {
+        self.fixed_delta
+    }
+
+    pub fn fixed_delta_seconds(&self) -> f32 {
+        self.fixed_delta.as_secs_f32()
+    }
+
+    pub(crate) fn set_fixed_delta(&mut self, fixed_delta: Duration) {
+        self.fixed_delta = fixed_delta;
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        assert!(
+            time_scale >= 0.0 && time_scale.is_finite(),
+            "time scale must be a finite, non-negative number"
+        );
+        self.time_scale = time_scale;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+        self.pending_step = Duration::ZERO;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Advances the virtual clock by `delta` on the next frame while paused. Does nothing when
+    /// not paused.
+    pub fn step(&mut self, delta: Duration) {
+        if self.paused {
+            self.pending_step += delta;
+        }
+    }
+
+    /// The number of frames that have started, including the current one.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+}
+
+/// Configuration and state of the fixed timestep that drives `Stage::FixedUpdate`.
+///
+/// Every frame the virtual time that passed (see `Time`) is added to an accumulator, and `Stage::FixedUpdate`
+/// runs once for every full timestep in it. This keeps the number of simulation steps
+/// deterministic, no matter how fast or slow frames are rendered.
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    timestep: Duration,
+    max_steps: u32,
+    accumulator: Duration,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::from_hz(60.0)
+    }
+}
+
+impl FixedTimestep {
+    pub fn new(timestep: Duration) -> Self {
+        assert!(!timestep.is_zero(), "fixed timestep must not be zero");
+
+        Self {
+            timestep,
+            max_steps: 8,
+            accumulator: Duration::ZERO,
+        }
+    }
+
+    pub fn from_hz(hz: f64) -> Self {
+        Self::new(Duration::from_secs_f64(1.0 / hz))
+    }
+
+    pub fn timestep(&self) -> Duration {
+        self.timestep
+    }
+
+    pub fn set_timestep(&mut self, timestep: Duration) {
+        assert!(!timestep.is_zero(), "fixed timestep must not be zero");
+        self.timestep = timestep;
+    }
+
+    pub fn max_steps(&self) -> u32 {
+        self.max_steps
+    }
+
+    /// Sets the most steps that can run in a single frame. If a frame takes long enough to need
+    /// more, the remaining time is dropped so slow frames can not cause even slower frames.
+    pub fn set_max_steps(&mut self, max_steps: u32) {
+        self.max_steps = max_steps;
+    }
+
+    pub fn accumulator(&self) -> Duration {
+        self.accumulator
+    }
+
+    /// How far the current frame is between the last fixed step and the next one, from 0 to 1.
+    /// Used to interpolate between simulation states when rendering.
+    pub fn alpha(&self) -> f64 {
+        self.accumulator.as_secs_f64() / self.timestep.as_secs_f64()
+    }
+
+    /// Adds the frame's time to the accumulator and returns how many fixed steps should run.
+    pub fn expend(&mut self, delta: Duration) -> u32 {
+        self.accumulator += delta;
+
+        let mut steps = 0;
+        while self.accumulator >= self.timestep {
+            if steps == self.max_steps {
+                let skipped = self.accumulator.as_nanos() / self.timestep.as_nanos();
+                warn!("fixed timestep is falling behind, skipping {skipped} steps");
+
+                self.accumulator = Duration::from_nanos(
+                    (self.accumulator.as_nanos() % self.timestep.as_nanos()) as u64,
+                );
+                break;
+            }
+
+            self.accumulator -= self.timestep;
+            steps += 1;
+        }
+
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{FixedTimestep, Time};
+
+    #[test]
+    fn time() {
+        let mut time = Time::default();
+        time.update(Duration::from_millis(10));
+        assert_eq!(time.delta(), Duration::from_millis(10));
+
+        time.set_time_scale(0.5);
+        time.update(Duration::from_millis(10));
+        assert_eq!(time.delta(), Duration::from_millis(5));
+        assert_eq!(time.elapsed(), Duration::from_millis(15));
+
+        time.pause();
+        time.update(Duration::from_millis(10));
+        assert_eq!(time.delta(), Duration::ZERO);
+
+        time.step(Duration::from_millis(16));
+        time.update(Duration::from_millis(10));
+        assert_eq!(time.delta(), Duration::from_millis(16));
+        time.update(Duration::from_millis(10));
+        assert_eq!(time.delta(), Duration::ZERO);
+
+        time.unpause();
+        time.update(Duration::from_millis(10));
+        assert_eq!(time.delta(), Duration::from_millis(5));
+
+        assert_eq!(time.elapsed(), Duration::from_millis(36));
+        assert_eq!(time.real_elapsed(), Duration::from_millis(60));
+        assert_eq!(time.frame_count(), 6);
+    }
+
+    #[test]
+    fn fixed_timestep() {
+        let mut fixed = FixedTimestep::new(Duration::from_millis(10));
+        fixed.set_max_steps(3);
+
+        assert_eq!(fixed.expend(Duration::from_millis(4)), 0);
+        assert_eq!(fixed.expend(Duration::from_millis(4)), 0);
+        assert_eq!(fixed.expend(Duration::from_millis(4)), 1);
+        assert_eq!(fixed.accumulator(), Duration::from_millis(2));
+        assert!((fixed.alpha() - 0.2).abs() < 1e-9);
+
+        assert_eq!(fixed.expend(Duration::from_millis(25)), 2);
+        assert_eq!(fixed.accumulator(), Duration::from_millis(7));
+
+        // Capped, the extra steps are dropped but the leftover time is kept
+        assert_eq!(fixed.expend(Duration::from_millis(58)), 3);
+        assert_eq!(fixed.accumulator(), Duration::from_millis(5));
+    }
+}
@@ -1,17 +1,26 @@
 use std::{
+    alloc::Layout,
     any::TypeId,
     cell::{RefCell, UnsafeCell},
+    mem::ManuallyDrop,
     ptr::NonNull,
+    rc::Rc,
 };
 
+use fnv::FnvHashMap;
+
 use crate::{
-    archetype::Archetypes,
+    archetype::{ArchetypeId, Archetypes},
     bundle::Bundle,
+    commands::CommandQueue,
+    component::Component,
     entity::{Entities, EntityId, EntityMeta},
+    error::{ComponentError, ComponentNotRegistered, EntityNotFound, ResourceMissing},
     pointer::change_detection::{ChangeDetectionContext, Tick},
     query::{Query, QueryFilter, ToQueryInfo},
-    registry::{ComponentInfo, ComponentRegistry},
+    registry::{ComponentHook, ComponentHooks, ComponentInfo, ComponentRegistry},
     resource::ResourceRegistry,
+    storage::anyvec::Dropper,
     systems::{IntoSystem, SystemRunContext},
     ComponentId, Res, ResMut,
 };
@@ -22,6 +31,11 @@ pub struct World {
     pub(crate) resources: ResourceRegistry,
     pub(crate) archetypes: Archetypes,
     pub(crate) swap_fn: RefCell<Option<Box<dyn FnOnce(&mut World)>>>,
+    pub(crate) commands: CommandQueue,
+    // The component ids of every bundle type that has been spawned or inserted
+    bundles: FnvHashMap<TypeId, Rc<[ComponentId]>>,
+    // The tick recorded as the added/changed tick of components inserted into the world
+    change_tick: Tick,
 }
 
 impl std::fmt::Debug for World {
@@ -32,6 +46,8 @@ impl std::fmt::Debug for World {
             .field("resources", &self.resources)
             .field("archetypes", &self.archetypes)
             .field("swap_fn", &"...")
+            .field("commands", &self.commands)
+            .field("change_tick", &self.change_tick)
             .finish()
     }
 }
@@ -44,19 +60,159 @@ impl World {
             resources: ResourceRegistry::new(),
             archetypes: Archetypes::new(),
             swap_fn: RefCell::new(None),
+            commands: CommandQueue::default(),
+            bundles: FnvHashMap::default(),
+            change_tick: Tick::new(1),
         }
     }
 
     pub fn spawn<T: Bundle>(&mut self, bundle: T) -> EntityId {
-        let entity = self.spawn_empty();
-        bundle.build(self, entity);
+        let components = self.bundle_components::<T>();
+        let archetype_id = self.archetypes.get_or_create_bundle_archetype(
+            ArchetypeId::EMPTY,
+            TypeId::of::<T>(),
+            &components,
+            &self.components,
+        );
+
+        let entity = self.push_bundle(archetype_id, &components, bundle);
+        for &component_id in components.iter() {
+            self.run_insert_hooks(entity, component_id, true);
+        }
         entity
     }
 
+    /// Spawns an entity for every bundle, reserving space for all of them up front.
+    pub fn spawn_batch<T: Bundle>(
+        &mut self,
+        bundles: impl IntoIterator<Item = T>,
+    ) -> Vec<EntityId> {
+        let components = self.bundle_components::<T>();
+        let archetype_id = self.archetypes.get_or_create_bundle_archetype(
+            ArchetypeId::EMPTY,
+            TypeId::of::<T>(),
+            &components,
+            &self.components,
+        );
+
+        let bundles = bundles.into_iter();
+        self.archetypes.reserve(archetype_id, bundles.size_hint().0);
+        let entities = bundles
+            .map(|bundle| self.push_bundle(archetype_id, &components, bundle))
+            .collect::<Vec<_>>();
+
+        for &entity in &entities {
+            for &component_id in components.iter() {
+                self.run_insert_hooks(entity, component_id, true);
+            }
+        }
+        entities
+    }
+
+    /// Inserts all of the bundle's components, replacing any the entity already has.
+    pub fn insert_bundle<T: Bundle>(&mut self, entity: EntityId, bundle: T) {
+        let components = self.bundle_components::<T>();
+        let archetype_id = self.entity_meta(entity).archetype_id;
+        let replaces_any = {
+            let archetype = self
+                .archetypes
+                .get_archetype(archetype_id)
+                .expect("archetype not found")
+                .borrow();
+            components
+                .iter()
+                .any(|&id| archetype.components.contains(id))
+        };
+
+        let bundle = ManuallyDrop::new(bundle);
+        let mut ptrs = Vec::with_capacity(components.len());
+        bundle.component_ptrs(&mut ptrs);
+
+        if replaces_any {
+            // Replaced values have to be dropped in place, so insert the components one by one
+            for (&component_id, ptr) in components.iter().zip(ptrs) {
+                unsafe {
+                    self.insert_by_id(entity, component_id, NonNull::new_unchecked(ptr as *mut u8))
+                };
+            }
+            return;
+        }
+
+        let new_archetype_id = self.archetypes.get_or_create_bundle_archetype(
+            archetype_id,
+            TypeId::of::<T>(),
+            &components,
+            &self.components,
+        );
+        unsafe {
+            self.archetypes.add_components_untyped(
+                &mut self.entities,
+                entity,
+                new_archetype_id,
+                components.iter().copied().zip(ptrs),
+                self.change_tick,
+            )
+        };
+        for &component_id in components.iter() {
+            self.run_insert_hooks(entity, component_id, true);
+        }
+    }
+
+    fn bundle_components<T: Bundle>(&mut self) -> Rc<[ComponentId]> {
+        if let Some(components) = self.bundles.get(&TypeId::of::<T>()) {
+            return Rc::clone(components);
+        }
+
+        let mut components = vec![];
+        T::component_ids(&mut self.components, &mut components);
+
+        let mut unique = components.clone();
+        unique.sort_by_key(|id| id.0);
+        unique.dedup();
+        assert_eq!(
+            unique.len(),
+            components.len(),
+            "bundle {} contains the same component more than once",
+            std::any::type_name::<T>()
+        );
+
+        let components: Rc<[ComponentId]> = components.into();
+        self.bundles
+            .insert(TypeId::of::<T>(), Rc::clone(&components));
+        components
+    }
+
+    fn push_bundle<T: Bundle>(
+        &mut self,
+        archetype_id: ArchetypeId,
+        components: &[ComponentId],
+        bundle: T,
+    ) -> EntityId {
+        // The components are moved into the archetype table, so the bundle must not be dropped
+        let bundle = ManuallyDrop::new(bundle);
+        let mut ptrs = Vec::with_capacity(components.len());
+        bundle.component_ptrs(&mut ptrs);
+
+        unsafe {
+            self.archetypes.push_entity(
+                &mut self.entities,
+                archetype_id,
+                components.iter().copied().zip(ptrs),
+                self.change_tick,
+            )
+        }
+    }
+
     pub fn spawn_empty(&mut self) -> EntityId {
         self.archetypes.push_empty_entity(&mut self.entities)
     }
 
+    /// Reserves an entity id that can be handed out before the entity is spawned. The entity is
+    /// spawned (with no components) when commands are next applied.
+    pub fn reserve_entity(&self) -> EntityId {
+        self.entities.reserve()
+    }
+
     pub fn resources(&self) -> &ResourceRegistry {
         &self.resources
     }
@@ -66,17 +222,65 @@ impl World {
     }
 
     pub fn entity_meta(&self, entity: EntityId) -> &EntityMeta {
-        self.entity_meta_opt(entity).expect("entity not found")
+        self.try_entity_meta(entity)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_entity_meta(&self, entity: EntityId) -> Result<&EntityMeta, EntityNotFound> {
+        self.entity_meta_opt(entity).ok_or(EntityNotFound(entity))
+    }
+
+    pub fn init_component<T: Component>(&mut self) -> &ComponentInfo {
+        let id = self.components.register::<T>();
+        self.components.get_info(id)
     }
 
-    pub fn init_component<T: 'static>(&mut self) -> &ComponentInfo {
-        self.components.init_rust_type::<T>()
+    /// Registers a component along with the metadata from its `Component` impl, such as hooks.
+    /// Inserting a component registers it as well, so this is only needed to have the id up front.
+    pub fn register_component<T: Component>(&mut self) -> ComponentId {
+        self.components.register::<T>()
     }
 
     pub fn get_component_info<T: 'static>(&self) -> &ComponentInfo {
+        self.try_get_component_info::<T>()
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_get_component_info<T: 'static>(
+        &self,
+    ) -> Result<&ComponentInfo, ComponentNotRegistered> {
         self.components
             .get::<T>()
-            .unwrap_or_else(|| panic!("component {} not found", std::any::type_name::<T>()))
+            .ok_or_else(ComponentNotRegistered::of::<T>)
+    }
+
+    /// Registers a component type defined at runtime, see [`ComponentRegistry::register_dynamic`].
+    pub fn register_dynamic_component(
+        &mut self,
+        name: impl ToString,
+        layout: Layout,
+        dropper: Dropper,
+    ) -> ComponentId {
+        self.components.register_dynamic(name, layout, dropper)
+    }
+
+    pub fn get_dynamic_component(&self, name: &str) -> Option<ComponentId> {
+        self.components.get_dynamic(name).map(|info| info.id)
+    }
+
+    pub fn get_component_info_by_id(&self, component_id: ComponentId) -> &ComponentInfo {
+        self.components.get_info(component_id)
+    }
+
+    /// Sets the hooks called when `T` is added to or removed from an entity, replacing any that
+    /// were set before.
+    pub fn set_component_hooks<T: 'static>(&mut self, hooks: ComponentHooks) {
+        let component_id = self.get_component_info::<T>().id;
+        self.components.set_hooks(component_id, hooks);
+    }
+
+    pub fn set_component_hooks_by_id(&mut self, component_id: ComponentId, hooks: ComponentHooks) {
+        self.components.set_hooks(component_id, hooks);
     }
 
     pub fn get_component_info_id(&self, id: TypeId) -> &ComponentInfo {
@@ -85,29 +289,150 @@ impl World {
             .unwrap_or_else(|| panic!("component with id {id:?} not found"))
     }
 
-    pub fn insert<T: 'static>(&mut self, entity: EntityId, component: T) {
-        let component_info = self
-            .components
-            .get::<T>()
-            .unwrap_or_else(|| panic!("component {} not found", std::any::type_name::<T>()));
-
-        self.archetypes.add_component(
+    /// Inserts a component, replacing the entity's existing value. Registers the component if
+    /// this is the first time it is used.
+    pub fn insert<T: Component>(&mut self, entity: EntityId, component: T) {
+        let component_id = self.components.register::<T>();
+        let added = self.archetypes.add_component(
             &self.components,
             &mut self.entities,
             entity,
-            component_info.id,
+            component_id,
             component,
+            self.change_tick,
         );
+        self.run_insert_hooks(entity, component_id, added);
+    }
+
+    /// Inserts a component, or returns an error instead of panicking if the entity does not exist.
+    pub fn try_insert<T: Component>(
+        &mut self,
+        entity: EntityId,
+        component: T,
+    ) -> Result<(), EntityNotFound> {
+        self.try_entity_meta(entity)?;
+        self.insert(entity, component);
+        Ok(())
+    }
+
+    /// Inserts a component from raw bytes. Mostly useful for dynamic components, which have no
+    /// Rust type.
+    ///
+    /// # Safety
+    /// `data` must point to a valid value with the component's layout. The world takes ownership
+    /// of the value, so the caller must not drop it afterwards.
+    pub unsafe fn insert_by_id(
+        &mut self,
+        entity: EntityId,
+        component_id: ComponentId,
+        data: NonNull<u8>,
+    ) {
+        let component_info = self.components.get_info(component_id);
+
+        let added = unsafe {
+            self.archetypes.add_component_untyped(
+                &self.components,
+                &mut self.entities,
+                entity,
+                component_info.id,
+                data.as_ptr(),
+                self.change_tick,
+            )
+        };
+        self.run_insert_hooks(entity, component_id, added);
     }
 
     pub fn remove<T: 'static>(&mut self, entity: EntityId) -> T {
-        let component_info = self.components.get::<T>().expect("component not found");
-        self.archetypes.remove_component(
+        self.try_remove::<T>(entity)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Removes a component and returns it, or an error if the entity does not have it.
+    pub fn try_remove<T: 'static>(&mut self, entity: EntityId) -> Result<T, ComponentError> {
+        self.try_entity_meta(entity)?;
+        let component_id = self.try_get_component_info::<T>()?.id;
+        if self.get_ptr(entity, component_id, None).is_none() {
+            return Err(ComponentError::MissingComponent {
+                entity,
+                component: std::any::type_name::<T>(),
+            });
+        }
+
+        self.run_remove_hook(entity, component_id);
+        Ok(self.archetypes.remove_component(
             &self.components,
             &mut self.entities,
             entity,
-            component_info.id,
-        )
+            component_id,
+        ))
+    }
+
+    /// Removes a component by id and drops it.
+    pub fn remove_by_id(&mut self, entity: EntityId, component_id: ComponentId) {
+        self.run_remove_hook(entity, component_id);
+        self.archetypes.remove_component_and_drop(
+            &self.components,
+            &mut self.entities,
+            entity,
+            component_id,
+        );
+    }
+
+    /// Removes an entity and drops all of its components. Returns false if the entity has already
+    /// been despawned.
+    pub fn despawn(&mut self, entity: EntityId) -> bool {
+        self.archetypes.flush_reserved(&mut self.entities);
+
+        let Some(meta) = self.entities.get(entity) else {
+            return false;
+        };
+
+        let component_ids = self
+            .archetypes
+            .get_archetype(meta.archetype_id)
+            .map(|archetype| {
+                archetype
+                    .borrow()
+                    .components
+                    .iter()
+                    .map(|info| info.id)
+                    .collect::<Vec<_>>()
+            });
+        for component_id in component_ids.into_iter().flatten() {
+            // An earlier hook may have already removed the component
+            if self.get_ptr(entity, component_id, None).is_some() {
+                self.run_remove_hook(entity, component_id);
+            }
+        }
+
+        self.archetypes.despawn(&mut self.entities, entity)
+    }
+
+    fn run_insert_hooks(&mut self, entity: EntityId, component_id: ComponentId, added: bool) {
+        let hooks = self.components.hooks(component_id);
+        if added {
+            if let Some(on_add) = hooks.on_add {
+                on_add(self, entity);
+            }
+        }
+        if let Some(on_insert) = hooks.on_insert {
+            on_insert(self, entity);
+        }
+    }
+
+    fn run_remove_hook(&mut self, entity: EntityId, component_id: ComponentId) {
+        if let Some(on_remove) = self.components.hooks(component_id).on_remove {
+            on_remove(self, entity);
+        }
+    }
+
+    pub fn change_tick(&self) -> Tick {
+        self.change_tick
+    }
+
+    /// Sets the tick that components inserted from now on are marked as added and changed at.
+    pub fn set_change_tick(&mut self, tick: Tick) {
+        self.change_tick = tick;
     }
 
     pub fn query<T, F>(&self) -> Query<T, F>
@@ -125,14 +450,17 @@ impl World {
     {
         let mut system_init = I::init(&self);
         let system_run_function = system.into_system();
-        (system_run_function)(
+        let result = (system_run_function)(
             SystemRunContext {
                 world: self,
                 tick,
                 last_run_tick: Tick::new(0),
             },
             system_init.as_mut(),
-        )
+        );
+        self.change_tick = tick;
+        self.apply_commands();
+        result
     }
 
     pub fn get<T: 'static>(&self, entity: EntityId) -> Option<&T> {
@@ -141,13 +469,43 @@ impl World {
             .get_component::<T>(&self.entities, entity, component_info.id)
     }
 
+    /// Mutably borrows a component, marking it as changed at the current change tick so
+    /// `Changed<T>` filters pick up the edit.
+    pub fn get_mut<T: 'static>(&mut self, entity: EntityId) -> Option<&mut T> {
+        let component_info = self.components.get::<T>()?;
+        self.archetypes
+            .get_component_untyped(
+                &self.entities,
+                entity,
+                component_info.id,
+                Some(self.change_tick),
+            )
+            .map(|ptr| unsafe { &mut *(ptr.as_ptr() as *mut T) })
+    }
+
+    /// Marks a component as changed at the current change tick, as if it was mutated through a
+    /// query, so `Changed<T>` filters pick it up. Returns false if the entity does not have it.
+    pub fn mark_changed<T: 'static>(&mut self, entity: EntityId) -> bool {
+        let Some(component_info) = self.components.get::<T>() else {
+            return false;
+        };
+        self.archetypes
+            .get_component_untyped(
+                &self.entities,
+                entity,
+                component_info.id,
+                Some(self.change_tick),
+            )
+            .is_some()
+    }
+
     pub fn get_ptr(
         &self,
         entity: EntityId,
         component_id: ComponentId,
         update_change_detection: Option<Tick>,
     ) -> Option<NonNull<u8>> {
-        let component_info = self.components.get_info(component_id);
+        let component_info = self.components.try_get_info(component_id)?;
         self.archetypes.get_component_untyped(
             &self.entities,
             entity,
@@ -160,15 +518,44 @@ impl World {
         self.resources.insert(resource);
     }
 
-    pub fn resource<T: 'static>(&self) -> Res<T> {
+    pub fn insert_non_send_resource<T: 'static>(&mut self, resource: T) {
+        self.resources.insert_non_send(resource);
+    }
+
+    pub fn resource<T: 'static>(&self) -> Res<'_, T> {
         self.resources.get::<T>()
     }
 
-    pub fn resource_mut<T: 'static>(&self) -> ResMut<T> {
+    pub fn resource_mut<T: 'static>(&self) -> ResMut<'_, T> {
         self.resources.get_mut::<T>()
     }
 
+    pub fn try_resource<T: 'static>(&self) -> Result<Res<'_, T>, ResourceMissing> {
+        self.resources.try_get::<T>()
+    }
+
+    pub fn try_resource_mut<T: 'static>(&self) -> Result<ResMut<'_, T>, ResourceMissing> {
+        self.resources.try_get_mut::<T>()
+    }
+
+    /// Removes every entity at once, calling the `on_remove` hooks of their components first.
     pub fn clear_all_entities(&mut self) {
+        let mut remove_hooks: Vec<(EntityId, ComponentHook)> = vec![];
+        for archetype in self.archetypes.iter() {
+            let archetype = archetype.borrow();
+            for info in archetype.components.iter() {
+                if let Some(on_remove) = self.components.hooks(info.id).on_remove {
+                    remove_hooks
+                        .extend(archetype.entities.iter().map(|&entity| (entity, on_remove)));
+                }
+            }
+        }
+        for (entity, on_remove) in remove_hooks {
+            if self.entities.get(entity).is_some() {
+                on_remove(self, entity);
+            }
+        }
+
         self.entities.clear();
         self.archetypes.clear();
     }
@@ -182,11 +569,24 @@ impl World {
             swap_fn(self);
         }
     }
+
+    /// Spawns reserved entities and runs all queued commands in the order they were queued.
+    pub fn apply_commands(&mut self) {
+        self.archetypes.flush_reserved(&mut self.entities);
+
+        // Commands can queue more commands, so keep going until the queue is empty
+        while !self.commands.is_empty() {
+            for command in self.commands.take() {
+                command(self);
+                self.archetypes.flush_reserved(&mut self.entities);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::archetype::ArchetypeId;
+    use crate::{archetype::ArchetypeId, Changed};
 
     use super::*;
 
@@ -232,5 +632,398 @@ mod tests {
 
         let entity_meta_1 = world.entity_meta(entity_1);
         assert_eq!(entity_meta_1.archetype_id, ArchetypeId::EMPTY);
+        // Removing from a row that is not the last one returns the removed entity's data
+        let entity_2 = world.spawn((1u32,));
+        let entity_3 = world.spawn((2u32,));
+        assert_eq!(world.remove::<u32>(entity_2), 1);
+        assert_eq!(world.get::<u32>(entity_3), Some(&2));
+    }
+
+    #[test]
+    fn despawn() {
+        use std::rc::Rc;
+
+        let mut world = World::new();
+        world.init_component::<u32>();
+        world.init_component::<Rc<()>>();
+
+        let counter = Rc::new(());
+        let entity_1 = world.spawn((1u32, Rc::clone(&counter)));
+        let entity_2 = world.spawn((2u32, Rc::clone(&counter)));
+        let entity_3 = world.spawn((3u32, Rc::clone(&counter)));
+        assert_eq!(Rc::strong_count(&counter), 4);
+
+        assert!(world.despawn(entity_1));
+        assert_eq!(Rc::strong_count(&counter), 3);
+        assert!(world.entity_meta_opt(entity_1).is_none());
+        assert_eq!(world.get::<u32>(entity_1), None);
+
+        // The last entity is swapped into the despawned entity's row
+        assert_eq!(world.entity_meta(entity_3).table_index, 0);
+        assert_eq!(world.get::<u32>(entity_3), Some(&3));
+        assert_eq!(world.get::<u32>(entity_2), Some(&2));
+
+        assert!(!world.despawn(entity_1));
+
+        // The freed slot is reused with a new generation
+        let entity_4 = world.spawn((4u32,));
+        assert_eq!(entity_4.index, entity_1.index);
+        assert_eq!(entity_4.generation, entity_1.generation + 1);
+        assert_eq!(world.get::<u32>(entity_1), None);
+        assert_eq!(world.get::<u32>(entity_4), Some(&4));
+
+        world.despawn(entity_2);
+        world.despawn(entity_3);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn get_mut_marks_changed() {
+        let mut world = World::new();
+        world.init_component::<u32>();
+        world.spawn((1u32,));
+        let entity_2 = world.spawn((2u32,));
+
+        world.set_change_tick(Tick::new(5));
+        *world.get_mut::<u32>(entity_2).unwrap() = 3;
+        assert_eq!(world.get::<u32>(entity_2), Some(&3));
+
+        let changed = world.query::<EntityId, Changed<u32>>();
+        let ctx = SystemRunContext {
+            world: &world,
+            tick: Tick::new(6),
+            last_run_tick: Tick::new(4),
+        };
+        assert_eq!(changed.over(ctx).collect::<Vec<_>>(), vec![entity_2]);
+    }
+
+    #[test]
+    fn dynamic_components() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+        let mut world = World::new();
+        world.init_component::<u32>();
+        let health = world.register_dynamic_component(
+            "Health",
+            Layout::new::<[u8; 8]>(),
+            Some(|_ptr| {
+                DROPPED.fetch_add(1, Ordering::SeqCst);
+            }),
+        );
+        assert_eq!(
+            world.register_dynamic_component("Health", Layout::new::<[u8; 8]>(), None),
+            health
+        );
+        assert_eq!(world.get_dynamic_component("Health"), Some(health));
+
+        let entity_1 = world.spawn((1u32,));
+        let entity_2 = world.spawn((2u32,));
+        let entity_3 = world.spawn((3u32,));
+        for (entity, value) in [(entity_1, 10u64), (entity_2, 20u64)] {
+            let mut bytes = value.to_le_bytes();
+            unsafe { world.insert_by_id(entity, health, NonNull::from(&mut bytes).cast()) };
+        }
+
+        let read_health = |world: &World, entity| {
+            world
+                .get_ptr(entity, health, None)
+                .map(|ptr| u64::from_le_bytes(unsafe { ptr.cast::<[u8; 8]>().read() }))
+        };
+        assert_eq!(read_health(&world, entity_1), Some(10));
+        assert_eq!(read_health(&world, entity_2), Some(20));
+        assert_eq!(read_health(&world, entity_3), None);
+
+        let with_health = world.query::<&u32, ()>().with_id(&world, health);
+        let mut values = with_health.over(&world).copied().collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, vec![1, 2]);
+
+        let without_health = world.query::<&u32, ()>().without_id(&world, health);
+        assert_eq!(
+            without_health.over(&world).copied().collect::<Vec<_>>(),
+            vec![3]
+        );
+
+        world.remove_by_id(entity_1, health);
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+        assert_eq!(read_health(&world, entity_1), None);
+        assert_eq!(read_health(&world, entity_2), Some(20));
+
+        world.despawn(entity_2);
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn component_hooks() {
+        use std::rc::Rc;
+
+        #[derive(Debug, Default)]
+        struct HookLog(Vec<(&'static str, u32)>);
+
+        fn log(world: &mut World, event: &'static str, entity: EntityId) {
+            let value = *world
+                .get::<u32>(entity)
+                .expect("component should be readable");
+            world.resource_mut::<HookLog>().0.push((event, value));
+        }
+
+        let mut world = World::new();
+        world.insert_resource(HookLog::default());
+        world.init_component::<u32>();
+        world.init_component::<Rc<()>>();
+        world.set_component_hooks::<u32>(
+            ComponentHooks::new()
+                .on_add(|world, entity| log(world, "add", entity))
+                .on_insert(|world, entity| log(world, "insert", entity))
+                .on_remove(|world, entity| log(world, "remove", entity)),
+        );
+
+        let entity_1 = world.spawn((1u32,));
+        // Replacing a value only calls on_insert, and drops the old value
+        let counter = Rc::new(());
+        world.insert(entity_1, Rc::clone(&counter));
+        world.insert(entity_1, Rc::clone(&counter));
+        assert_eq!(Rc::strong_count(&counter), 2);
+        world.insert(entity_1, 2u32);
+        assert_eq!(world.get::<u32>(entity_1), Some(&2));
+
+        assert_eq!(world.remove::<u32>(entity_1), 2);
+        let entity_2 = world.spawn((3u32,));
+        world.despawn(entity_2);
+        world.spawn((4u32,));
+        world.clear_all_entities();
+
+        assert_eq!(
+            world.resource::<HookLog>().0,
+            vec![
+                ("add", 1),
+                ("insert", 1),
+                ("insert", 2),
+                ("remove", 2),
+                ("add", 3),
+                ("insert", 3),
+                ("remove", 3),
+                ("add", 4),
+                ("insert", 4),
+                ("remove", 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn spawn_bundles() {
+        use std::rc::Rc;
+
+        let mut world = World::new();
+        world.init_component::<u32>();
+        world.init_component::<bool>();
+        world.init_component::<Rc<()>>();
+
+        // The entity goes straight to its final archetype
+        let entity_1 = world.spawn((1u32, true));
+        assert_eq!(world.archetypes.generation, 1);
+        assert_eq!(world.get::<u32>(entity_1), Some(&1));
+        assert_eq!(world.get::<bool>(entity_1), Some(&true));
+
+        let counter = Rc::new(());
+        let entities = world.spawn_batch((0..100u32).map(|i| (i, i % 2 == 0, Rc::clone(&counter))));
+        assert_eq!(entities.len(), 100);
+        assert_eq!(Rc::strong_count(&counter), 101);
+        assert_eq!(world.get::<u32>(entities[42]), Some(&42));
+        assert_eq!(world.get::<bool>(entities[43]), Some(&false));
+        assert_eq!(
+            world.entity_meta(entities[99]).archetype_id,
+            world.entity_meta(entities[0]).archetype_id
+        );
+
+        // Inserting a bundle that overlaps the entity's components replaces them
+        let entity_2 = world.spawn((2u32,));
+        world.insert_bundle(entity_2, (3u32, Rc::clone(&counter)));
+        world.insert_bundle(entity_2, (false, Rc::clone(&counter)));
+        assert_eq!(world.get::<u32>(entity_2), Some(&3));
+        assert_eq!(world.get::<bool>(entity_2), Some(&false));
+        assert_eq!(Rc::strong_count(&counter), 102);
+
+        for entity in entities {
+            world.despawn(entity);
+        }
+        world.despawn(entity_2);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn derived_bundles() {
+        use crate::{Bundle, Component, Name};
+
+        #[derive(Debug, PartialEq, Component)]
+        #[component(on_add = count_added, script_name = "Position")]
+        struct Position(f32, f32);
+
+        #[derive(Debug, PartialEq, Component)]
+        struct Velocity {
+            x: f32,
+            y: f32,
+        }
+
+        #[derive(Bundle)]
+        struct Body {
+            position: Position,
+            velocity: Velocity,
+        }
+
+        #[derive(Bundle)]
+        struct Player {
+            name: Name,
+            body: Body,
+        }
+
+        #[derive(Debug, Default)]
+        struct Added(u32);
+
+        fn count_added(world: &mut World, _entity: EntityId) {
+            world.resource_mut::<Added>().0 += 1;
+        }
+
+        assert_eq!(Position::SCRIPT_NAME, Some("Position"));
+        assert_eq!(Velocity::SCRIPT_NAME, None);
+
+        let mut world = World::new();
+        world.insert_resource(Added::default());
+
+        // Nothing is registered up front
+        let player = world.spawn(Player {
+            name: Name("player".to_string()),
+            body: Body {
+                position: Position(1.0, 2.0),
+                velocity: Velocity { x: 3.0, y: 4.0 },
+            },
+        });
+        assert_eq!(world.get::<Position>(player), Some(&Position(1.0, 2.0)));
+        assert_eq!(
+            world.get::<Velocity>(player),
+            Some(&Velocity { x: 3.0, y: 4.0 })
+        );
+        assert_eq!(
+            world.get::<Name>(player).map(|name| name.0.as_str()),
+            Some("player")
+        );
+
+        // A single derived component is a bundle too
+        let entity = world.spawn(Position(5.0, 6.0));
+        world.insert(entity, 7u64);
+        assert_eq!(world.get::<u64>(entity), Some(&7));
+        assert_eq!(world.resource::<Added>().0, 2);
+    }
+
+    #[test]
+    fn component_metadata_on_insert() {
+        use crate::{Component, StorageType};
+
+        #[derive(Component)]
+        #[component(storage = "table", on_add = count_added, on_remove = count_removed)]
+        struct Health(u32);
+
+        #[derive(Debug, Default)]
+        struct Counts {
+            added: u32,
+            removed: u32,
+        }
+
+        fn count_added(world: &mut World, _entity: EntityId) {
+            world.resource_mut::<Counts>().added += 1;
+        }
+
+        fn count_removed(world: &mut World, _entity: EntityId) {
+            world.resource_mut::<Counts>().removed += 1;
+        }
+
+        let mut world = World::new();
+        world.insert_resource(Counts::default());
+
+        // Neither path registers the component up front
+        let entity_1 = world.spawn_empty();
+        world.insert(entity_1, Health(10));
+        let entity_2 = world.spawn((Health(20), 1u32));
+        assert_eq!(world.resource::<Counts>().added, 2);
+
+        assert_eq!(
+            world.get_component_info::<Health>().storage,
+            StorageType::Table
+        );
+
+        world.despawn(entity_1);
+        world.remove::<Health>(entity_2);
+        assert_eq!(world.resource::<Counts>().removed, 2);
+    }
+
+    #[test]
+    fn fallible_access() {
+        use crate::error::{
+            ComponentError, ComponentNotRegistered, EntityNotFound, ResourceMissing,
+        };
+
+        let mut world = World::new();
+        let entity = world.spawn((1u32,));
+        let despawned = world.spawn((2u32,));
+        world.despawn(despawned);
+
+        assert_eq!(
+            world.try_insert(despawned, true),
+            Err(EntityNotFound(despawned))
+        );
+        assert_eq!(world.try_insert(entity, true), Ok(()));
+        assert!(world.try_entity_meta(despawned).is_err());
+
+        assert_eq!(
+            world.try_remove::<u32>(despawned),
+            Err(ComponentError::EntityNotFound(EntityNotFound(despawned)))
+        );
+        assert_eq!(
+            world.try_remove::<u64>(entity),
+            Err(ComponentError::ComponentNotRegistered(
+                ComponentNotRegistered::of::<u64>()
+            ))
+        );
+        world.init_component::<u64>();
+        assert_eq!(
+            world.try_remove::<u64>(entity),
+            Err(ComponentError::MissingComponent {
+                entity,
+                component: "u64"
+            })
+        );
+        assert_eq!(world.try_remove::<u32>(entity), Ok(1));
+        assert!(world.try_get_component_info::<i8>().is_err());
+
+        assert_eq!(
+            world.try_resource::<String>().err(),
+            Some(ResourceMissing::of::<String>())
+        );
+        world.insert_resource("resource".to_string());
+        world.try_resource_mut::<String>().unwrap().push('!');
+        assert_eq!(
+            world.try_resource::<String>().unwrap().as_str(),
+            "resource!"
+        );
+    }
+
+    #[test]
+    fn mark_changed() {
+        let mut world = World::new();
+        let entity = world.spawn((1u32,));
+
+        world.set_change_tick(Tick::new(5));
+        assert!(world.mark_changed::<u32>(entity));
+        assert!(!world.mark_changed::<bool>(entity));
+
+        let changed = world.query::<EntityId, Changed<u32>>();
+        let ctx = SystemRunContext {
+            world: &world,
+            tick: Tick::new(6),
+            last_run_tick: Tick::new(4),
+        };
+        assert_eq!(changed.over(ctx).collect::<Vec<_>>(), vec![entity]);
     }
 }
@@ -0,0 +1,98 @@
+use acro_ecs::{EntityId, SystemRunContext, World};
+use acro_math::{
+    propagate_global_transform, Children, GlobalTransform, Parent, Transform, TransformBoundary,
+    TransformBundle, Vec3, WorldHierarchyExt,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn criterion_benchmark(c: &mut Criterion) {
+    c.bench_function("propagate deep 1_000", |cx| {
+        let mut world = transform_world();
+        let root = spawn_node(&mut world);
+        let mut parent = root;
+        for _ in 0..1_000 {
+            let child = spawn_node(&mut world);
+            world.set_parent(child, parent).unwrap();
+            parent = child;
+        }
+
+        cx.iter(|| run_frame(&mut world, &[root]));
+    });
+
+    c.bench_function("propagate wide 10_000", |cx| {
+        let mut world = transform_world();
+        let root = spawn_node(&mut world);
+        for _ in 0..10_000 {
+            let child = spawn_node(&mut world);
+            world.set_parent(child, root).unwrap();
+        }
+
+        cx.iter(|| run_frame(&mut world, &[root]));
+    });
+
+    c.bench_function("propagate wide 10_000, every entity changed", |cx| {
+        let mut world = transform_world();
+        let root = spawn_node(&mut world);
+        let mut children = vec![];
+        for _ in 0..10_000 {
+            let child = spawn_node(&mut world);
+            world.set_parent(child, root).unwrap();
+            children.push(child);
+        }
+
+        cx.iter(|| run_frame(&mut world, &children));
+    });
+
+    c.bench_function("propagate 100 trees of 100, one changed", |cx| {
+        let mut world = transform_world();
+        let mut roots = vec![];
+        for _ in 0..100 {
+            let root = spawn_node(&mut world);
+            for _ in 0..100 {
+                let child = spawn_node(&mut world);
+                world.set_parent(child, root).unwrap();
+            }
+            roots.push(root);
+        }
+
+        cx.iter(|| run_frame(&mut world, &roots[50..51]));
+    });
+}
+
+fn transform_world() -> World {
+    let mut world = World::new();
+    world.init_component::<Transform>();
+    world.init_component::<GlobalTransform>();
+    world.init_component::<Parent>();
+    world.init_component::<Children>();
+    world.init_component::<TransformBoundary>();
+    world
+}
+
+fn spawn_node(world: &mut World) -> EntityId {
+    world.spawn(TransformBundle::from_transform(Transform {
+        position: Vec3::new(1.0, 0.0, 0.0),
+        ..Default::default()
+    }))
+}
+
+// Marks `changed` as moved and runs the propagation the way the schedule does every frame
+fn run_frame(world: &mut World, changed: &[EntityId]) {
+    let last_run_tick = world.change_tick();
+    let tick = last_run_tick.next();
+    world.set_change_tick(tick);
+    for &entity in changed {
+        world.mark_changed::<Transform>(entity);
+    }
+
+    let ctx = SystemRunContext {
+        world,
+        tick,
+        last_run_tick,
+    };
+    propagate_global_transform(ctx.clone(), world.query(), world.query(), world.query());
+    black_box(world.get::<GlobalTransform>(changed[0]));
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,636 @@
+use std::{collections::HashSet, fmt};
+
+use acro_assets::Assets;
+use acro_ecs::{
+    query::QueryFilter,
+    systems::{IntoSystemRunContext, SystemRunContext},
+    Disabled, EntityId, Query, World,
+};
+
+use crate::{types::Mat4, Children, Parent, Root, Transform};
+
+/// Why a hierarchy edit was rejected. The hierarchy is left unchanged when one is returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HierarchyError {
+    EntityNotFound(EntityId),
+    /// The parent is the child itself or one of its descendants.
+    Cycle {
+        child: EntityId,
+        parent: EntityId,
+    },
+    NotAChild {
+        parent: EntityId,
+        child: EntityId,
+    },
+}
+
+impl fmt::Display for HierarchyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HierarchyError::EntityNotFound(entity) => write!(f, "entity {entity:?} not found"),
+            HierarchyError::Cycle { child, parent } => write!(
+                f,
+                "cannot make {parent:?} the parent of {child:?}, it would create a cycle"
+            ),
+            HierarchyError::NotAChild { parent, child } => {
+                write!(f, "entity {child:?} is not a child of {parent:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HierarchyError {}
+
+/// Edits `Parent` and `Children` together, so they always agree. The moved entity's `Transform` is
+/// marked as changed, which makes `propagate_global_transform` recompute its whole subtree.
+pub trait WorldHierarchyExt {
+    /// Despawns an entity along with all of its descendants, removing it from its parent's
+    /// children.
+    fn despawn_recursive(&mut self, entity: EntityId);
+
+    /// Adds or removes `Disabled` on an entity and all of its descendants. Use
+    /// `World::insert`/`World::remove` to only change the entity itself.
+    fn set_disabled_recursive(&mut self, entity: EntityId, disabled: bool);
+
+    /// Moves `child` under `parent`, appending it to the parent's children. The child keeps its
+    /// local `Transform`, so it moves along with its new parent, and stops being a `Root`.
+    fn set_parent(&mut self, child: EntityId, parent: EntityId) -> Result<(), HierarchyError>;
+
+    /// Same as `set_parent(child, parent)`.
+    fn add_child(&mut self, parent: EntityId, child: EntityId) -> Result<(), HierarchyError>;
+
+    /// Detaches `child`, which has to be a child of `parent`.
+    fn remove_child(&mut self, parent: EntityId, child: EntityId) -> Result<(), HierarchyError>;
+
+    /// Moves `child` under `new_parent`, adjusting its local `Transform` so it stays where it is
+    /// in the world. Shear from non-uniformly scaled ancestors can't be kept.
+    fn reparent_keep_world_transform(
+        &mut self,
+        child: EntityId,
+        new_parent: EntityId,
+    ) -> Result<(), HierarchyError>;
+
+    /// Removes an entity from its parent, making it the `Root` of its own tree. Does nothing if it
+    /// has no parent.
+    fn detach(&mut self, child: EntityId) -> Result<(), HierarchyError>;
+}
+
+impl WorldHierarchyExt for World {
+    fn despawn_recursive(&mut self, entity: EntityId) {
+        if let Some(&Parent(parent)) = self.get::<Parent>(entity) {
+            if let Some(siblings) = self.get_mut::<Children>(parent) {
+                siblings.0.retain(|&sibling| sibling != entity);
+            }
+        }
+
+        let mut stack = vec![entity];
+        let mut despawned = vec![];
+
+        while let Some(current) = stack.pop() {
+            if let Some(children) = self.get::<Children>(current) {
+                stack.extend(children.0.iter().copied());
+            }
+
+            if self.despawn(current) {
+                despawned.push(current);
+            }
+        }
+
+        if self.resources().contains::<Assets>() {
+            let assets = self.resource::<Assets>();
+            for entity in despawned {
+                assets.remove_notify_changes_from_entity(entity);
+            }
+        }
+    }
+
+    fn set_disabled_recursive(&mut self, entity: EntityId, disabled: bool) {
+        let mut stack = vec![entity];
+        let mut visited = HashSet::new();
+
+        while let Some(current) = stack.pop() {
+            // `Children` inserted by hand can form a cycle
+            if !visited.insert(current) {
+                continue;
+            }
+
+            if let Some(children) = self.get::<Children>(current) {
+                stack.extend(children.0.iter().copied());
+            }
+
+            // Children that were already despawned are skipped
+            if disabled {
+                let _ = self.try_insert(current, Disabled);
+            } else {
+                let _ = self.try_remove::<Disabled>(current);
+            }
+        }
+    }
+
+    fn set_parent(&mut self, child: EntityId, parent: EntityId) -> Result<(), HierarchyError> {
+        check_new_parent(self, child, parent)?;
+
+        if self.get::<Parent>(child).map(|&Parent(current)| current) != Some(parent) {
+            remove_from_parent(self, child);
+            self.insert(child, Parent(parent));
+
+            match self.get_mut::<Children>(parent) {
+                Some(children) => children.0.push(child),
+                None => self.insert(parent, Children(vec![child])),
+            }
+        }
+
+        if self.get::<Root>(child).is_some() {
+            self.remove::<Root>(child);
+        }
+
+        // Propagation and path lookups expect every entity in the tree to have children
+        if self.get::<Children>(child).is_none() {
+            self.insert(child, Children(vec![]));
+        }
+        self.mark_changed::<Transform>(child);
+
+        Ok(())
+    }
+
+    fn add_child(&mut self, parent: EntityId, child: EntityId) -> Result<(), HierarchyError> {
+        self.set_parent(child, parent)
+    }
+
+    fn remove_child(&mut self, parent: EntityId, child: EntityId) -> Result<(), HierarchyError> {
+        check_exists(self, parent)?;
+        check_exists(self, child)?;
+        if self.get::<Parent>(child).map(|&Parent(current)| current) != Some(parent) {
+            return Err(HierarchyError::NotAChild { parent, child });
+        }
+
+        self.detach(child)
+    }
+
+    fn reparent_keep_world_transform(
+        &mut self,
+        child: EntityId,
+        new_parent: EntityId,
+    ) -> Result<(), HierarchyError> {
+        check_new_parent(self, child, new_parent)?;
+
+        // A parent scaled to zero can't be inverted, the local transform is kept in that case
+        let child_matrix = world_matrix(self, child);
+        if let Some(parent_inverse) = world_matrix(self, new_parent).try_inverse() {
+            if let Some(transform) = self.get_mut::<Transform>(child) {
+                *transform = Transform::from_matrix(&(parent_inverse * child_matrix));
+            }
+        }
+
+        self.set_parent(child, new_parent)
+    }
+
+    fn detach(&mut self, child: EntityId) -> Result<(), HierarchyError> {
+        check_exists(self, child)?;
+
+        if remove_from_parent(self, child) {
+            let _ = self.try_remove::<Parent>(child);
+            self.insert(child, Root);
+            self.mark_changed::<Transform>(child);
+        }
+
+        Ok(())
+    }
+}
+
+fn check_exists(world: &World, entity: EntityId) -> Result<(), HierarchyError> {
+    world
+        .entity_meta_opt(entity)
+        .map(|_| ())
+        .ok_or(HierarchyError::EntityNotFound(entity))
+}
+
+fn check_new_parent(
+    world: &World,
+    child: EntityId,
+    parent: EntityId,
+) -> Result<(), HierarchyError> {
+    check_exists(world, child)?;
+    check_exists(world, parent)?;
+
+    let mut current = Some(parent);
+    while let Some(entity) = current {
+        if entity == child {
+            return Err(HierarchyError::Cycle { child, parent });
+        }
+        current = world.get::<Parent>(entity).map(|&Parent(parent)| parent);
+    }
+
+    Ok(())
+}
+
+/// Removes an entity from its parent's children. Returns false if it has no parent.
+fn remove_from_parent(world: &mut World, child: EntityId) -> bool {
+    let Some(&Parent(parent)) = world.get::<Parent>(child) else {
+        return false;
+    };
+    if let Some(siblings) = world.get_mut::<Children>(parent) {
+        siblings.0.retain(|&sibling| sibling != child);
+    }
+    true
+}
+
+// Computed from the local transforms rather than `GlobalTransform`, which is only up to date after
+// propagation has run
+pub(crate) fn world_matrix(world: &World, entity: EntityId) -> Mat4 {
+    let mut matrix = Mat4::identity();
+    let mut current = Some(entity);
+    while let Some(entity) = current {
+        if let Some(transform) = world.get::<Transform>(entity) {
+            matrix = transform.get_matrix() * matrix;
+        }
+        current = world.get::<Parent>(entity).map(|&Parent(parent)| parent);
+    }
+    matrix
+}
+
+pub trait ParentQueryExt<'c, F: QueryFilter> {
+    /// Walks up the hierarchy from an entity, yielding its parent, its parent's parent and so on.
+    /// The entity itself is not included.
+    fn ancestors<'w, 'q>(
+        &'q self,
+        ctx: impl IntoSystemRunContext<'w>,
+        entity: EntityId,
+    ) -> Ancestors<'w, 'q, 'c, F>;
+}
+
+impl<'c, F: QueryFilter + 'static> ParentQueryExt<'c, F> for Query<&'c Parent, F> {
+    fn ancestors<'w, 'q>(
+        &'q self,
+        ctx: impl IntoSystemRunContext<'w>,
+        entity: EntityId,
+    ) -> Ancestors<'w, 'q, 'c, F> {
+        Ancestors {
+            ctx: ctx.into_system_run_context(),
+            query: self,
+            current: Some(entity),
+            visited: HashSet::from([entity]),
+        }
+    }
+}
+
+pub trait ChildrenQueryExt<'c, F: QueryFilter> {
+    /// Walks down the hierarchy from an entity depth first, yielding every descendant before its
+    /// next sibling. The entity itself is not included.
+    fn descendants<'w, 'q>(
+        &'q self,
+        ctx: impl IntoSystemRunContext<'w>,
+        entity: EntityId,
+    ) -> Descendants<'w, 'q, 'c, F>;
+}
+
+impl<'c, F: QueryFilter + 'static> ChildrenQueryExt<'c, F> for Query<&'c Children, F> {
+    fn descendants<'w, 'q>(
+        &'q self,
+        ctx: impl IntoSystemRunContext<'w>,
+        entity: EntityId,
+    ) -> Descendants<'w, 'q, 'c, F> {
+        let ctx = ctx.into_system_run_context();
+        let stack = self
+            .get(ctx.clone(), entity)
+            .map(|children| children.0.iter().rev().copied().collect())
+            .unwrap_or_default();
+
+        Descendants {
+            ctx,
+            query: self,
+            stack,
+            visited: HashSet::from([entity]),
+        }
+    }
+}
+
+/// Iterator returned by [`ParentQueryExt::ancestors`]. Stops at the first entity without a
+/// `Parent` matching the query, or when the parents loop back to an entity already visited.
+pub struct Ancestors<'w, 'q, 'c, F: QueryFilter> {
+    ctx: SystemRunContext<'w>,
+    query: &'q Query<&'c Parent, F>,
+    current: Option<EntityId>,
+    // `set_parent` rejects cycles, but `Parent` can still be inserted by hand
+    visited: HashSet<EntityId>,
+}
+
+impl<F: QueryFilter + 'static> Iterator for Ancestors<'_, '_, '_, F> {
+    type Item = EntityId;
+
+    fn next(&mut self) -> Option<EntityId> {
+        let parent = self
+            .current
+            .and_then(|current| self.query.get(self.ctx.clone(), current))
+            .map(|&Parent(parent)| parent)
+            .filter(|&parent| self.visited.insert(parent));
+        self.current = parent;
+        parent
+    }
+}
+
+/// Iterator returned by [`ChildrenQueryExt::descendants`]. Entities without `Children` matching
+/// the query are treated as leaves, and entities already visited are skipped.
+pub struct Descendants<'w, 'q, 'c, F: QueryFilter> {
+    ctx: SystemRunContext<'w>,
+    query: &'q Query<&'c Children, F>,
+    stack: Vec<EntityId>,
+    // Like `Parent`, `Children` can be edited by hand into a cycle
+    visited: HashSet<EntityId>,
+}
+
+impl<F: QueryFilter + 'static> Iterator for Descendants<'_, '_, '_, F> {
+    type Item = EntityId;
+
+    fn next(&mut self) -> Option<EntityId> {
+        loop {
+            let entity = self.stack.pop()?;
+            if !self.visited.insert(entity) {
+                continue;
+            }
+
+            if let Some(children) = self.query.get(self.ctx.clone(), entity) {
+                self.stack.extend(children.0.iter().rev().copied());
+            }
+            return Some(entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acro_ecs::{Changed, Disabled, EntityId, SystemRunContext, Tick, With, World};
+
+    use crate::{
+        propagate_global_transform, types::Vec3, Children, GlobalTransform, Parent, Root,
+        Transform, TransformBoundary,
+    };
+
+    use super::{ChildrenQueryExt, HierarchyError, ParentQueryExt, WorldHierarchyExt};
+
+    #[test]
+    fn despawn_recursive() {
+        let mut world = World::new();
+        world.init_component::<Root>();
+        world.init_component::<Parent>();
+        world.init_component::<Children>();
+
+        let root = world.spawn((Root,));
+        let child_1 = world.spawn((Parent(root),));
+        let child_2 = world.spawn((Parent(root), Children(vec![])));
+        let child_of_child_1 = world.spawn((Parent(child_1), Children(vec![])));
+
+        world.insert(child_1, Children(vec![child_of_child_1]));
+        world.insert(root, Children(vec![child_1, child_2]));
+
+        world.despawn_recursive(child_1);
+
+        assert!(world.entity_meta_opt(child_1).is_none());
+        assert!(world.entity_meta_opt(child_of_child_1).is_none());
+        assert!(world.entity_meta_opt(child_2).is_some());
+        assert_eq!(world.get::<Children>(root).unwrap().0, vec![child_2]);
+
+        world.despawn_recursive(root);
+
+        assert!(world.entity_meta_opt(root).is_none());
+        assert!(world.entity_meta_opt(child_2).is_none());
+    }
+
+    #[test]
+    fn hierarchy_iterators() {
+        let mut world = World::new();
+        world.init_component::<Root>();
+        world.init_component::<Parent>();
+        world.init_component::<Children>();
+        world.init_component::<u32>();
+
+        let root = world.spawn((Root,));
+        let child_1 = world.spawn((Parent(root), 1u32));
+        let child_2 = world.spawn((Parent(root), Children(vec![]), 2u32));
+        let child_of_child_1 = world.spawn((Parent(child_1), Children(vec![]), 3u32));
+
+        world.insert(child_1, Children(vec![child_of_child_1]));
+        world.insert(root, Children(vec![child_1, child_2]));
+
+        let parent_query = world.query::<&Parent, ()>();
+        let children_query = world.query::<&Children, ()>();
+
+        assert_eq!(
+            parent_query
+                .ancestors(&world, child_of_child_1)
+                .collect::<Vec<_>>(),
+            vec![child_1, root]
+        );
+        assert_eq!(parent_query.ancestors(&world, root).count(), 0);
+        assert_eq!(
+            children_query.descendants(&world, root).collect::<Vec<_>>(),
+            vec![child_1, child_of_child_1, child_2]
+        );
+        assert_eq!(children_query.descendants(&world, child_2).count(), 0);
+
+        // A cycle made by inserting `Parent` directly ends the walk instead of looping forever
+        world.insert(root, Parent(child_of_child_1));
+        assert_eq!(
+            parent_query
+                .ancestors(&world, child_of_child_1)
+                .collect::<Vec<_>>(),
+            vec![child_1, root]
+        );
+        world.remove::<Parent>(root);
+
+        // Same for `Children`
+        world.insert(child_of_child_1, Children(vec![root, child_1]));
+        assert_eq!(
+            children_query.descendants(&world, root).collect::<Vec<_>>(),
+            vec![child_1, child_of_child_1, child_2]
+        );
+        world.insert(child_of_child_1, Children(vec![]));
+
+        // The entities can be looked up with any other query
+        let value_query = world.query::<&u32, ()>();
+        let sum = children_query
+            .descendants(&world, root)
+            .filter_map(|entity| value_query.get(&world, entity))
+            .sum::<u32>();
+        assert_eq!(sum, 6);
+    }
+
+    #[test]
+    fn disable_recursive() {
+        let mut world = World::new();
+        world.init_component::<Root>();
+        world.init_component::<Parent>();
+        world.init_component::<Children>();
+
+        let root = world.spawn((Root,));
+        let child_1 = world.spawn((Parent(root),));
+        let child_2 = world.spawn((Parent(root), Children(vec![])));
+        let child_of_child_1 = world.spawn((Parent(child_1), Children(vec![])));
+
+        world.insert(child_1, Children(vec![child_of_child_1]));
+        world.insert(root, Children(vec![child_1, child_2]));
+
+        world.set_disabled_recursive(child_1, true);
+
+        let parent_query = world.query::<EntityId, With<Parent>>();
+        assert_eq!(parent_query.over(&world).collect::<Vec<_>>(), vec![child_2]);
+        assert!(world.get::<Disabled>(child_of_child_1).is_some());
+
+        world.set_disabled_recursive(root, false);
+        assert_eq!(parent_query.over(&world).count(), 3);
+
+        // Terminates on a cycle made by inserting `Children` directly
+        world.insert(child_of_child_1, Children(vec![child_1]));
+        world.set_disabled_recursive(child_1, true);
+        assert_eq!(parent_query.over(&world).collect::<Vec<_>>(), vec![child_2]);
+    }
+
+    fn spawn_node(world: &mut World, position: Vec3) -> EntityId {
+        world.spawn((
+            Transform {
+                position,
+                ..Default::default()
+            },
+            GlobalTransform::default(),
+        ))
+    }
+
+    #[test]
+    fn set_parent() {
+        let mut world = World::new();
+        let root = world.spawn((Root, Transform::default(), GlobalTransform::default()));
+        let a = spawn_node(&mut world, Vec3::zeros());
+        let b = spawn_node(&mut world, Vec3::zeros());
+        let child = spawn_node(&mut world, Vec3::zeros());
+
+        world.add_child(root, a).unwrap();
+        world.add_child(root, b).unwrap();
+        world.set_parent(child, a).unwrap();
+        assert_eq!(world.get::<Children>(root).unwrap().0, vec![a, b]);
+        assert_eq!(world.get::<Children>(a).unwrap().0, vec![child]);
+        assert!(world.get::<Children>(child).unwrap().0.is_empty());
+
+        world.set_change_tick(Tick::new(5));
+        world.set_parent(child, b).unwrap();
+        assert_eq!(world.get::<Parent>(child).unwrap().0, b);
+        assert!(world.get::<Children>(a).unwrap().0.is_empty());
+        assert_eq!(world.get::<Children>(b).unwrap().0, vec![child]);
+
+        // Setting the same parent again does not duplicate the child
+        world.set_parent(child, b).unwrap();
+        assert_eq!(world.get::<Children>(b).unwrap().0, vec![child]);
+
+        let changed = world.query::<EntityId, Changed<Transform>>();
+        let ctx = SystemRunContext {
+            world: &world,
+            tick: Tick::new(6),
+            last_run_tick: Tick::new(4),
+        };
+        assert_eq!(changed.over(ctx).collect::<Vec<_>>(), vec![child]);
+    }
+
+    #[test]
+    fn set_parent_rejects_cycles() {
+        let mut world = World::new();
+        let a = spawn_node(&mut world, Vec3::zeros());
+        let b = spawn_node(&mut world, Vec3::zeros());
+        let c = spawn_node(&mut world, Vec3::zeros());
+        world.set_parent(b, a).unwrap();
+        world.set_parent(c, b).unwrap();
+
+        assert_eq!(
+            world.set_parent(a, c),
+            Err(HierarchyError::Cycle {
+                child: a,
+                parent: c
+            })
+        );
+        assert_eq!(
+            world.set_parent(a, a),
+            Err(HierarchyError::Cycle {
+                child: a,
+                parent: a
+            })
+        );
+        assert!(world.get::<Parent>(a).is_none());
+        assert_eq!(world.get::<Children>(c).unwrap().0, vec![]);
+
+        world.despawn(c);
+        assert_eq!(
+            world.set_parent(a, c),
+            Err(HierarchyError::EntityNotFound(c))
+        );
+    }
+
+    #[test]
+    fn detach_and_remove_child() {
+        let mut world = World::new();
+        let parent = spawn_node(&mut world, Vec3::zeros());
+        let child_1 = spawn_node(&mut world, Vec3::zeros());
+        let child_2 = spawn_node(&mut world, Vec3::zeros());
+        world.add_child(parent, child_1).unwrap();
+        world.add_child(parent, child_2).unwrap();
+
+        world.detach(child_1).unwrap();
+        assert!(world.get::<Parent>(child_1).is_none());
+        assert!(world.get::<Root>(child_1).is_some());
+        assert_eq!(world.get::<Children>(parent).unwrap().0, vec![child_2]);
+        // Detaching a root does nothing
+        world.detach(child_1).unwrap();
+        assert!(world.get::<Root>(child_1).is_some());
+
+        assert_eq!(
+            world.remove_child(child_1, child_2),
+            Err(HierarchyError::NotAChild {
+                parent: child_1,
+                child: child_2
+            })
+        );
+        world.remove_child(parent, child_2).unwrap();
+        assert!(world.get::<Parent>(child_2).is_none());
+        assert!(world.get::<Root>(child_2).is_some());
+        assert!(world.get::<Children>(parent).unwrap().0.is_empty());
+
+        // Parenting a root again makes it part of the other tree
+        world.set_parent(child_1, child_2).unwrap();
+        assert!(world.get::<Root>(child_1).is_none());
+        assert_eq!(world.get::<Children>(child_2).unwrap().0, vec![child_1]);
+    }
+
+    #[test]
+    fn reparent_keep_world_transform() {
+        let mut world = World::new();
+        world.init_component::<TransformBoundary>();
+        let root = world.spawn((Root, Transform::default(), GlobalTransform::default()));
+        let a = spawn_node(&mut world, Vec3::new(1.0, 0.0, 0.0));
+        let b = world.spawn((
+            Transform {
+                position: Vec3::new(0.0, 5.0, 0.0),
+                scale: Vec3::new(2.0, 2.0, 2.0),
+                ..Default::default()
+            }
+            .with_euler_angles(Vec3::new(0.0, 0.0, std::f32::consts::FRAC_PI_2 as _)),
+            GlobalTransform::default(),
+        ));
+        let child = spawn_node(&mut world, Vec3::new(0.0, 1.0, 0.0));
+        world.add_child(root, a).unwrap();
+        world.add_child(root, b).unwrap();
+        world.add_child(a, child).unwrap();
+
+        world.reparent_keep_world_transform(child, b).unwrap();
+        assert_eq!(world.get::<Parent>(child).unwrap().0, b);
+
+        world
+            .run_system(propagate_global_transform, Tick::new(1))
+            .expect("system failed to run");
+        let matrix = world.get::<GlobalTransform>(child).unwrap().matrix;
+        let position = matrix.fixed_view::<3, 1>(0, 3).into_owned();
+        assert!(
+            (position - Vec3::new(1.0, 1.0, 0.0)).norm() < 1e-5,
+            "{position:?}"
+        );
+
+        let transform = world.get::<Transform>(child).unwrap();
+        assert!((transform.scale - Vec3::new(0.5, 0.5, 0.5)).norm() < 1e-5);
+    }
+}
@@ -1,19 +1,28 @@
+mod hierarchy;
 mod ops;
+mod primitives;
 mod transform;
 mod tree;
 mod types;
 
 pub use crate::{
+    hierarchy::{
+        Ancestors, ChildrenQueryExt, Descendants, HierarchyError, ParentQueryExt, WorldHierarchyExt,
+    },
+    primitives::{Aabb, BoundingSphere, Frustum, Plane, Ray},
     transform::{
         propagate_global_transform, Children, GlobalTransform, Parent, Root, Transform,
-        TransformBoundary,
+        TransformBoundary, TransformBundle,
     },
     types::*,
 };
 
 use acro_ecs::{schedule::Stage, Application, Plugin};
 use acro_scripting::ScriptingRuntime;
-use ops::op_get_entity_by_absolute_path;
+use ops::{
+    op_get_entity_by_absolute_path, op_get_global_transform, op_global_transform_point,
+    op_transform_look_at,
+};
 use tree::TreeData;
 
 pub struct MathPlugin {
@@ -28,17 +37,17 @@ impl Default for MathPlugin {
 
 impl Plugin for MathPlugin {
     fn build(&mut self, app: &mut Application) {
-        app.init_component::<Transform>()
-            .init_component::<GlobalTransform>()
-            .init_component::<Parent>()
-            .init_component::<Children>()
-            .init_component::<Root>()
-            .init_component::<TransformBoundary>()
+        app.register_component::<Transform>()
+            .register_component::<GlobalTransform>()
+            .register_component::<Parent>()
+            .register_component::<Children>()
+            .register_component::<Root>()
+            .register_component::<TransformBoundary>()
             .add_system(Stage::PostUpdate, [], propagate_global_transform);
 
         if self.scripting {
             app.with_resource::<ScriptingRuntime>(|mut runtime| {
-                runtime.register_component::<Transform>("Transform");
+                runtime.register_script_component::<Transform>();
 
                 #[cfg(not(target_arch = "wasm32"))]
                 {
@@ -51,6 +60,9 @@ impl Plugin for MathPlugin {
                     runtime.native_add_op(op_set_property_vec4());
 
                     runtime.native_add_op(op_get_entity_by_absolute_path());
+                    runtime.native_add_op(op_get_global_transform());
+                    runtime.native_add_op(op_global_transform_point());
+                    runtime.native_add_op(op_transform_look_at());
                 }
             });
         }
@@ -58,4 +70,16 @@ impl Plugin for MathPlugin {
         let tree = TreeData::new(&*app.world());
         app.insert_resource(tree);
     }
+
+    fn name(&self) -> &'static str {
+        "math"
+    }
+
+    fn dependencies(&self) -> Vec<&'static str> {
+        if self.scripting {
+            vec!["scripting"]
+        } else {
+            vec![]
+        }
+    }
 }
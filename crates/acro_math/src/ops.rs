@@ -3,13 +3,104 @@ use acro_scripting::wasm_ops;
 use cfg_if::cfg_if;
 use std::{cell::RefCell, rc::Rc};
 
-use acro_ecs::{EntityId, World};
+use acro_ecs::{EntityId, Tick, World};
+#[cfg(not(target_arch = "wasm32"))]
+use acro_scripting::eyre_to_any_error;
 #[cfg(not(target_arch = "wasm32"))]
 use deno_core::op2;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
-use crate::tree::WorldTreeExt;
+use crate::{
+    hierarchy::world_matrix,
+    tree::WorldTreeExt,
+    types::{Float, Vec3},
+    GlobalTransform, Parent, Transform,
+};
+
+/// World-space data of a `GlobalTransform`, as sent to scripts.
+#[derive(serde::Serialize)]
+pub struct GlobalTransformData {
+    position: [Float; 3],
+    /// `[x, y, z, w]`
+    rotation: [Float; 4],
+    scale: [Float; 3],
+    forward: [Float; 3],
+    right: [Float; 3],
+    up: [Float; 3],
+}
+
+impl From<&GlobalTransform> for GlobalTransformData {
+    fn from(global_transform: &GlobalTransform) -> Self {
+        let transform = global_transform.to_transform();
+        Self {
+            position: transform.position.into(),
+            rotation: transform.unit_rotation().coords.into(),
+            scale: transform.scale.into(),
+            forward: global_transform.forward().into(),
+            right: global_transform.right().into(),
+            up: global_transform.up().into(),
+        }
+    }
+}
+
+fn get_global_transform(world: &World, generation: u32, index: u32) -> Option<GlobalTransformData> {
+    world
+        .get::<GlobalTransform>(EntityId::new(generation, index))
+        .map(GlobalTransformData::from)
+}
+
+fn global_transform_point(
+    world: &World,
+    generation: u32,
+    index: u32,
+    point: Vec3,
+    inverse: bool,
+) -> Option<[Float; 3]> {
+    let global_transform = world.get::<GlobalTransform>(EntityId::new(generation, index))?;
+    let global_transform = if inverse {
+        global_transform.inverse()?
+    } else {
+        *global_transform
+    };
+    Some(global_transform.transform_point(&point).into())
+}
+
+// The target is in world space, so it is moved into the parent's space first. The parent's world
+// matrix is computed from the local transforms, as they may have changed earlier this frame.
+fn transform_look_at(
+    world: &World,
+    tick: &Tick,
+    generation: u32,
+    index: u32,
+    target: Vec3,
+    up: Vec3,
+) -> eyre::Result<()> {
+    let entity = EntityId::new(generation, index);
+    let (target, up) = match world.get::<Parent>(entity) {
+        Some(&Parent(parent)) => {
+            let parent_inverse = GlobalTransform::from_matrix(world_matrix(world, parent))
+                .inverse()
+                .ok_or_else(|| eyre::eyre!("parent is scaled to zero"))?;
+            (
+                parent_inverse.transform_point(&target),
+                parent_inverse.transform_vector(&up),
+            )
+        }
+        None => (target, up),
+    };
+
+    let component_id = world.try_get_component_info::<Transform>()?.id;
+    let transform = world
+        .get_ptr(entity, component_id, Some(*tick))
+        .ok_or_else(|| eyre::eyre!("entity does not have a transform"))?;
+    // SAFETY: the pointer points to a `Transform`, scripts run one at a time so nothing else
+    // borrows it
+    let transform = unsafe { &mut *(transform.as_ptr() as *mut Transform) };
+    transform.look_at(target, up);
+
+    Ok(())
+}
 
 cfg_if! {
     if #[cfg(not(target_arch = "wasm32"))] {
@@ -21,6 +112,50 @@ cfg_if! {
         ) -> Option<EntityId> {
             world.borrow().get_entity_by_absolute_path(&path)
         }
+
+        #[op2]
+        #[serde]
+        pub fn op_get_global_transform(
+            #[state] world: &Rc<RefCell<World>>,
+            generation: u32,
+            index: u32,
+        ) -> Option<GlobalTransformData> {
+            get_global_transform(&world.borrow(), generation, index)
+        }
+
+        #[op2]
+        #[serde]
+        pub fn op_global_transform_point(
+            #[state] world: &Rc<RefCell<World>>,
+            generation: u32,
+            index: u32,
+            x: f64,
+            y: f64,
+            z: f64,
+            inverse: bool,
+        ) -> Option<[Float; 3]> {
+            let point = Vec3::new(x as Float, y as Float, z as Float);
+            global_transform_point(&world.borrow(), generation, index, point, inverse)
+        }
+
+        #[op2(fast)]
+        pub fn op_transform_look_at(
+            #[state] world: &Rc<RefCell<World>>,
+            #[state] tick: &Tick,
+            generation: u32,
+            index: u32,
+            target_x: f64,
+            target_y: f64,
+            target_z: f64,
+            up_x: f64,
+            up_y: f64,
+            up_z: f64,
+        ) -> Result<(), deno_core::error::AnyError> {
+            let target = Vec3::new(target_x as Float, target_y as Float, target_z as Float);
+            let up = Vec3::new(up_x as Float, up_y as Float, up_z as Float);
+            transform_look_at(&world.borrow(), tick, generation, index, target, up)
+                .map_err(eyre_to_any_error)
+        }
     } else {
         #[wasm_bindgen]
         pub fn op_get_entity_by_absolute_path(path: &str) -> Result<JsValue, JsError> {
@@ -32,5 +167,53 @@ cfg_if! {
                 None => Ok(JsValue::null()),
             }
         }
+
+        #[wasm_bindgen]
+        pub fn op_get_global_transform(generation: u32, index: u32) -> Result<JsValue, JsError> {
+            let (world, ..) = wasm_ops::get_ecs_state();
+            match get_global_transform(&world.borrow(), generation, index) {
+                Some(data) => {
+                    Ok(serde_wasm_bindgen::to_value(&data).map_err(wasm_ops::into_js_error)?)
+                }
+                None => Ok(JsValue::null()),
+            }
+        }
+
+        #[wasm_bindgen]
+        pub fn op_global_transform_point(
+            generation: u32,
+            index: u32,
+            x: f64,
+            y: f64,
+            z: f64,
+            inverse: bool,
+        ) -> Result<JsValue, JsError> {
+            let (world, ..) = wasm_ops::get_ecs_state();
+            let point = Vec3::new(x as Float, y as Float, z as Float);
+            match global_transform_point(&world.borrow(), generation, index, point, inverse) {
+                Some(point) => {
+                    Ok(serde_wasm_bindgen::to_value(&point).map_err(wasm_ops::into_js_error)?)
+                }
+                None => Ok(JsValue::null()),
+            }
+        }
+
+        #[wasm_bindgen]
+        pub fn op_transform_look_at(
+            generation: u32,
+            index: u32,
+            target_x: f64,
+            target_y: f64,
+            target_z: f64,
+            up_x: f64,
+            up_y: f64,
+            up_z: f64,
+        ) -> Result<(), JsError> {
+            let (world, _, tick) = wasm_ops::get_ecs_state();
+            let target = Vec3::new(target_x as Float, target_y as Float, target_z as Float);
+            let up = Vec3::new(up_x as Float, up_y as Float, up_z as Float);
+            transform_look_at(&world.borrow(), tick, generation, index, target, up)
+                .map_err(wasm_ops::into_js_error)
+        }
     }
 }
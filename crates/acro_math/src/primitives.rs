@@ -0,0 +1,621 @@
+use acro_reflect::Reflect;
+
+use crate::types::{Float, Mat4, Vec3, Vec4};
+
+/// An axis-aligned bounding box. `min` should be less than or equal to `max` on every axis.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, serde::Serialize, serde::Deserialize)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center_half_extents(center: Vec3, half_extents: Vec3) -> Self {
+        Self {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    /// The smallest box containing all the points, or `None` if there are none.
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |aabb, point| {
+            aabb.expanded_to_include(point)
+        }))
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) / 2.0
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    pub fn expanded_to_include(&self, point: Vec3) -> Self {
+        Self {
+            min: self.min.inf(&point),
+            max: self.max.sup(&point),
+        }
+    }
+
+    pub fn union(&self, other: &Aabb) -> Self {
+        Self {
+            min: self.min.inf(&other.min),
+            max: self.max.sup(&other.max),
+        }
+    }
+
+    /// The point in the box closest to `point`, which is `point` itself if it is inside.
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        point.sup(&self.min).inf(&self.max)
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        (0..3).all(|axis| self.min[axis] <= point[axis] && point[axis] <= self.max[axis])
+    }
+
+    pub fn contains_aabb(&self, other: &Aabb) -> bool {
+        self.contains_point(other.min) && self.contains_point(other.max)
+    }
+
+    /// Touching boxes count as intersecting.
+    pub fn intersects_aabb(&self, other: &Aabb) -> bool {
+        (0..3).all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        sphere.intersects_aabb(self)
+    }
+
+    /// The box containing this box after it is transformed, e.g. to move a mesh's bounds into
+    /// world space. Rotations make the result larger than the transformed contents.
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        let center = matrix.transform_point(&self.center().into()).coords;
+        let linear = matrix.fixed_view::<3, 3>(0, 0).abs();
+        Self::from_center_half_extents(center, linear * self.half_extents())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, serde::Serialize, serde::Deserialize)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: Float,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Vec3, radius: Float) -> Self {
+        Self { center, radius }
+    }
+
+    /// The sphere through the box's corners.
+    pub fn from_aabb(aabb: &Aabb) -> Self {
+        Self::new(aabb.center(), aabb.half_extents().norm())
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        (point - self.center).norm_squared() <= self.radius * self.radius
+    }
+
+    pub fn intersects_sphere(&self, other: &BoundingSphere) -> bool {
+        let radii = self.radius + other.radius;
+        (other.center - self.center).norm_squared() <= radii * radii
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.contains_point(aabb.closest_point(self.center))
+    }
+
+    /// The sphere containing this sphere after it is transformed. Non-uniform scale grows the
+    /// radius by the largest scale factor.
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        let center = matrix.transform_point(&self.center.into()).coords;
+        let linear = matrix.fixed_view::<3, 3>(0, 0);
+        let scale = (0..3)
+            .map(|axis| linear.column(axis).norm())
+            .fold(0.0, Float::max);
+        Self::new(center, self.radius * scale)
+    }
+}
+
+/// The points `p` where `normal.dot(p) == distance`. The normal is expected to be normalized,
+/// which the constructors take care of.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, serde::Serialize, serde::Deserialize)]
+pub struct Plane {
+    pub normal: Vec3,
+    pub distance: Float,
+}
+
+impl Plane {
+    pub fn new(normal: Vec3, distance: Float) -> Self {
+        let length = normal.norm();
+        Self {
+            normal: normal / length,
+            distance: distance / length,
+        }
+    }
+
+    pub fn from_point_normal(point: Vec3, normal: Vec3) -> Self {
+        let normal = normal.normalize();
+        Self {
+            normal,
+            distance: normal.dot(&point),
+        }
+    }
+
+    /// The plane through three points, facing the side they wind counter-clockwise around. `None`
+    /// if the points are on a line.
+    pub fn from_points(a: Vec3, b: Vec3, c: Vec3) -> Option<Self> {
+        let normal = (b - a).cross(&(c - a)).try_normalize(Float::EPSILON)?;
+        Some(Self::from_point_normal(a, normal))
+    }
+
+    /// Positive in front of the plane, on the side the normal points to.
+    pub fn signed_distance(&self, point: Vec3) -> Float {
+        self.normal.dot(&point) - self.distance
+    }
+
+    pub fn project_point(&self, point: Vec3) -> Vec3 {
+        point - self.normal * self.signed_distance(point)
+    }
+}
+
+/// A half-line starting at `origin`. Hits are reported as the distance `t` along it, the hit point
+/// is `ray.at(t)`. Distances are in multiples of `direction`, which `Ray::new` normalizes.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, serde::Serialize, serde::Deserialize)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    pub fn at(&self, t: Float) -> Vec3 {
+        self.origin + self.direction * t
+    }
+
+    /// Hits the plane from either side. Rays parallel to the plane never hit it.
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<Float> {
+        let facing = plane.normal.dot(&self.direction);
+        if facing.abs() <= Float::EPSILON {
+            return None;
+        }
+
+        let t = -plane.signed_distance(self.origin) / facing;
+        (t >= 0.0).then_some(t)
+    }
+
+    /// The distance to the first point in the box, 0 if the ray starts inside it.
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<Float> {
+        let mut t_min: Float = 0.0;
+        let mut t_max = Float::INFINITY;
+
+        for axis in 0..3 {
+            let origin = self.origin[axis];
+            let direction = self.direction[axis];
+            if direction.abs() <= Float::EPSILON {
+                // Parallel to the slab, it misses unless it starts between the sides
+                if origin < aabb.min[axis] || origin > aabb.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+
+            let near = (aabb.min[axis] - origin) / direction;
+            let far = (aabb.max[axis] - origin) / direction;
+            t_min = t_min.max(near.min(far));
+            t_max = t_max.min(near.max(far));
+            if t_min > t_max {
+                return None;
+            }
+        }
+
+        Some(t_min)
+    }
+
+    /// The distance to the first point in the sphere, 0 if the ray starts inside it.
+    pub fn intersect_sphere(&self, sphere: &BoundingSphere) -> Option<Float> {
+        let offset = self.origin - sphere.center;
+        let a = self.direction.norm_squared();
+        let b = offset.dot(&self.direction);
+        let c = offset.norm_squared() - sphere.radius * sphere.radius;
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+
+        let discriminant = b * b - a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let t = (-b - discriminant.sqrt()) / a;
+        (t >= 0.0).then_some(t)
+    }
+
+    /// Hits the triangle from either side (Möller–Trumbore).
+    pub fn intersect_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<Float> {
+        let edge_1 = b - a;
+        let edge_2 = c - a;
+        let p = self.direction.cross(&edge_2);
+        let determinant = edge_1.dot(&p);
+        if determinant.abs() <= Float::EPSILON {
+            return None;
+        }
+
+        let inverse_determinant = 1.0 / determinant;
+        let offset = self.origin - a;
+        let u = offset.dot(&p) * inverse_determinant;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = offset.cross(&edge_1);
+        let v = self.direction.dot(&q) * inverse_determinant;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge_2.dot(&q) * inverse_determinant;
+        (t >= 0.0).then_some(t)
+    }
+}
+
+/// The volume a camera can see, as six planes facing inwards.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, serde::Serialize, serde::Deserialize)]
+pub struct Frustum {
+    pub left: Plane,
+    pub right: Plane,
+    pub bottom: Plane,
+    pub top: Plane,
+    pub near: Plane,
+    pub far: Plane,
+}
+
+impl Frustum {
+    /// Extracts the planes from a view-projection matrix, `projection * view`. The projection
+    /// maps depth to -1..1, like `Mat4::new_perspective` and `Mat4::new_orthographic` do.
+    pub fn from_view_projection(view_projection: &Mat4) -> Self {
+        let row = |index: usize| view_projection.row(index).transpose();
+        let plane = |coefficients: Vec4| Plane::new(coefficients.xyz(), -coefficients.w);
+
+        Self {
+            left: plane(row(3) + row(0)),
+            right: plane(row(3) - row(0)),
+            bottom: plane(row(3) + row(1)),
+            top: plane(row(3) - row(1)),
+            near: plane(row(3) + row(2)),
+            far: plane(row(3) - row(2)),
+        }
+    }
+
+    pub fn planes(&self) -> [Plane; 6] {
+        [
+            self.left,
+            self.right,
+            self.bottom,
+            self.top,
+            self.near,
+            self.far,
+        ]
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes()
+            .iter()
+            .all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes()
+            .iter()
+            .all(|plane| plane.signed_distance(sphere.center) >= -sphere.radius)
+    }
+
+    /// Conservative, which is what culling needs: a box outside the frustum but near one of its
+    /// corners can still be reported as intersecting.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        // The corner furthest along each plane's normal has to be in front of it
+        self.planes().iter().all(|plane| {
+            let corner = Vec3::from_fn(|axis, _| {
+                if plane.normal[axis] >= 0.0 {
+                    aabb.max[axis]
+                } else {
+                    aabb.min[axis]
+                }
+            });
+            plane.signed_distance(corner) >= 0.0
+        })
+    }
+
+    pub fn contains_aabb(&self, aabb: &Aabb) -> bool {
+        // The corner closest along each plane's normal has to be in front of it
+        self.planes().iter().all(|plane| {
+            let corner = Vec3::from_fn(|axis, _| {
+                if plane.normal[axis] >= 0.0 {
+                    aabb.min[axis]
+                } else {
+                    aabb.max[axis]
+                }
+            });
+            plane.signed_distance(corner) >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acro_reflect::{ReflectExt, ReflectPath};
+    use nalgebra::Point3;
+
+    use crate::types::{Float, Mat4, Vec3};
+
+    use super::{Aabb, BoundingSphere, Frustum, Plane, Ray};
+
+    const FRAC_PI_2: Float = std::f64::consts::FRAC_PI_2 as Float;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).norm() < 1e-4, "{a:?} != {b:?}");
+    }
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn aabb() {
+        let aabb = Aabb::from_points([
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(-1.0, 1.0, 0.5),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ])
+        .unwrap();
+        assert_eq!(aabb, unit_box());
+        assert_eq!(Aabb::from_points([]), None);
+        assert_eq!(
+            Aabb::from_center_half_extents(Vec3::zeros(), Vec3::new(1.0, 1.0, 1.0)),
+            aabb
+        );
+        assert_eq!(aabb.size(), Vec3::new(2.0, 2.0, 2.0));
+
+        assert!(aabb.contains_point(Vec3::new(1.0, 0.0, -0.5)));
+        assert!(!aabb.contains_point(Vec3::new(1.5, 0.0, 0.0)));
+        assert_eq!(
+            aabb.closest_point(Vec3::new(3.0, 0.5, -2.0)),
+            Vec3::new(1.0, 0.5, -1.0)
+        );
+
+        let inner = Aabb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.5, 0.5, 0.5));
+        let overlapping = Aabb::new(Vec3::new(0.5, 0.5, 0.5), Vec3::new(2.0, 2.0, 2.0));
+        let touching = Aabb::new(Vec3::new(1.0, -1.0, -1.0), Vec3::new(2.0, 1.0, 1.0));
+        let apart = Aabb::new(Vec3::new(1.5, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0));
+        assert!(aabb.contains_aabb(&inner));
+        assert!(!aabb.contains_aabb(&overlapping));
+        assert!(aabb.intersects_aabb(&overlapping));
+        assert!(aabb.intersects_aabb(&touching));
+        assert!(!aabb.intersects_aabb(&apart));
+        assert!(!apart.intersects_aabb(&aabb));
+        assert_eq!(
+            aabb.union(&overlapping),
+            Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(2.0, 2.0, 2.0))
+        );
+    }
+
+    #[test]
+    fn aabb_transformed() {
+        let aabb = Aabb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0));
+
+        let moved = aabb.transformed(&Mat4::new_translation(&Vec3::new(1.0, 2.0, 3.0)));
+        assert_close(moved.min, Vec3::new(1.0, 2.0, 3.0));
+        assert_close(moved.max, Vec3::new(3.0, 3.0, 4.0));
+
+        // A quarter turn around z swaps the x and y extents
+        let rotated = aabb.transformed(&Mat4::from_scaled_axis(Vec3::z() * FRAC_PI_2));
+        assert_close(rotated.min, Vec3::new(-1.0, 0.0, 0.0));
+        assert_close(rotated.max, Vec3::new(0.0, 2.0, 1.0));
+
+        // An eighth turn grows the box to fit the rotated corners
+        let rotated = unit_box().transformed(&Mat4::from_scaled_axis(Vec3::z() * FRAC_PI_2 / 2.0));
+        let extent = Float::sqrt(2.0);
+        assert_close(rotated.max, Vec3::new(extent, extent, 1.0));
+    }
+
+    #[test]
+    fn bounding_sphere() {
+        let sphere = BoundingSphere::new(Vec3::new(0.0, 0.0, 0.0), 1.0);
+        assert!(sphere.contains_point(Vec3::new(0.0, 1.0, 0.0)));
+        assert!(!sphere.contains_point(Vec3::new(0.8, 0.8, 0.0)));
+
+        assert!(sphere.intersects_sphere(&BoundingSphere::new(Vec3::new(1.5, 0.0, 0.0), 0.5)));
+        assert!(!sphere.intersects_sphere(&BoundingSphere::new(Vec3::new(1.5, 0.0, 0.0), 0.4)));
+
+        // The box's corner is further than the radius, but its face is not
+        let aabb = Aabb::new(Vec3::new(0.9, -5.0, -5.0), Vec3::new(2.0, 5.0, 5.0));
+        assert!(sphere.intersects_aabb(&aabb));
+        assert!(aabb.intersects_sphere(&sphere));
+        let corner = Aabb::new(Vec3::new(0.8, 0.8, 0.0), Vec3::new(2.0, 2.0, 1.0));
+        assert!(!sphere.intersects_aabb(&corner));
+
+        let from_box = BoundingSphere::from_aabb(&unit_box());
+        assert_eq!(from_box.center, Vec3::zeros());
+        assert!((from_box.radius - Float::sqrt(3.0)).abs() < 1e-5);
+
+        let scaled = sphere.transformed(
+            &(Mat4::new_translation(&Vec3::new(0.0, 3.0, 0.0))
+                * Mat4::new_nonuniform_scaling(&Vec3::new(1.0, 2.0, 0.5))),
+        );
+        assert_close(scaled.center, Vec3::new(0.0, 3.0, 0.0));
+        assert!((scaled.radius - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn plane() {
+        let plane = Plane::from_points(
+            Vec3::new(0.0, 2.0, 0.0),
+            Vec3::new(0.0, 2.0, 1.0),
+            Vec3::new(1.0, 2.0, 0.0),
+        )
+        .unwrap();
+        assert_close(plane.normal, Vec3::y());
+        assert!((plane.distance - 2.0).abs() < 1e-5);
+        assert!((plane.signed_distance(Vec3::new(5.0, 5.0, 5.0)) - 3.0).abs() < 1e-5);
+        assert!((plane.signed_distance(Vec3::new(0.0, 0.0, 0.0)) + 2.0).abs() < 1e-5);
+        assert_close(
+            plane.project_point(Vec3::new(1.0, 7.0, 1.0)),
+            Vec3::new(1.0, 2.0, 1.0),
+        );
+
+        assert_eq!(Plane::new(Vec3::new(0.0, 2.0, 0.0), 4.0), plane);
+        assert_eq!(
+            Plane::from_points(Vec3::zeros(), Vec3::x(), Vec3::x() * 2.0),
+            None
+        );
+    }
+
+    #[test]
+    fn ray_intersections() {
+        let ray = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 2.0));
+        assert_eq!(ray.direction, Vec3::z());
+        assert_eq!(ray.at(2.0), Vec3::new(0.0, 0.0, -3.0));
+
+        let plane = Plane::from_point_normal(Vec3::new(0.0, 0.0, 1.0), -Vec3::z());
+        assert_eq!(ray.intersect_plane(&plane), Some(6.0));
+        let behind = Plane::from_point_normal(Vec3::new(0.0, 0.0, -6.0), Vec3::z());
+        assert_eq!(ray.intersect_plane(&behind), None);
+        let parallel = Plane::from_point_normal(Vec3::zeros(), Vec3::x());
+        assert_eq!(ray.intersect_plane(&parallel), None);
+
+        assert_eq!(ray.intersect_aabb(&unit_box()), Some(4.0));
+        let inside = Ray::new(Vec3::zeros(), Vec3::new(1.0, 1.0, 0.0));
+        assert_eq!(inside.intersect_aabb(&unit_box()), Some(0.0));
+        let beside = Ray::new(Vec3::new(2.0, 0.0, -5.0), Vec3::z());
+        assert_eq!(beside.intersect_aabb(&unit_box()), None);
+        let diagonal = Ray::new(Vec3::new(-3.0, -3.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+        let t = diagonal.intersect_aabb(&unit_box()).unwrap();
+        assert_close(diagonal.at(t), Vec3::new(-1.0, -1.0, 0.0));
+        let away = Ray::new(Vec3::new(0.0, 0.0, -5.0), -Vec3::z());
+        assert_eq!(away.intersect_aabb(&unit_box()), None);
+
+        let sphere = BoundingSphere::new(Vec3::new(0.0, 0.0, 0.0), 1.0);
+        assert_eq!(ray.intersect_sphere(&sphere), Some(4.0));
+        assert_eq!(inside.intersect_sphere(&sphere), Some(0.0));
+        assert_eq!(beside.intersect_sphere(&sphere), None);
+        assert_eq!(away.intersect_sphere(&sphere), None);
+    }
+
+    #[test]
+    fn ray_triangle() {
+        let a = Vec3::new(-1.0, -1.0, 0.0);
+        let b = Vec3::new(1.0, -1.0, 0.0);
+        let c = Vec3::new(0.0, 1.0, 0.0);
+
+        let front = Ray::new(Vec3::new(0.0, 0.0, -2.0), Vec3::z());
+        assert_eq!(front.intersect_triangle(a, b, c), Some(2.0));
+        let back = Ray::new(Vec3::new(0.0, 0.0, 3.0), -Vec3::z());
+        assert_eq!(back.intersect_triangle(a, b, c), Some(3.0));
+
+        let edge = Ray::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::z());
+        assert_eq!(edge.intersect_triangle(a, b, c), Some(1.0));
+        let outside = Ray::new(Vec3::new(0.9, 0.9, -1.0), Vec3::z());
+        assert_eq!(outside.intersect_triangle(a, b, c), None);
+        let away = Ray::new(Vec3::new(0.0, 0.0, -2.0), -Vec3::z());
+        assert_eq!(away.intersect_triangle(a, b, c), None);
+        let parallel = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::x());
+        assert_eq!(parallel.intersect_triangle(a, b, c), None);
+    }
+
+    fn camera_frustum() -> Frustum {
+        // 90° field of view from (0, 0, 10) looking towards the origin
+        let projection = Mat4::new_perspective(1.0, FRAC_PI_2, 1.0, 100.0);
+        let view = Mat4::look_at_rh(&Point3::new(0.0, 0.0, 10.0), &Point3::origin(), &Vec3::y());
+        Frustum::from_view_projection(&(projection * view))
+    }
+
+    #[test]
+    fn frustum_planes() {
+        let frustum = camera_frustum();
+        assert_close(frustum.near.normal, -Vec3::z());
+        assert!((frustum.near.distance + 9.0).abs() < 1e-3);
+        assert_close(frustum.far.normal, Vec3::z());
+        assert!((frustum.far.distance + 90.0).abs() < 1e-2);
+        let diagonal = Float::sqrt(0.5);
+        assert_close(frustum.left.normal, Vec3::new(diagonal, 0.0, -diagonal));
+        assert_close(frustum.top.normal, Vec3::new(0.0, -diagonal, -diagonal));
+
+        assert!(frustum.contains_point(Vec3::zeros()));
+        assert!(frustum.contains_point(Vec3::new(9.0, 0.0, 0.0)));
+        assert!(!frustum.contains_point(Vec3::new(11.0, 0.0, 0.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 9.5)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -95.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 20.0)));
+
+        assert!(frustum.intersects_sphere(&BoundingSphere::new(Vec3::new(11.0, 0.0, 0.0), 1.0)));
+        assert!(!frustum.intersects_sphere(&BoundingSphere::new(Vec3::new(12.0, 0.0, 0.0), 1.0)));
+    }
+
+    #[test]
+    fn frustum_aabb() {
+        let frustum = camera_frustum();
+
+        let inside = unit_box();
+        assert!(frustum.intersects_aabb(&inside));
+        assert!(frustum.contains_aabb(&inside));
+
+        let across_edge =
+            Aabb::from_center_half_extents(Vec3::new(10.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        assert!(frustum.intersects_aabb(&across_edge));
+        assert!(!frustum.contains_aabb(&across_edge));
+
+        let enclosing =
+            Aabb::from_center_half_extents(Vec3::zeros(), Vec3::new(500.0, 500.0, 500.0));
+        assert!(frustum.intersects_aabb(&enclosing));
+        assert!(!frustum.contains_aabb(&enclosing));
+
+        let beside =
+            Aabb::from_center_half_extents(Vec3::new(20.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        let behind =
+            Aabb::from_center_half_extents(Vec3::new(0.0, 0.0, 15.0), Vec3::new(1.0, 1.0, 1.0));
+        assert!(!frustum.intersects_aabb(&beside));
+        assert!(!frustum.intersects_aabb(&behind));
+    }
+
+    #[test]
+    fn serde_and_reflect() {
+        let aabb: Aabb =
+            serde_json::from_str(r#"{"min": [-1.0, -1.0, -1.0], "max": [1.0, 1.0, 1.0]}"#).unwrap();
+        assert_eq!(aabb, unit_box());
+
+        let frustum = camera_frustum();
+        let json = serde_json::to_string(&frustum).unwrap();
+        assert_eq!(serde_json::from_str::<Frustum>(&json).unwrap(), frustum);
+
+        let mut sphere = BoundingSphere::new(Vec3::zeros(), 1.0);
+        sphere.set::<Float>(&ReflectPath::parse("radius"), 2.0);
+        sphere.set(&ReflectPath::parse("center"), Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(sphere, BoundingSphere::new(Vec3::new(1.0, 2.0, 3.0), 2.0));
+        assert_eq!(
+            sphere.get_value::<Float>(&ReflectPath::parse("center.y")),
+            2.0
+        );
+
+        let mut ray = Ray::new(Vec3::zeros(), Vec3::z());
+        ray.set::<Float>(&ReflectPath::parse("direction.x"), 1.0);
+        assert_eq!(ray.direction, Vec3::new(1.0, 0.0, 1.0));
+    }
+}
@@ -1,22 +1,112 @@
-use std::any::Any;
+use std::{any::Any, collections::HashSet};
 
-use acro_ecs::{world::World, Changed, EntityId, Query, SystemRunContext};
-use acro_reflect::{Reflect, ReflectPath, ReflectSetError};
+use acro_ecs::{
+    world::World, Added, Bundle, Changed, Component, EntityId, IncludeDisabled, Or, Query,
+    SystemRunContext,
+};
+use acro_reflect::{type_mismatch, Reflect, ReflectPath, ReflectSetError};
+use nalgebra::Rotation3;
 
-use crate::types::{Mat4, Vec3};
+use crate::types::{Float, Mat3, Mat4, Quaternion, UnitQuaternion, Vec3};
 
-#[derive(Debug, Clone, Copy, Reflect, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Component, serde::Serialize, serde::Deserialize)]
+#[component(script_name = "Transform")]
 pub struct Transform {
     pub position: Vec3,
-    pub rotation: Vec3,
+    /// Doesn't have to be normalized, it is normalized when the matrix is built. Scenes set it as
+    /// a quaternion, `[x, y, z, w]`, or like older scenes as a scaled axis, `[x, y, z]`, whose
+    /// length is the angle in radians.
+    #[serde(with = "rotation_serde")]
+    pub rotation: Quaternion,
     pub scale: Vec3,
 }
 
 impl Transform {
+    /// Scales, then rotates, then translates.
     pub fn get_matrix(&self) -> Mat4 {
-        Mat4::new_nonuniform_scaling(&self.scale)
-            * Mat4::new_translation(&self.position)
-            * Mat4::new_rotation(self.rotation)
+        Mat4::new_translation(&self.position)
+            * self.unit_rotation().to_homogeneous()
+            * Mat4::new_nonuniform_scaling(&self.scale)
+    }
+
+    /// Splits a matrix built by [`Transform::get_matrix`] back into position, rotation and scale.
+    /// Shear can't be represented and is lost. A negative determinant is folded into the x scale.
+    pub fn from_matrix(matrix: &Mat4) -> Self {
+        let position = matrix.fixed_view::<3, 1>(0, 3).into_owned();
+        let linear = matrix.fixed_view::<3, 3>(0, 0).into_owned();
+
+        let mut scale = Vec3::new(
+            linear.column(0).norm(),
+            linear.column(1).norm(),
+            linear.column(2).norm(),
+        );
+        if linear.determinant() < 0.0 {
+            scale.x = -scale.x;
+        }
+
+        let rotation = if scale.iter().any(|&axis| axis == 0.0) {
+            UnitQuaternion::identity()
+        } else {
+            let rotation = Mat3::from_columns(&[
+                linear.column(0) / scale.x,
+                linear.column(1) / scale.y,
+                linear.column(2) / scale.z,
+            ]);
+            UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(rotation))
+        };
+
+        Self {
+            position,
+            rotation: *rotation.quaternion(),
+            scale,
+        }
+    }
+
+    pub fn unit_rotation(&self) -> UnitQuaternion {
+        UnitQuaternion::new_normalize(self.rotation)
+    }
+
+    /// The rotation as Euler angles in radians: roll around x, then pitch around y, then yaw
+    /// around z.
+    pub fn euler_angles(&self) -> Vec3 {
+        let (roll, pitch, yaw) = self.unit_rotation().euler_angles();
+        Vec3::new(roll, pitch, yaw)
+    }
+
+    pub fn set_euler_angles(&mut self, angles: Vec3) {
+        self.rotation = quaternion_from_euler_angles(angles);
+    }
+
+    pub fn with_euler_angles(mut self, angles: Vec3) -> Self {
+        self.set_euler_angles(angles);
+        self
+    }
+
+    /// Rotates the transform so its forward axis (-z) points at `target`, with its up axis (+y) as
+    /// close to `up` as possible. Both are in the parent's space, use
+    /// [`GlobalTransform::inverse`] on the parent to convert world-space positions. Does nothing
+    /// if `target` is at the transform's position.
+    pub fn look_at(&mut self, target: Vec3, up: Vec3) {
+        let direction = target - self.position;
+        if direction.norm_squared() == 0.0 {
+            return;
+        }
+
+        // `face_towards` turns +z towards its direction, and is undefined when looking straight
+        // along `up`
+        let rotation = if direction.cross(&up).norm_squared() > Float::EPSILON {
+            UnitQuaternion::face_towards(&-direction, &up)
+        } else {
+            UnitQuaternion::rotation_between(&-Vec3::z(), &direction).unwrap_or_else(|| {
+                UnitQuaternion::from_axis_angle(&Vec3::y_axis(), std::f64::consts::PI as Float)
+            })
+        };
+        self.rotation = *rotation.quaternion();
+    }
+
+    pub fn looking_at(mut self, target: Vec3, up: Vec3) -> Self {
+        self.look_at(target, up);
+        self
     }
 }
 
@@ -24,13 +114,97 @@ impl Default for Transform {
     fn default() -> Self {
         Self {
             position: [0.0, 0.0, 0.0].into(),
-            rotation: [0.0, 0.0, 0.0].into(),
+            rotation: Quaternion::identity(),
             scale: [1.0, 1.0, 1.0].into(),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+fn quaternion_from_euler_angles(angles: Vec3) -> Quaternion {
+    *UnitQuaternion::from_euler_angles(angles.x, angles.y, angles.z).quaternion()
+}
+
+// Scripts see `rotation` as Euler angles, which are computed from the quaternion. The quaternion
+// itself is at `quaternion`.
+impl Reflect for Transform {
+    fn get_field_names(&self) -> &'static [&'static str] {
+        &["position", "rotation", "quaternion", "scale"]
+    }
+
+    fn get_opt(&self, path: &ReflectPath) -> Option<&dyn Any> {
+        match path {
+            ReflectPath::End => Some(self),
+            ReflectPath::Property("position", rest) => self.position.get_opt(rest),
+            ReflectPath::Property("quaternion", rest) => self.rotation.get_opt(rest),
+            ReflectPath::Property("scale", rest) => self.scale.get_opt(rest),
+            _ => None,
+        }
+    }
+
+    fn get_computed(&self, path: &ReflectPath) -> Option<Box<dyn Any>> {
+        let ReflectPath::Property("rotation", rest) = path else {
+            return None;
+        };
+
+        let angles = self.euler_angles();
+        match **rest {
+            ReflectPath::End => Some(Box::new(angles)),
+            _ => angles
+                .get_opt(rest)?
+                .downcast_ref::<Float>()
+                .map(|&angle| Box::new(angle) as Box<dyn Any>),
+        }
+    }
+
+    fn set_any(&mut self, path: &ReflectPath, data: Box<dyn Any>) -> Result<(), ReflectSetError> {
+        match path {
+            ReflectPath::End => *self = *data.downcast().map_err(type_mismatch)?,
+            ReflectPath::Property("position", rest) => self.position.set_any(rest, data)?,
+            ReflectPath::Property("quaternion", rest) => self.rotation.set_any(rest, data)?,
+            ReflectPath::Property("scale", rest) => self.scale.set_any(rest, data)?,
+            ReflectPath::Property("rotation", rest) => {
+                let mut angles = self.euler_angles();
+                angles.set_any(rest, data)?;
+                self.set_euler_angles(angles);
+            }
+            _ => return Err(ReflectSetError::PathNotFound),
+        }
+        Ok(())
+    }
+}
+
+mod rotation_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::types::{Float, Quaternion, UnitQuaternion, Vec3};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Rotation {
+        // What `rotation` was before it became a quaternion
+        ScaledAxis([Float; 3]),
+        Quaternion([Float; 4]),
+    }
+
+    pub fn serialize<S: Serializer>(
+        rotation: &Quaternion,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let coords: [Float; 4] = rotation.coords.into();
+        coords.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Quaternion, D::Error> {
+        Ok(match Rotation::deserialize(deserializer)? {
+            Rotation::ScaledAxis(axis) => {
+                *UnitQuaternion::from_scaled_axis(Vec3::from(axis)).quaternion()
+            }
+            Rotation::Quaternion([x, y, z, w]) => Quaternion::new(w, x, y, z),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Component)]
 pub struct GlobalTransform {
     pub matrix: Mat4,
 }
@@ -43,95 +217,200 @@ impl Default for GlobalTransform {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Forward is -z, right is +x and up is +y, the same axes cameras use, so an entity's forward
+/// axis is where a camera on it looks. `Transform::look_at` and scripts follow this convention.
+impl GlobalTransform {
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        Self { matrix }
+    }
+
+    pub fn translation(&self) -> Vec3 {
+        self.matrix.fixed_view::<3, 1>(0, 3).into_owned()
+    }
+
+    pub fn rotation(&self) -> UnitQuaternion {
+        self.to_transform().unit_rotation()
+    }
+
+    pub fn scale(&self) -> Vec3 {
+        self.to_transform().scale
+    }
+
+    /// Splits the matrix into world-space position, rotation and scale.
+    pub fn to_transform(&self) -> Transform {
+        Transform::from_matrix(&self.matrix)
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        self.rotation() * -Vec3::z()
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.rotation() * Vec3::x()
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.rotation() * Vec3::y()
+    }
+
+    /// Moves a point from the entity's local space into world space.
+    pub fn transform_point(&self, point: &Vec3) -> Vec3 {
+        self.matrix.transform_point(&(*point).into()).coords
+    }
+
+    /// Like `transform_point`, but ignores the translation. The result is not normalized.
+    pub fn transform_vector(&self, vector: &Vec3) -> Vec3 {
+        self.matrix.transform_vector(vector)
+    }
+
+    /// The transform from world space into the entity's local space, or `None` if it is scaled
+    /// to zero on some axis.
+    pub fn inverse(&self) -> Option<GlobalTransform> {
+        self.matrix.try_inverse().map(Self::from_matrix)
+    }
+}
+
+/// The components an entity needs to be placed in the world. `GlobalTransform` is computed from
+/// the `Transform` every frame.
+#[derive(Debug, Clone, Copy, Default, Bundle)]
+pub struct TransformBundle {
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl TransformBundle {
+    pub fn from_transform(transform: Transform) -> Self {
+        Self {
+            transform,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Component)]
 pub struct Parent(pub EntityId);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Component)]
 pub struct Children(pub Vec<EntityId>);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Component)]
 pub struct Root;
 
-#[derive(Debug, Clone)]
+/// Marks an entity whose `GlobalTransform` is managed by another system, e.g. UI layout.
+/// Propagation never writes it, but its descendants are still placed relative to it.
+#[derive(Debug, Clone, Component)]
 pub struct TransformBoundary;
 
+type DirtyFilter = (
+    Or<Changed<Transform>, Changed<Parent>, Added<GlobalTransform>>,
+    IncludeDisabled,
+);
+
+type TransformNode = (
+    Option<&'static Transform>,
+    Option<&'static Parent>,
+    Option<&'static Children>,
+    Option<&'static TransformBoundary>,
+);
+
+/// Recomputes `GlobalTransform` for every entity whose `Transform` or `Parent` changed or which
+/// just got a `GlobalTransform`, along with all of their descendants. Every subtree is walked once,
+/// even when several entities in it changed.
+///
+/// Entities without a parent, or whose parent is gone, are placed relative to the world origin.
+/// Entities without a `Transform` follow their parent, and entities without a `GlobalTransform`
+/// are skipped along with their descendants.
 pub fn propagate_global_transform(
     ctx: SystemRunContext,
-    transform_query: Query<
-        (
-            EntityId,
-            &Transform,
-            &Children,
-            &Parent,
-            Option<&TransformBoundary>,
-        ),
-        Changed<Transform>,
-    >,
-    global_transform_query: Query<(EntityId, &mut GlobalTransform, &Children)>,
+    dirty_query: Query<EntityId, DirtyFilter>,
+    node_query: Query<TransformNode, IncludeDisabled>,
+    global_transform_query: Query<&mut GlobalTransform, IncludeDisabled>,
 ) {
-    for (entity, transform, _children, parent, boundary) in transform_query.over(&ctx) {
-        if boundary.is_some() {
-            continue;
-        }
-
-        recurse_propagate(
-            &ctx,
-            entity,
-            parent.0,
-            transform,
-            &transform_query,
-            &global_transform_query,
-        );
+    let dirty = dirty_query.over(&ctx).collect::<HashSet<_>>();
+    if dirty.is_empty() {
+        return;
     }
-}
 
-fn recurse_propagate(
-    ctx: &SystemRunContext,
-    current_entity: EntityId,
-    parent: EntityId,
-    transform: &Transform,
-    transform_query: &Query<
-        (
-            EntityId,
-            &Transform,
-            &Children,
-            &Parent,
-            Option<&TransformBoundary>,
-        ),
-        Changed<Transform>,
-    >,
-    global_transform_query: &Query<(EntityId, &mut GlobalTransform, &Children)>,
-) {
-    // Update the global transform of the current entity
-    let (_parent_id, parent_global_transform, _parent_children) = global_transform_query
-        .get(ctx, parent)
-        .expect("Invalid tree structure: parent entity does not have a global transform!");
-    let (_, mut this_global_transform, children) = global_transform_query
-        .get(ctx, current_entity)
-        .expect("Invalid tree structure: entity does not have a global transform!");
-    this_global_transform.matrix = parent_global_transform.matrix * transform.get_matrix();
-
-    // Propagate the global transform of this entity to its children
-    for child in children.0.iter() {
-        recurse_propagate(
-            ctx,
-            *child,
-            current_entity,
-            transform_query
-                .get(ctx, *child)
-                .expect("Invalid tree structure: child entity does not have a transform!")
-                .1,
-            transform_query,
-            global_transform_query,
-        );
+    let parent_of = |entity| {
+        node_query
+            .get(&ctx, entity)
+            .and_then(|(_, parent, _, _)| parent)
+            .map(|&Parent(parent)| parent)
+    };
+    // Entities with a dirty ancestor are reached from that ancestor
+    let mut ancestors = HashSet::new();
+    let dirty_roots = dirty
+        .iter()
+        .copied()
+        .filter(|&entity| {
+            ancestors.clear();
+            let mut current = parent_of(entity);
+            while let Some(ancestor) = current {
+                if dirty.contains(&ancestor) {
+                    return false;
+                }
+                // Stop at cycles in malformed trees
+                if !ancestors.insert(ancestor) {
+                    break;
+                }
+                current = parent_of(ancestor);
+            }
+            true
+        })
+        .collect::<Vec<_>>();
+
+    let mut visited = HashSet::new();
+    let mut stack = vec![];
+    for root in dirty_roots {
+        let parent_matrix = parent_of(root)
+            .and_then(|parent| global_transform_query.get(&ctx, parent))
+            .map(|parent| parent.matrix)
+            .unwrap_or_else(Mat4::identity);
+        stack.push((root, parent_matrix));
+
+        while let Some((entity, parent_matrix)) = stack.pop() {
+            if !visited.insert(entity) {
+                continue;
+            }
+            let Some((transform, _, children, boundary)) = node_query.get(&ctx, entity) else {
+                continue;
+            };
+            let Some(mut global_transform) = global_transform_query.get(&ctx, entity) else {
+                continue;
+            };
+
+            let matrix = if boundary.is_some() {
+                global_transform.matrix
+            } else {
+                let matrix = match transform {
+                    Some(transform) => parent_matrix * transform.get_matrix(),
+                    None => parent_matrix,
+                };
+                global_transform.matrix = matrix;
+                matrix
+            };
+
+            if let Some(children) = children {
+                stack.extend(children.0.iter().rev().map(|&child| (child, matrix)));
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use acro_ecs::{pointer::change_detection::Tick, world::World};
+    use std::f32::consts::FRAC_PI_2;
+
+    use acro_ecs::{
+        pointer::change_detection::Tick, world::World, Changed, EntityId, SystemRunContext,
+    };
+    use acro_reflect::{ReflectExt, ReflectPath};
 
-    use crate::{transform::Root, types::Mat4, TransformBoundary};
+    use crate::{
+        transform::Root,
+        types::{Float, Mat4, Quaternion, Vec3},
+        TransformBoundary, TransformBundle, WorldHierarchyExt,
+    };
 
     use super::{propagate_global_transform, Children, GlobalTransform, Parent, Transform};
 
@@ -150,7 +429,7 @@ mod tests {
             root,
             Transform {
                 position: [0.0, 0.0, 0.0].into(),
-                rotation: [0.0, 0.0, 0.0].into(),
+                rotation: Quaternion::identity(),
                 scale: [1.0, 1.0, 1.0].into(),
             },
         );
@@ -167,7 +446,7 @@ mod tests {
             child_1,
             Transform {
                 position: [0.0, -2.0, 0.0].into(),
-                rotation: [0.0, 0.0, 0.0].into(),
+                rotation: Quaternion::identity(),
                 scale: [1.0, 1.0, 1.0].into(),
             },
         );
@@ -184,7 +463,7 @@ mod tests {
             child_of_child_1,
             Transform {
                 position: [0.0, 2.0, 0.0].into(),
-                rotation: [0.0, 0.0, 0.0].into(),
+                rotation: Quaternion::identity(),
                 scale: [1.0, 1.0, 1.0].into(),
             },
         );
@@ -209,7 +488,7 @@ mod tests {
             child_1_global_transform.matrix,
             Transform {
                 position: [0.0, -2.0, 0.0].into(),
-                rotation: [0.0, 0.0, 0.0].into(),
+                rotation: Quaternion::identity(),
                 scale: [1.0, 1.0, 1.0].into(),
             }
             .get_matrix()
@@ -221,10 +500,294 @@ mod tests {
             child_of_child_1_global_transform.matrix,
             Transform {
                 position: [0.0, 0.0, 0.0].into(),
-                rotation: [0.0, 0.0, 0.0].into(),
+                rotation: Quaternion::identity(),
                 scale: [1.0, 1.0, 1.0].into(),
             }
             .get_matrix()
         );
     }
+
+    fn hierarchy_world() -> World {
+        let mut world = World::new();
+        world.init_component::<TransformBoundary>();
+        world.init_component::<Transform>();
+        world.init_component::<GlobalTransform>();
+        world.init_component::<Parent>();
+        world.init_component::<Children>();
+        world
+    }
+
+    fn spawn_at(world: &mut World, x: Float) -> EntityId {
+        world.spawn(TransformBundle::from_transform(Transform {
+            position: Vec3::new(x, 0.0, 0.0),
+            ..Default::default()
+        }))
+    }
+
+    /// Runs the propagation like the schedule would, seeing changes made after `last_run`.
+    fn propagate(world: &mut World, last_run: u32) {
+        let tick = Tick::new(last_run + 1);
+        let ctx = SystemRunContext {
+            world,
+            tick,
+            last_run_tick: Tick::new(last_run),
+        };
+        propagate_global_transform(ctx.clone(), world.query(), world.query(), world.query());
+        world.set_change_tick(tick);
+    }
+
+    fn global_x(world: &World, entity: EntityId) -> Float {
+        world.get::<GlobalTransform>(entity).unwrap().matrix[(0, 3)]
+    }
+
+    #[test]
+    fn propagation_follows_parent_changes() {
+        let mut world = hierarchy_world();
+        let a = spawn_at(&mut world, 10.0);
+        let b = spawn_at(&mut world, 20.0);
+        let child = spawn_at(&mut world, 1.0);
+        let grandchild = spawn_at(&mut world, 1.0);
+        world.set_parent(child, a).unwrap();
+        world.set_parent(grandchild, child).unwrap();
+
+        world.set_change_tick(Tick::new(1));
+        propagate(&mut world, 0);
+        assert_eq!(global_x(&world, grandchild), 12.0);
+
+        // Only `Parent` and `Children` change, the transforms are untouched
+        world.set_change_tick(Tick::new(3));
+        world.get_mut::<Children>(a).unwrap().0.clear();
+        world.insert(b, Children(vec![child]));
+        world.insert(child, Parent(b));
+        propagate(&mut world, 2);
+        assert_eq!(global_x(&world, child), 21.0);
+        assert_eq!(global_x(&world, grandchild), 22.0);
+
+        // Subtrees that did not change are not written
+        let changed = world.query::<EntityId, Changed<GlobalTransform>>();
+        let ctx = SystemRunContext {
+            world: &world,
+            tick: Tick::new(4),
+            last_run_tick: Tick::new(2),
+        };
+        let mut changed = changed.over(ctx).collect::<Vec<_>>();
+        changed.sort_by_key(|entity| global_x(&world, *entity) as i32);
+        assert_eq!(changed, vec![child, grandchild]);
+    }
+
+    #[test]
+    fn propagation_handles_new_and_orphaned_entities() {
+        let mut world = hierarchy_world();
+        let parent = spawn_at(&mut world, 10.0);
+        let orphan = spawn_at(&mut world, 1.0);
+        world.set_parent(orphan, parent).unwrap();
+        world.set_change_tick(Tick::new(1));
+        propagate(&mut world, 0);
+
+        // The parent is despawned without updating the child
+        world.set_change_tick(Tick::new(3));
+        world.despawn(parent);
+        world.mark_changed::<Transform>(orphan);
+        propagate(&mut world, 2);
+        assert_eq!(global_x(&world, orphan), 1.0);
+
+        // A `GlobalTransform` inserted later is filled in even though nothing else changed
+        let late = world.spawn((Transform::default(), Parent(orphan), Children(vec![])));
+        world.get_mut::<Children>(orphan).unwrap().0.push(late);
+        world.set_change_tick(Tick::new(5));
+        world.insert(late, GlobalTransform::default());
+        propagate(&mut world, 4);
+        assert_eq!(global_x(&world, late), 1.0);
+    }
+
+    #[test]
+    fn propagation_keeps_boundaries() {
+        let mut world = hierarchy_world();
+        let root = spawn_at(&mut world, 10.0);
+        let boundary = spawn_at(&mut world, 1.0);
+        let child = spawn_at(&mut world, 1.0);
+        world.insert(boundary, TransformBoundary);
+        world.set_parent(boundary, root).unwrap();
+        world.set_parent(child, boundary).unwrap();
+        world.get_mut::<GlobalTransform>(boundary).unwrap().matrix =
+            Mat4::new_translation(&Vec3::new(100.0, 0.0, 0.0));
+
+        world.set_change_tick(Tick::new(1));
+        propagate(&mut world, 0);
+        assert_eq!(global_x(&world, root), 10.0);
+        assert_eq!(global_x(&world, boundary), 100.0);
+        assert_eq!(global_x(&world, child), 101.0);
+    }
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).norm() < 1e-5, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn trs_composition() {
+        let transform = Transform {
+            position: Vec3::new(1.0, 2.0, 3.0),
+            rotation: Quaternion::identity(),
+            scale: Vec3::new(2.0, 2.0, 2.0),
+        }
+        .with_euler_angles(Vec3::new(0.0, FRAC_PI_2 as Float, 0.0));
+        let matrix = transform.get_matrix();
+
+        // The translation is not scaled
+        assert_close(
+            matrix.transform_point(&Vec3::zeros().into()).coords,
+            transform.position,
+        );
+        // Points are scaled, then rotated a quarter turn around y, then translated
+        assert_close(
+            matrix
+                .transform_point(&Vec3::new(1.0, 0.0, 0.0).into())
+                .coords,
+            Vec3::new(1.0, 2.0, 1.0),
+        );
+    }
+
+    #[test]
+    fn global_transform_decomposition() {
+        let transform = Transform {
+            position: Vec3::new(1.0, 2.0, 3.0),
+            scale: Vec3::new(2.0, 3.0, 4.0),
+            ..Default::default()
+        }
+        .with_euler_angles(Vec3::new(0.0, FRAC_PI_2 as Float, 0.0));
+        let global_transform = GlobalTransform::from_matrix(transform.get_matrix());
+
+        let decomposed = global_transform.to_transform();
+        assert_close(decomposed.position, transform.position);
+        assert_close(decomposed.scale, transform.scale);
+        assert!(
+            decomposed
+                .unit_rotation()
+                .angle_to(&transform.unit_rotation())
+                < 1e-5
+        );
+
+        // Turned a quarter around y, forward (-z) now points along -x
+        assert_close(global_transform.forward(), -Vec3::x());
+        assert_close(global_transform.right(), -Vec3::z());
+        assert_close(global_transform.up(), Vec3::y());
+
+        let point = Vec3::new(1.0, 1.0, 1.0);
+        let world_point = global_transform.transform_point(&point);
+        assert_close(world_point, Vec3::new(5.0, 5.0, 1.0));
+        let inverse = global_transform.inverse().unwrap();
+        assert_close(inverse.transform_point(&world_point), point);
+
+        let flat =
+            GlobalTransform::from_matrix(Mat4::new_nonuniform_scaling(&Vec3::new(1.0, 0.0, 1.0)));
+        assert!(flat.inverse().is_none());
+        assert_close(flat.forward(), -Vec3::z());
+    }
+
+    #[test]
+    fn look_at() {
+        let mut transform = Transform {
+            position: Vec3::new(1.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        transform.look_at(Vec3::new(1.0, 0.0, -5.0), Vec3::y());
+        assert!(transform.unit_rotation().angle() < 1e-5);
+
+        transform.look_at(Vec3::new(-4.0, 0.0, 0.0), Vec3::y());
+        let global_transform = GlobalTransform::from_matrix(transform.get_matrix());
+        assert_close(global_transform.forward(), -Vec3::x());
+        assert_close(global_transform.up(), Vec3::y());
+
+        // Looking straight up still points forward at the target
+        let transform = transform.looking_at(Vec3::new(1.0, 10.0, 0.0), Vec3::y());
+        let global_transform = GlobalTransform::from_matrix(transform.get_matrix());
+        assert_close(global_transform.forward(), Vec3::y());
+    }
+
+    #[test]
+    fn look_at_projects_to_screen_center() {
+        let target = Vec3::new(3.0, -2.0, 7.0);
+        let camera = Transform {
+            position: Vec3::new(-5.0, 4.0, -10.0),
+            ..Default::default()
+        }
+        .looking_at(target, Vec3::y());
+
+        // Same view and projection the renderer uses for the main camera
+        let view = GlobalTransform::from_matrix(camera.get_matrix())
+            .inverse()
+            .unwrap();
+        let projection = Mat4::new_perspective(16.0 / 9.0, FRAC_PI_2 as Float, 0.1, 100.0);
+        let clip = projection * view.matrix * target.push(1.0);
+        let ndc = clip.xyz() / clip.w;
+
+        assert!(clip.w > 0.0, "target is behind the camera");
+        assert!(ndc.x.abs() < 1e-5 && ndc.y.abs() < 1e-5, "{ndc:?}");
+        assert!(ndc.z.abs() <= 1.0, "{ndc:?}");
+    }
+
+    #[test]
+    fn euler_angles() {
+        let angles = Vec3::new(0.1, -0.4, 1.2);
+        let transform = Transform::default().with_euler_angles(angles);
+        assert_close(transform.euler_angles(), angles);
+
+        // A rotation that isn't normalized gives the same result
+        let transform = Transform {
+            rotation: transform.rotation * 3.0,
+            ..transform
+        };
+        assert_close(transform.euler_angles(), angles);
+    }
+
+    #[test]
+    fn rotation_serde() {
+        let transform: Transform = serde_json::from_str(
+            r#"{"position": [0.0, 0.0, 0.0], "rotation": [0.0, 1.5, 0.0], "scale": [1.0, 1.0, 1.0]}"#,
+        )
+        .unwrap();
+        assert_close(transform.euler_angles(), Vec3::new(0.0, 1.5, 0.0));
+
+        let json = serde_json::to_string(&transform).unwrap();
+        assert_eq!(serde_json::from_str::<Transform>(&json).unwrap(), transform);
+
+        // Rotations around several axes keep the meaning they had as a scaled axis
+        let axis = Vec3::new(0.3, -0.5, 0.8);
+        let transform: Transform = serde_json::from_str(
+            r#"{"position": [0.0, 0.0, 0.0], "rotation": [0.3, -0.5, 0.8], "scale": [1.0, 1.0, 1.0]}"#,
+        )
+        .unwrap();
+        let point = Vec3::new(1.0, 2.0, 3.0);
+        assert_close(
+            transform.get_matrix().transform_vector(&point),
+            Mat4::new_rotation(axis).transform_vector(&point),
+        );
+
+        let json = serde_json::to_string(&transform).unwrap();
+        let round_trip = serde_json::from_str::<Transform>(&json).unwrap();
+        assert!((round_trip.rotation.coords - transform.rotation.coords).norm() < 1e-5);
+        assert_close(round_trip.unit_rotation().scaled_axis(), axis);
+    }
+
+    #[test]
+    fn rotation_reflect_paths() {
+        let mut transform = Transform::default();
+
+        transform.set(&ReflectPath::parse("rotation"), Vec3::new(0.5, 0.0, 0.0));
+        assert_close(transform.euler_angles(), Vec3::new(0.5, 0.0, 0.0));
+
+        transform.set::<Float>(&ReflectPath::parse("rotation.y"), 0.25);
+        assert_close(
+            transform.get_value::<Vec3>(&ReflectPath::parse("rotation")),
+            Vec3::new(0.5, 0.25, 0.0),
+        );
+        let y = transform.get_value::<Float>(&ReflectPath::parse("rotation.y"));
+        assert!((y - 0.25).abs() < 1e-5);
+
+        transform.set(&ReflectPath::parse("quaternion"), Quaternion::identity());
+        assert_eq!(
+            *transform.get::<Quaternion>(&ReflectPath::parse("quaternion")),
+            Quaternion::identity()
+        );
+    }
 }
@@ -1,34 +1,48 @@
-use acro_ecs::{EntityId, Name, Query, With, World};
+use acro_ecs::{EntityId, IncludeDisabled, Name, Query, With, World};
 
 use crate::{Children, Root};
 
+// Disabled entities can still be looked up by path, e.g. to enable them again. Paths are resolved
+// through `Children` every time, so they follow hierarchy edits without any bookkeeping.
 pub struct TreeData {
-    root_query: Query<(EntityId, &'static Children), With<Root>>,
-    children_query: Query<(EntityId, &'static Name, &'static Children)>,
+    root_query: Query<TreeNode, (With<Root>, IncludeDisabled)>,
+    children_query: Query<(EntityId, &'static Name, Option<&'static Children>), IncludeDisabled>,
 }
 
+type TreeNode = (EntityId, Option<&'static Children>);
+
 impl TreeData {
     pub fn new(world: &World) -> Self {
         Self {
-            root_query: world.query::<(EntityId, &Children), With<Root>>(),
-            children_query: world.query::<(EntityId, &Name, &Children), ()>(),
+            root_query: world.query::<TreeNode, (With<Root>, IncludeDisabled)>(),
+            children_query: world.query::<(EntityId, &Name, Option<&Children>), IncludeDisabled>(),
         }
     }
 
+    /// Paths start at a `Root`. Detaching an entity makes it the root of another tree, so each
+    /// root is tried in turn.
     pub fn get_entity_by_path(&self, world: &World, path: &str) -> Option<EntityId> {
-        let (mut current_entity, mut current_entity_children) = self.root_query.single(world);
+        self.root_query
+            .over(world)
+            .find_map(|root| self.get_entity_by_path_from(world, root, path))
+    }
 
+    fn get_entity_by_path_from(
+        &self,
+        world: &World,
+        (mut current_entity, mut current_entity_children): TreeNode,
+        path: &str,
+    ) -> Option<EntityId> {
         for part in path.trim_matches('/').split('/') {
             if part == "" {
                 continue;
             }
 
+            // Children without a name can't be part of a path
             let (next_entity_id, next_children) =
-                current_entity_children.0.iter().find_map(|&children_id| {
-                    let (entity_id, name, children) = self
-                        .children_query
-                        .get(world, children_id)
-                        .expect("children not found");
+                current_entity_children?.0.iter().find_map(|&children_id| {
+                    let (entity_id, name, children) =
+                        self.children_query.get(world, children_id)?;
 
                     if name.0 == part {
                         Some((entity_id, children))
@@ -59,7 +73,9 @@ impl WorldTreeExt for World {
 mod tests {
     use acro_ecs::{Name, World};
 
-    use crate::{tree::TreeData, Children, GlobalTransform, Parent, Root, Transform};
+    use crate::{
+        tree::TreeData, Children, GlobalTransform, Parent, Root, Transform, WorldHierarchyExt,
+    };
 
     use super::WorldTreeExt;
 
@@ -118,4 +134,33 @@ mod tests {
             child_2
         );
     }
+
+    #[test]
+    fn paths_follow_reparenting() {
+        let mut world = World::new();
+        world.init_component::<Name>();
+        world.init_component::<Root>();
+        world.init_component::<Parent>();
+        world.init_component::<Children>();
+        world.insert_resource(TreeData::new(&world));
+
+        let root = world.spawn((Name("root".to_string()), Root));
+        let a = world.spawn((Name("a".to_string()),));
+        let b = world.spawn((Name("b".to_string()),));
+        let unnamed = world.spawn((Transform::default(),));
+        world.add_child(root, unnamed).unwrap();
+        world.add_child(root, a).unwrap();
+        world.add_child(root, b).unwrap();
+
+        assert_eq!(world.get_entity_by_absolute_path("/b"), Some(b));
+
+        world.set_parent(b, a).unwrap();
+        assert_eq!(world.get_entity_by_absolute_path("/b"), None);
+        assert_eq!(world.get_entity_by_absolute_path("/a/b"), Some(b));
+
+        // A detached entity becomes another root, which doesn't hide the scene's tree
+        world.detach(b).unwrap();
+        assert_eq!(world.get_entity_by_absolute_path("/a/b"), None);
+        assert_eq!(world.get_entity_by_absolute_path("/a"), Some(a));
+    }
 }
@@ -28,6 +28,7 @@ pub type Mat3 = na::Matrix3<Float>;
 pub type Mat4 = na::Matrix4<Float>;
 
 pub type Quaternion = na::Quaternion<Float>;
+pub type UnitQuaternion = na::UnitQuaternion<Float>;
 
 #[cfg(not(target_arch = "wasm32"))]
 macro_rules! set_vector_op {
@@ -95,7 +96,7 @@ macro_rules! get_vector_op {
             )
             .map_err(eyre_to_any_error)?;
 
-            let data = *object.get::<$vector_type>(&path);
+            let data = object.get_value::<$vector_type>(&path);
 
             Ok($new_type_name {
                 $($new_type_fields: data.$new_type_fields),+
@@ -165,7 +166,7 @@ macro_rules! get_vector_op {
             )
             .map_err(wasm_ops::into_js_error)?;
 
-            let data = *object.get::<$vector_type>(&path);
+            let data = object.get_value::<$vector_type>(&path);
 
             Ok(serde_wasm_bindgen::to_value(&$new_type_name {
                 $($new_type_fields: data.$new_type_fields),+
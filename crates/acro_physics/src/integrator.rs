@@ -1,18 +1,12 @@
-use acro_ecs::{Query, ResMut, SystemRunContext, With};
+use acro_ecs::{Query, Res, SystemRunContext, Time, With};
 use acro_math::{Float, GlobalTransform, Transform};
-use chrono::Utc;
 use tracing::info;
 
 use crate::properties::{Force, Mass, Rigidbody3D, Velocity};
 
-#[derive(Debug, Default)]
-pub struct PhysicsContext {
-    pub last_integrate: Option<f64>,
-}
-
 pub fn integrate_velocity_and_acceleration(
     ctx: SystemRunContext,
-    mut context: ResMut<PhysicsContext>,
+    time: Res<Time>,
     rigidbodies: Query<
         (
             &GlobalTransform,
@@ -24,17 +18,7 @@ pub fn integrate_velocity_and_acceleration(
         With<Rigidbody3D>,
     >,
 ) {
-    let now = Utc::now().timestamp_micros() as f64 / 1_000_000.0;
-
-    let last_integrate = match context.last_integrate {
-        Some(last_integrate) => last_integrate,
-        None => {
-            context.last_integrate = Some(now);
-            return;
-        }
-    };
-
-    let dt = (now - last_integrate) as Float;
+    let dt = time.fixed_delta().as_secs_f64() as Float;
 
     for (global_transform, mut transform, mut velocity, mass, force) in rigidbodies.over(&ctx) {
         let force = force.0;
@@ -48,6 +32,4 @@ pub fn integrate_velocity_and_acceleration(
         // info!("position: {:?}", transform.position);
         // info!("velocity: {:?}", velocity.0);
     }
-
-    context.last_integrate = Some(now);
 }
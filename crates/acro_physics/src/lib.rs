@@ -1,7 +1,7 @@
 use acro_ecs::{Application, Plugin, Stage};
 use acro_math::Vec3;
 use acro_scene::ComponentLoaders;
-use integrator::{integrate_velocity_and_acceleration, PhysicsContext};
+use integrator::integrate_velocity_and_acceleration;
 use properties::{Force, Mass, Rigidbody3D, Velocity};
 
 mod integrator;
@@ -11,12 +11,11 @@ pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
     fn build(&mut self, app: &mut Application) {
-        app.init_component::<Mass>()
-            .init_component::<Velocity>()
-            .init_component::<Force>()
-            .init_component::<Rigidbody3D>()
-            .add_system(Stage::Update, [], integrate_velocity_and_acceleration)
-            .insert_resource(PhysicsContext::default())
+        app.register_component::<Mass>()
+            .register_component::<Velocity>()
+            .register_component::<Force>()
+            .register_component::<Rigidbody3D>()
+            .add_system(Stage::FixedUpdate, [], integrate_velocity_and_acceleration)
             .with_resource::<ComponentLoaders>(|loaders| {
                 loaders.register("Rigidbody3D", |world, entity, serialized| {
                     world.insert(entity, Rigidbody3D);
@@ -28,4 +27,76 @@ impl Plugin for PhysicsPlugin {
                 })
             });
     }
+
+    fn name(&self) -> &'static str {
+        "physics"
+    }
+
+    fn dependencies(&self) -> Vec<&'static str> {
+        vec!["math", "scene"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acro_assets::AssetsPlugin;
+    use acro_ecs::{Application, HeadlessRunner, Name};
+    use acro_math::{Float, MathPlugin, Transform};
+    use acro_scene::{SceneManager, ScenePlugin};
+
+    use crate::PhysicsPlugin;
+
+    const FALLING_SCENE: &str = r#"
+entities:
+  - name: ball
+    components:
+      - name: Transform
+        position: [0.0, 10.0, 0.0]
+        rotation: [0.0, 0.0, 0.0]
+        scale: [1.0, 1.0, 1.0]
+      - name: Rigidbody3D
+"#;
+
+    fn ball_height(runner: &HeadlessRunner) -> Float {
+        let world = runner.world();
+        let query = world.query::<(&Name, &Transform), ()>();
+        query
+            .over(&*world)
+            .find(|(name, _)| name.0 == "ball")
+            .map(|(_, transform)| transform.position.y)
+            .expect("ball not spawned")
+    }
+
+    #[test]
+    fn headless_scene_with_physics() {
+        let scene_path =
+            std::env::temp_dir().join(format!("acro_physics_falling_{}.yml", std::process::id()));
+        std::fs::write(&scene_path, FALLING_SCENE).unwrap();
+
+        let app = Application::new()
+            .add_plugin(AssetsPlugin)
+            .add_plugin(ScenePlugin)
+            .add_plugin(MathPlugin { scripting: false })
+            .add_plugin(PhysicsPlugin);
+        app.world()
+            .resource_mut::<SceneManager>()
+            .queue(scene_path.to_str().unwrap());
+
+        let mut runner = HeadlessRunner::new(app);
+        // The scene is loaded during the first frame
+        runner.step();
+        std::fs::remove_file(&scene_path).unwrap();
+        let start_height = ball_height(&runner);
+
+        runner.step_n(10);
+        let end_height = ball_height(&runner);
+
+        // Every fixed step of 1/60s moves by the velocity before adding gravity (-10/s²). The
+        // first frame already gave the body one step's worth of velocity.
+        let expected_drop = (1..=10)
+            .map(|step| step as Float / 6.0 / 60.0)
+            .sum::<Float>();
+        assert_eq!(start_height, 10.0);
+        assert!((start_height - end_height - expected_drop).abs() < 1e-3);
+    }
 }
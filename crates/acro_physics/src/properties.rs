@@ -1,11 +1,14 @@
+use acro_ecs::Component;
 use acro_math::{Float, Vec3};
 
+#[derive(Component)]
 pub struct Rigidbody3D;
 
+#[derive(Component)]
 pub struct Mass(pub Float);
 
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Component)]
 pub struct Velocity(pub Vec3);
 
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Component)]
 pub struct Force(pub Vec3);
@@ -54,6 +54,8 @@ fn reflect_derive_struct(
     let field_names_string_3 = field_names_string.clone();
     let field_idents_4 = field_idents.clone();
     let field_names_string_4 = field_names_string.clone();
+    let field_idents_5 = field_idents.clone();
+    let field_names_string_5 = field_names_string.clone();
 
     quote! {
         impl acro_reflect::Reflect for #name {
@@ -73,6 +75,19 @@ fn reflect_derive_struct(
                 }
             }
 
+            fn get_computed(
+                &self,
+                path: &acro_reflect::ReflectPath,
+            ) -> Option<Box<dyn std::any::Any>> {
+                match path {
+                    #(
+                        acro_reflect::ReflectPath::Property(#field_names_string_5, rest)
+                            => self.#field_idents_5.get_computed(rest),
+                    )*
+                    _ => None,
+                }
+            }
+
             fn set_any(
                 &mut self,
                 path: &acro_reflect::ReflectPath,
@@ -48,6 +48,12 @@ pub trait Reflect {
     fn set_any(&mut self, path: &ReflectPath, data: Box<dyn Any>) -> Result<(), ReflectSetError>;
     fn get_opt(&self, path: &ReflectPath) -> Option<&dyn Any>;
 
+    /// Reads a value that is computed instead of stored in a field, e.g. a rotation exposed as
+    /// Euler angles. Only used when `get_opt` finds nothing at the path.
+    fn get_computed(&self, _path: &ReflectPath) -> Option<Box<dyn Any>> {
+        None
+    }
+
     fn call_method(
         &mut self,
         _path: &ReflectPath,
@@ -77,6 +83,19 @@ pub trait ReflectExt: Reflect {
             .expect("type mismatch")
     }
 
+    /// Reads a copy of the value at the path, which may be stored or computed.
+    fn get_value<T: Clone + 'static>(&self, path: &ReflectPath) -> T {
+        if let Some(value) = self.get_opt(path) {
+            return value.downcast_ref::<T>().expect("type mismatch").clone();
+        }
+
+        *self
+            .get_computed(path)
+            .unwrap_or_else(|| panic!("field {path:?} not found on {}", self.get_name()))
+            .downcast()
+            .expect("type mismatch")
+    }
+
     fn set<T: 'static>(&mut self, path: &ReflectPath, value: T) {
         self.set_any(path, Box::new(value))
             .expect("error setting value");
@@ -174,6 +193,15 @@ mod tests {
             }
         }
 
+        fn get_computed(&self, path: &R) -> Option<Box<dyn Any>> {
+            match path {
+                R::Property("b_squared", path) if **path == R::End => {
+                    Some(Box::new(self.b * self.b))
+                }
+                _ => None,
+            }
+        }
+
         fn get_field_names(&self) -> &'static [&'static str] {
             &["b"]
         }
@@ -228,6 +256,13 @@ mod tests {
             }
         }
 
+        fn get_computed(&self, path: &R) -> Option<Box<dyn Any>> {
+            match path {
+                R::Property("inner", rest) => self.inner.get_computed(rest),
+                _ => None,
+            }
+        }
+
         fn get_field_names(&self) -> &'static [&'static str] {
             &["a", "inner"]
         }
@@ -261,6 +296,10 @@ mod tests {
 
         assert_eq!(test.get_name(), "ReflectedStruct");
 
+        assert_eq!(test.get_value::<u32>(&R::parse("a")), 2);
+        assert_eq!(test.get_value::<u32>(&R::parse("inner.b_squared")), 9);
+        assert!(test.get_opt(&R::parse("inner.b_squared")).is_none());
+
         assert_eq!(
             *test
                 .call_method(&R::parse("inner.get_b_times_two"), vec![])
@@ -1,4 +1,4 @@
-use acro_ecs::{Query, Res, SystemRunContext, With};
+use acro_ecs::{Component, IncludeDisabled, Query, Res, SystemRunContext, With};
 use acro_math::{Float, Mat4};
 use serde::{Deserialize, Serialize};
 use tracing::info;
@@ -6,7 +6,7 @@ use winit::dpi::PhysicalSize;
 
 use crate::state::RendererHandle;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Component)]
 pub struct Camera {
     pub(crate) camera_type: CameraType,
     pub(crate) projection_matrix: Mat4,
@@ -70,12 +70,12 @@ impl CameraType {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Component)]
 pub struct MainCamera;
 
 pub fn update_projection_matrix(
     ctx: SystemRunContext,
-    query: Query<&mut Camera, With<MainCamera>>,
+    query: Query<&mut Camera, (With<MainCamera>, IncludeDisabled)>,
     renderer: Res<RendererHandle>,
 ) {
     let new_size = *renderer.size.borrow();
@@ -13,7 +13,7 @@ use std::cell::RefCell;
 pub use crate::{
     camera::{Camera, CameraType, MainCamera},
     color::{Color, Srgba},
-    mesh::Mesh,
+    mesh::{Mesh, MeshBundle},
     state::{FrameState, RendererHandle},
     texture::Texture,
     window::WindowState,
@@ -33,9 +33,9 @@ pub struct RenderPlugin;
 
 impl Plugin for RenderPlugin {
     fn build(&mut self, app: &mut Application) {
-        app.init_component::<Mesh>()
-            .init_component::<Camera>()
-            .init_component::<MainCamera>()
+        app.register_component::<Mesh>()
+            .register_component::<Camera>()
+            .register_component::<MainCamera>()
             .with_resource::<ScriptingRuntime>(|mut runtime| {
                 #[cfg(not(target_arch = "wasm32"))]
                 {
@@ -94,6 +94,14 @@ impl Plugin for RenderPlugin {
         app.add_system(Stage::Render, [], render_mesh_system);
         app.add_system(Stage::PostRender, [], end_render_system);
     }
+
+    fn name(&self) -> &'static str {
+        "render"
+    }
+
+    fn dependencies(&self) -> Vec<&'static str> {
+        vec!["assets", "math", "scene", "scripting"]
+    }
 }
 
 fn start_render_system(_ctx: SystemRunContext, renderer: Res<RendererHandle>) {
@@ -1,10 +1,15 @@
+use std::sync::Once;
+
 use acro_assets::Assets;
-use acro_ecs::{Changed, EntityId, Query, Res, SystemRunContext, With};
-use acro_math::{GlobalTransform, Vec2, Vec3};
+use acro_ecs::{
+    Bundle, Changed, Component, EntityId, IncludeDisabled, Query, QuerySingleError, Res,
+    SystemRunContext, With, World,
+};
+use acro_math::{GlobalTransform, TransformBundle, Vec2, Vec3};
 use bytemuck::{Pod, Zeroable};
 use cfg_if::cfg_if;
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{info, warn};
 use wgpu::util::DeviceExt;
 
 use crate::{
@@ -15,7 +20,8 @@ use crate::{
     Camera, RendererHandle,
 };
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Component, Serialize, Deserialize)]
+#[component(on_remove = remove_mesh)]
 pub struct Mesh {
     // pub vertices: Vec<Vertex>,
     // pub indices: Vec<u32>,
@@ -48,12 +54,44 @@ impl Mesh {
     }
 }
 
+/// A mesh along with the transform it is rendered at.
+#[derive(Debug, Bundle)]
+pub struct MeshBundle {
+    pub mesh: Mesh,
+    pub transform: TransformBundle,
+}
+
+/// Frees the mesh's GPU resources right away instead of waiting for the component to be dropped,
+/// and stops notifying it about shader reloads.
+pub(crate) fn remove_mesh(world: &mut World, entity: EntityId) {
+    let mesh_component = world.get_component_info::<Mesh>().id;
+    let Some(mesh) = world.get_mut::<Mesh>(entity) else {
+        return;
+    };
+    let Some(data) = mesh.data.take() else {
+        return;
+    };
+    let shader_path = mesh.shader_path.clone();
+
+    data.vertex_buffer.destroy();
+    data.index_buffer.destroy();
+
+    // The assets may already be gone when the world is torn down
+    if !world.resources().contains::<Assets>() {
+        return;
+    }
+    world
+        .resource::<Assets>()
+        .get::<Shader>(&shader_path)
+        .remove_notify_changes_from_component(entity, mesh_component);
+}
+
 // TODO:
 // Should mesh buffer data be uploaded to the GPU in a synchronous or asynchronous manner?
 // Should the mesh buffer data be stored separately from the component?
 pub fn upload_mesh_system(
     ctx: SystemRunContext,
-    mesh_query: Query<(EntityId, &mut Mesh), Changed<Mesh>>,
+    mesh_query: Query<(EntityId, &mut Mesh), (Changed<Mesh>, IncludeDisabled)>,
     renderer: Res<RendererHandle>,
     assets: Res<Assets>,
 ) {
@@ -159,12 +197,30 @@ pub fn render_mesh_system(
     renderer: Res<RendererHandle>,
     assets: Res<Assets>,
 ) {
+    let (camera_transform, camera) = match camera_query.try_single(&ctx) {
+        Ok(camera) => camera,
+        // Nothing to draw until there is an enabled main camera
+        Err(QuerySingleError::None) => return,
+        Err(QuerySingleError::Multiple) => {
+            static WARN_MULTIPLE: Once = Once::new();
+            WARN_MULTIPLE.call_once(|| {
+                warn!("more than one main camera, rendering with the first one");
+            });
+            camera_query
+                .over(&ctx)
+                .next()
+                .expect("main camera disappeared")
+        }
+    };
+    // The view matrix takes world space into camera space; a camera scaled to zero sees nothing
+    let Some(view_matrix) = camera_transform.inverse().map(|inverse| inverse.matrix) else {
+        return;
+    };
+
     let frame_state = renderer.frame_state();
     let view = &frame_state.view;
     let mut encoder = frame_state.encoder.borrow_mut();
 
-    let (camera_transform, camera) = camera_query.single(&ctx);
-
     for (global_transform, mesh) in mesh_query.over(&ctx) {
         let data = mesh.data.as_ref().expect("mesh data not loaded");
         let shader = assets.get::<Shader>(&mesh.shader_path);
@@ -221,7 +277,7 @@ pub fn render_mesh_system(
             renderer.queue.write_buffer(
                 &view_matrix_uniform.data_type.assert_buffer(),
                 0,
-                bytemuck::cast_slice(camera_transform.matrix.as_slice()),
+                bytemuck::cast_slice(view_matrix.as_slice()),
             );
             let projection_matrix_uniform = view_projection_matrix_bind_group
                 .uniforms
@@ -139,7 +139,7 @@ impl Window {
             .as_mut()
             .expect("application not created")
             .world()
-            .insert_resource(new_state.clone());
+            .insert_non_send_resource(new_state.clone());
 
         *state.borrow_mut() = Some(new_state);
 
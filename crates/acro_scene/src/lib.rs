@@ -8,11 +8,11 @@ use acro_ecs::{
 mod manager;
 mod scene;
 
-use acro_math::{GlobalTransform, Transform};
+use acro_math::{Transform, TransformBundle};
 use acro_scripting::{Behavior, SourceFile};
 use eyre::Result;
 use manager::load_queued_scene;
-pub use manager::SceneManager;
+pub use manager::{queue_scene, SceneManager};
 
 pub type ComponentLoader = fn(&mut World, EntityId, serde_yml::Value) -> Result<()>;
 
@@ -41,8 +41,8 @@ impl Plugin for ScenePlugin {
     fn build(&mut self, app: &mut Application) {
         let loaders = ComponentLoaders::default();
         loaders.register("Transform", |world, entity, serialized| {
-            world.insert(entity, serde_yml::from_value::<Transform>(serialized)?);
-            world.insert(entity, GlobalTransform::default());
+            let transform = serde_yml::from_value::<Transform>(serialized)?;
+            world.insert_bundle(entity, TransformBundle::from_transform(transform));
             Ok(())
         });
         loaders.register("Behavior", |world, entity, serialized| {
@@ -59,10 +59,22 @@ impl Plugin for ScenePlugin {
             .insert_resource(SceneManager::default())
             .add_system(
                 Stage::PreUpdate,
-                [SystemSchedulingRequirement::RunBefore(SystemId::Native(
-                    load_queued_assets.type_id(),
-                ))],
+                [
+                    SystemSchedulingRequirement::RunBefore(SystemId::Native(
+                        load_queued_assets.type_id(),
+                    )),
+                    SystemSchedulingRequirement::RunOnMainThread,
+                ],
                 load_queued_scene,
             );
     }
+
+    fn name(&self) -> &'static str {
+        "scene"
+    }
+
+    // The built-in loaders insert math components and queue behavior source files
+    fn dependencies(&self) -> Vec<&'static str> {
+        vec!["assets", "math", "scripting"]
+    }
 }
@@ -16,6 +16,13 @@ impl SceneManager {
     }
 }
 
+/// Creates a system that queues a scene, to load scenes on state transitions:
+/// `app.add_state_system(OnEnter(GameState::Playing), [], queue_scene("level.scene"))`
+pub fn queue_scene(scene: impl ToString) -> impl Fn(SystemRunContext, ResMut<SceneManager>) {
+    let scene = scene.to_string();
+    move |_ctx, mut scene_manager| scene_manager.queue(&scene)
+}
+
 pub fn load_queued_scene(
     ctx: SystemRunContext,
     mut scene_manager: ResMut<SceneManager>,
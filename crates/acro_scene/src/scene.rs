@@ -1,7 +1,7 @@
 use std::{any::Any, collections::HashMap};
 
 use acro_ecs::{EntityId, Name, World};
-use acro_math::{Children, GlobalTransform, Parent, Root, Transform};
+use acro_math::{Children, GlobalTransform, Root, Transform, WorldHierarchyExt};
 use tracing::warn;
 
 use crate::{ComponentLoader, ComponentLoaders};
@@ -36,19 +36,15 @@ impl Scene {
             Root,
             Transform::default(),
             GlobalTransform::default(),
+            Children(vec![]),
         ));
 
         let component_loaders = world.resources().get::<ComponentLoaders>().loaders.clone();
         let component_loaders = &*component_loaders.borrow();
 
-        let mut root_children = vec![];
         for entity in self.entities.into_iter() {
-            let entity_id =
-                Self::spawn_entity_with_parent(world, root_entity, entity, component_loaders);
-            root_children.push(entity_id);
+            Self::spawn_entity_with_parent(world, root_entity, entity, component_loaders);
         }
-
-        world.insert(root_entity, Children(root_children));
     }
 
     fn spawn_entity_with_parent(
@@ -56,19 +52,16 @@ impl Scene {
         parent: EntityId,
         entity: Entity,
         component_loaders: &HashMap<String, ComponentLoader>,
-    ) -> EntityId {
-        let entity_id = world.spawn((Name(entity.name.clone()),));
+    ) {
+        let entity_id = world.spawn((Name(entity.name.clone()), Children(vec![])));
+        world
+            .add_child(parent, entity_id)
+            .expect("scene entities should be spawned under an existing parent");
 
-        let mut children = vec![];
         for child in entity.children.into_iter() {
-            let child_id =
-                Self::spawn_entity_with_parent(world, entity_id, child, component_loaders);
-            children.push(child_id);
+            Self::spawn_entity_with_parent(world, entity_id, child, component_loaders);
         }
 
-        world.insert(entity_id, Children(children));
-        world.insert(entity_id, Parent(parent));
-
         // println!("{:?}", entity.components);
 
         for component in entity.components.into_iter() {
@@ -81,8 +74,6 @@ impl Scene {
                 warn!("No loader for component `{}`. Ignoring..", component.name);
             }
         }
-
-        entity_id
     }
 }
 
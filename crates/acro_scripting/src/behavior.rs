@@ -1,7 +1,9 @@
 use acro_assets::Asset;
+use acro_ecs::Component;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Component, Serialize, Deserialize)]
+#[component(on_remove = crate::runtime::remove_behavior)]
 pub struct Behavior {
     pub source: String,
     #[serde(skip)]
@@ -29,9 +29,9 @@ pub struct ScriptingPlugin;
 impl Plugin for ScriptingPlugin {
     fn build(&mut self, app: &mut Application) {
         let world_handle = app.get_world_handle();
-        app.init_component::<Behavior>()
-            .insert_resource(EventListenerStore::default())
-            .insert_resource(ScriptingRuntime::new(world_handle))
+        app.register_component::<Behavior>()
+            .insert_non_send_resource(EventListenerStore::default())
+            .insert_non_send_resource(ScriptingRuntime::new(world_handle))
             .with_resource::<Assets>(|mut assets| {
                 assets.register_loader::<SourceFile>();
             })
@@ -52,4 +52,12 @@ impl Plugin for ScriptingPlugin {
                 flush_events,
             );
     }
+
+    fn name(&self) -> &'static str {
+        "scripting"
+    }
+
+    fn dependencies(&self) -> Vec<&'static str> {
+        vec!["assets"]
+    }
 }
@@ -41,7 +41,7 @@ pub fn op_get_property_string(
     )
     .map_err(eyre_to_any_error)?;
 
-    Ok(object.get::<String>(&path).clone())
+    Ok(object.get_value::<String>(&path))
 }
 
 #[op2(fast)]
@@ -96,7 +96,7 @@ pub fn op_get_property_number(
     )
     .map_err(eyre_to_any_error)?;
 
-    Ok(*object.get::<f32>(&path) as f64)
+    Ok(object.get_value::<f32>(&path) as f64)
 }
 
 #[op2(fast)]
@@ -150,7 +150,7 @@ pub fn op_get_property_boolean(
     )
     .map_err(eyre_to_any_error)?;
 
-    Ok(*object.get::<bool>(&path))
+    Ok(object.get_value::<bool>(&path))
 }
 
 #[op2(fast)]
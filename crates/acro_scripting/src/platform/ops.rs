@@ -28,11 +28,14 @@ pub fn get_dyn_reflect<'a>(
             },
         )
         .ok_or_else(|| eyre::eyre!("entity or component not found"))?;
+    let vtable = component_ids_to_vtables
+        .get(&component)
+        .ok_or_else(|| eyre::eyre!("component is not exposed to scripts"))?;
 
     Ok(unsafe {
         std::mem::transmute::<(*const (), *const ()), &mut dyn Reflect>((
             data_ptr.as_ptr() as *const (),
-            component_ids_to_vtables[&ComponentId(component_id)] as *const (),
+            *vtable,
         ))
     })
 }
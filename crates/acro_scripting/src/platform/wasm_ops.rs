@@ -102,7 +102,7 @@ pub fn op_get_property_number(
     )
     .map_err(into_js_error)?;
 
-    Ok(*object.get::<f32>(&path) as f64)
+    Ok(object.get_value::<f32>(&path) as f64)
 }
 
 #[wasm_bindgen]
@@ -152,7 +152,7 @@ pub fn op_get_property_string(
     )
     .map_err(into_js_error)?;
 
-    Ok(object.get::<String>(&path).clone())
+    Ok(object.get_value::<String>(&path))
 }
 
 #[wasm_bindgen]
@@ -202,7 +202,7 @@ pub fn op_get_property_boolean(
     )
     .map_err(into_js_error)?;
 
-    Ok(*object.get::<bool>(&path))
+    Ok(object.get_value::<bool>(&path))
 }
 
 #[wasm_bindgen]
@@ -1,7 +1,10 @@
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use acro_assets::Assets;
-use acro_ecs::{Changed, ComponentId, EntityId, Query, Res, ResMut, SystemRunContext, Tick, World};
+use acro_ecs::{
+    Changed, Component, ComponentId, Disabled, EntityId, IncludeDisabled, Query, Res, ResMut,
+    SystemRunContext, Tick, Time, With, World,
+};
 use acro_reflect::Reflect;
 
 pub trait Platform {
@@ -14,7 +17,14 @@ pub trait Platform {
         attached_to: EntityId,
         source_file: &SourceFile,
     ) -> eyre::Result<()>;
-    fn update(&mut self, last_update: DateTime<Utc>, tick: Tick) -> eyre::Result<DateTime<Utc>>;
+    fn destroy_behavior(&mut self, id: u32) -> eyre::Result<()>;
+    /// Updates every behavior except the ones in `disabled_behaviors`.
+    fn update(
+        &mut self,
+        delta_time: f64,
+        tick: Tick,
+        disabled_behaviors: &[u32],
+    ) -> eyre::Result<()>;
     fn late_init(
         &mut self,
         component_vtables: &mut ComponentVTables,
@@ -111,28 +121,37 @@ mod runtime_impl {
             Ok(())
         }
 
+        fn destroy_behavior(&mut self, id: u32) -> eyre::Result<()> {
+            let module_handle = self.init_module_handle.as_ref().map(|h| h.clone());
+            self.inner_mut().call_function::<()>(
+                module_handle.as_ref(),
+                "destroyBehavior",
+                json_args!(id),
+            )?;
+
+            Ok(())
+        }
+
         fn update(
             &mut self,
-            last_update: DateTime<Utc>,
+            delta_time: f64,
             tick: Tick,
-        ) -> eyre::Result<DateTime<Utc>> {
+            disabled_behaviors: &[u32],
+        ) -> eyre::Result<()> {
             self.inner_mut()
                 .deno_runtime()
                 .op_state()
                 .borrow_mut()
                 .put(tick);
 
-            let delta_time = Utc::now().timestamp_subsec_nanos() as f64 / 1_000_000_000.0
-                - last_update.timestamp_subsec_nanos() as f64 / 1_000_000_000.0;
-
             let module_handle = self.init_module_handle.as_ref().map(|h| h.clone());
             self.inner_mut().call_function::<()>(
                 module_handle.as_ref(),
                 "update",
-                json_args!(delta_time),
+                json_args!(delta_time, disabled_behaviors),
             )?;
 
-            Ok(Utc::now())
+            Ok(())
         }
 
         fn late_init(
@@ -270,15 +289,14 @@ mod runtime_impl {
 
 #[cfg(target_arch = "wasm32")]
 mod runtime_impl {
-    use acro_ecs::{utils::TimeDeltaExt, Tick};
-    use chrono::{DateTime, Utc};
+    use acro_ecs::Tick;
     use js_sys::{Object, Reflect};
     use wasm_bindgen::prelude::*;
 
     #[wasm_bindgen]
     extern "C" {
         #[wasm_bindgen(js_namespace = acro, js_name = update)]
-        fn js_update(delta_time: f64);
+        fn js_update(delta_time: f64, disabled_behaviors: &[u32]);
 
         #[wasm_bindgen(js_namespace = acro, js_name = registerComponents)]
         // components: Record<string, number>
@@ -286,6 +304,9 @@ mod runtime_impl {
 
         #[wasm_bindgen(js_namespace = acro, js_name = createBehavior)]
         fn js_create_behavior(generation: u32, index: u32, id: u32, name: &str);
+
+        #[wasm_bindgen(js_namespace = acro, js_name = destroyBehavior)]
+        fn js_destroy_behavior(id: u32);
     }
 
     pub struct WasmPlatform {
@@ -327,20 +348,22 @@ mod runtime_impl {
             Ok(())
         }
 
+        fn destroy_behavior(&mut self, id: u32) -> eyre::Result<()> {
+            js_destroy_behavior(id);
+
+            Ok(())
+        }
+
         fn update(
             &mut self,
-            last_update: DateTime<Utc>,
+            delta_time: f64,
             tick: Tick,
-        ) -> eyre::Result<DateTime<Utc>> {
+            disabled_behaviors: &[u32],
+        ) -> eyre::Result<()> {
             WASM_OPS_STATE.insert(tick);
+            js_update(delta_time, disabled_behaviors);
 
-            let delta_time = Utc::now()
-                .signed_duration_since(last_update)
-                .get_frac_secs() as f64;
-
-            js_update(delta_time);
-
-            Ok(Utc::now())
+            Ok(())
         }
 
         fn late_init(
@@ -404,9 +427,8 @@ mod runtime_impl {
     use crate::{behavior::BehaviorData, wasm_ops::WASM_OPS_STATE};
 }
 
-use chrono::{DateTime, Utc};
 use serde::de::DeserializeOwned;
-use tracing::info;
+use tracing::{error, info};
 
 use crate::{
     behavior::{Behavior, BehaviorData},
@@ -418,7 +440,6 @@ use crate::{
 type ComponentVTables = Option<HashMap<ComponentId, *const ()>>;
 
 pub struct ScriptingRuntime {
-    last_update: DateTime<Utc>,
     world_handle: Rc<RefCell<World>>,
     behavior_id: u32,
     name_to_component_id: HashMap<String, ComponentId>,
@@ -437,7 +458,6 @@ impl std::fmt::Debug for ScriptingRuntime {
 impl ScriptingRuntime {
     pub fn new(world_handle: Rc<RefCell<World>>) -> Self {
         Self {
-            last_update: Utc::now(),
             behavior_id: 0,
             world_handle,
             name_to_component_id: HashMap::new(),
@@ -465,6 +485,17 @@ impl ScriptingRuntime {
             .insert(name.to_string(), component_info.id);
     }
 
+    /// Registers a component under the script name from its `Component` impl.
+    pub fn register_script_component<T: Component + Reflect>(&mut self) {
+        let name = T::SCRIPT_NAME.unwrap_or_else(|| {
+            panic!(
+                "component {} has no script name",
+                std::any::type_name::<T>()
+            )
+        });
+        self.register_component::<T>(name);
+    }
+
     pub fn init_source_file(&mut self, source_file: &SourceFile) -> eyre::Result<()> {
         self.platform.init_source_file(source_file)
     }
@@ -482,9 +513,20 @@ impl ScriptingRuntime {
         self.platform.init_behavior(id, attached_to, source_file)
     }
 
-    pub fn update(&mut self, tick: Tick) -> eyre::Result<()> {
-        self.last_update = self.platform.update(self.last_update, tick)?;
-        Ok(())
+    pub fn destroy_behavior(&mut self, behavior: &mut Behavior) -> eyre::Result<()> {
+        match behavior.data.take() {
+            Some(data) => self.platform.destroy_behavior(data.id),
+            None => Ok(()),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        delta_time: f64,
+        tick: Tick,
+        disabled_behaviors: &[u32],
+    ) -> eyre::Result<()> {
+        self.platform.update(delta_time, tick, disabled_behaviors)
     }
 
     pub fn late_init(&mut self) {
@@ -517,7 +559,7 @@ pub fn late_init_scripting_runtime(_ctx: SystemRunContext, mut runtime: ResMut<S
 
 pub fn init_behavior(
     ctx: SystemRunContext,
-    behaviors: Query<(EntityId, &mut Behavior), Changed<Behavior>>,
+    behaviors: Query<(EntityId, &mut Behavior), (Changed<Behavior>, IncludeDisabled)>,
     assets: Res<Assets>,
     mut runtime: ResMut<ScriptingRuntime>,
 ) -> eyre::Result<()> {
@@ -530,12 +572,54 @@ pub fn init_behavior(
     Ok(())
 }
 
+/// Drops the script instance of a removed Behavior and stops notifying it about source file
+/// reloads.
+pub(crate) fn remove_behavior(world: &mut World, entity: EntityId) {
+    let behavior_component = world.get_component_info::<Behavior>().id;
+    let Some(behavior) = world.get_mut::<Behavior>(entity) else {
+        return;
+    };
+    if behavior.data.is_none() {
+        return;
+    }
+    // Take the instance out so the world is free to borrow the resources below
+    let mut behavior = Behavior {
+        source: behavior.source.clone(),
+        data: behavior.data.take(),
+    };
+
+    // Either resource may already be gone when the world is torn down
+    if world.resources().contains::<Assets>() {
+        world
+            .resource::<Assets>()
+            .get::<SourceFile>(&behavior.source)
+            .remove_notify_changes_from_component(entity, behavior_component);
+    }
+
+    if !world.resources().contains::<ScriptingRuntime>() {
+        return;
+    }
+    if let Err(error) = world
+        .resource_mut::<ScriptingRuntime>()
+        .destroy_behavior(&mut behavior)
+    {
+        error!("failed to destroy behavior on {entity:?}: {error:?}");
+    }
+}
+
 pub fn update_behaviors(
     ctx: SystemRunContext,
+    disabled_behaviors: Query<&Behavior, With<Disabled>>,
     mut runtime: ResMut<ScriptingRuntime>,
+    time: Res<Time>,
 ) -> eyre::Result<()> {
+    let disabled_behaviors = disabled_behaviors
+        .over(&ctx)
+        .filter_map(|behavior| behavior.data.as_ref().map(|data| data.id))
+        .collect::<Vec<_>>();
+
     // let now = Utc::now();
-    runtime.update(ctx.tick)?;
+    runtime.update(time.delta().as_secs_f64(), ctx.tick, &disabled_behaviors)?;
     // info!(
     //     "update_behaviors: {:?}",
     //     Utc::now().signed_duration_since(now).pretty()
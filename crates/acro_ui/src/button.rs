@@ -1,11 +1,12 @@
-use acro_ecs::{Query, Res, ResMut, SystemRunContext};
+use acro_ecs::{Component, Query, Res, ResMut, SystemRunContext};
 use acro_reflect::Reflect;
 use acro_scripting::{EventEmitter, EventQueue};
 use tracing::info;
 
 use crate::ui_element_state::UiElementState;
 
-#[derive(Debug, Default, Reflect)]
+#[derive(Debug, Default, Component, Reflect)]
+#[component(script_name = "Button")]
 pub struct Button {
     pub last_press_state: bool,
     pub click: EventEmitter<()>,
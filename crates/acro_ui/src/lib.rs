@@ -33,12 +33,12 @@ impl Plugin for UiPlugin {
     fn build(&mut self, app: &mut Application) {
         let ui_context = UiContext::default();
 
-        app.init_component::<Rect>()
-            .init_component::<UiElementState>()
-            .init_component::<Text>()
-            .init_component::<ScreenUi>()
-            .init_component::<Panel>()
-            .init_component::<Button>()
+        app.register_component::<Rect>()
+            .register_component::<UiElementState>()
+            .register_component::<Text>()
+            .register_component::<ScreenUi>()
+            .register_component::<Panel>()
+            .register_component::<Button>()
             .insert_resource(ui_context)
             .with_resource::<ComponentLoaders>(|loaders| {
                 loaders.register("ScreenUi", |world, entity, value| {
@@ -69,8 +69,8 @@ impl Plugin for UiPlugin {
                 });
             })
             .with_resource::<ScriptingRuntime>(|mut runtime| {
-                runtime.register_component::<Text>("Text");
-                runtime.register_component::<Button>("Button");
+                runtime.register_script_component::<Text>();
+                runtime.register_script_component::<Button>();
             })
             .add_system(Stage::PreUpdate, [], poll_ui_element_state)
             .add_system(
@@ -95,4 +95,12 @@ impl Plugin for UiPlugin {
             .add_system(Stage::PreRender, [], init_text)
             .add_system(Stage::Render, [], render_text);
     }
+
+    fn name(&self) -> &'static str {
+        "ui"
+    }
+
+    fn dependencies(&self) -> Vec<&'static str> {
+        vec!["math", "render", "scene", "scripting"]
+    }
 }
@@ -1,10 +1,10 @@
-use acro_ecs::{Query, Res, ResMut, SystemRunContext};
+use acro_ecs::{Component, Query, Res, ResMut, SystemRunContext};
 use acro_render::{Color, RendererHandle};
 use serde::{Deserialize, Serialize};
 
 use crate::{box_renderer::BoxInstance, context::UiContext, rect::Rect};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Component, Serialize, Deserialize)]
 pub struct Panel {
     pub(crate) color: Color,
 }
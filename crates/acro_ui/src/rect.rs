@@ -5,13 +5,13 @@ use std::{
     rc::Rc,
 };
 
-use acro_ecs::{entity, query, EntityId, Query, SystemRunContext};
+use acro_ecs::{entity, query, Component, EntityId, IncludeDisabled, Query, SystemRunContext};
 use acro_math::{Children, Parent, Vec2};
 use serde::{Deserialize, Serialize};
 
 use crate::positioning_options::{Dim, DirDim, FlexDirection, FlexOptions, PositioningOptions};
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Component)]
 pub struct Rect {
     inner: Rc<RefCell<RectInner>>,
 }
@@ -52,17 +52,17 @@ pub struct RootOptions {
 
 pub struct RectQueries<'a> {
     pub ctx: &'a SystemRunContext<'a>,
-    pub children_query: &'a Query<&'a Children>,
-    pub parent_query: &'a Query<&'a Parent>,
-    pub rect_query: &'a Query<&'a Rect>,
+    pub children_query: &'a Query<&'a Children, IncludeDisabled>,
+    pub parent_query: &'a Query<&'a Parent, IncludeDisabled>,
+    pub rect_query: &'a Query<&'a Rect, IncludeDisabled>,
 }
 
-impl RectQueries<'_> {
-    pub fn new<'a>(
+impl<'a> RectQueries<'a> {
+    pub fn new(
         ctx: &'a SystemRunContext,
-        children_query: &'a Query<&'a Children>,
-        parent_query: &'a Query<&'a Parent>,
-        rect_query: &'a Query<&'a Rect>,
+        children_query: &'a Query<&'a Children, IncludeDisabled>,
+        parent_query: &'a Query<&'a Parent, IncludeDisabled>,
+        rect_query: &'a Query<&'a Rect, IncludeDisabled>,
     ) -> RectQueries<'a> {
         RectQueries {
             ctx,
@@ -79,10 +79,10 @@ impl RectQueries<'_> {
             .0
     }
 
-    pub fn get_children(&self, entity_id: EntityId) -> Vec<EntityId> {
+    pub fn get_children(&self, entity_id: EntityId) -> &'a [EntityId] {
         self.children_query
             .get(self.ctx, entity_id)
-            .map(|children| children.0.clone())
+            .map(|children| children.0.as_slice())
             .unwrap_or_default()
     }
 
@@ -177,7 +177,7 @@ impl Rect {
 
         let children = queries.get_children(entity_id);
 
-        for &child_id in &children {
+        for &child_id in children {
             queries
                 .get_full_rect(child_id)
                 .recalculate(child_id, queries);
@@ -524,7 +524,7 @@ fn opt_clamp(value: f32, min: Option<f32>, max: Option<f32>) -> f32 {
 }
 #[cfg(test)]
 mod tests {
-    use acro_ecs::{EntityId, Query, SystemRunContext, Tick, World};
+    use acro_ecs::{EntityId, IncludeDisabled, Query, SystemRunContext, Tick, World};
     use acro_math::{Children, Parent, Root, Vec2};
 
     use crate::{
@@ -548,9 +548,9 @@ mod tests {
         world
             .run_system(
                 move |ctx: SystemRunContext,
-                      children_query: Query<&Children>,
-                      parent_query: Query<&Parent>,
-                      rect_query: Query<&Rect>| {
+                      children_query: Query<&Children, IncludeDisabled>,
+                      parent_query: Query<&Parent, IncludeDisabled>,
+                      rect_query: Query<&Rect, IncludeDisabled>| {
                     let queries =
                         RectQueries::new(&ctx, &children_query, &parent_query, &rect_query);
 
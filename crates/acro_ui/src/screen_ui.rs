@@ -1,4 +1,4 @@
-use acro_ecs::{EntityId, Query, Res, SystemRunContext};
+use acro_ecs::{Component, EntityId, IncludeDisabled, Query, Res, SystemRunContext};
 use acro_math::{Children, Parent, Vec2};
 use acro_render::RendererHandle;
 use tracing::info;
@@ -8,14 +8,16 @@ use crate::{
     rect::{Rect, RectQueries},
 };
 
+#[derive(Component)]
 pub struct ScreenUi;
 
 pub fn update_screen_ui_rect(
     ctx: SystemRunContext,
     screen_ui_query: Query<(EntityId, &mut ScreenUi, &Rect)>,
-    children_query: Query<&Children>,
-    parent_query: Query<&Parent>,
-    rect_query: Query<&Rect>,
+    // Disabled elements are still laid out, so they are in place once enabled again
+    children_query: Query<&Children, IncludeDisabled>,
+    parent_query: Query<&Parent, IncludeDisabled>,
+    rect_query: Query<&Rect, IncludeDisabled>,
     renderer: Res<RendererHandle>,
 ) {
     let renderer_size = renderer.size.borrow();
@@ -1,6 +1,6 @@
 use std::cell::RefCell;
 
-use acro_ecs::{Changed, Query, Res, ResMut, SystemRunContext};
+use acro_ecs::{Changed, Component, IncludeDisabled, Query, Res, ResMut, SystemRunContext};
 use acro_math::Vec2;
 use acro_reflect::Reflect;
 use acro_render::{FrameState, RendererHandle};
@@ -16,7 +16,8 @@ use crate::{
     rect::Rect,
 };
 
-#[derive(Reflect, Serialize, Deserialize)]
+#[derive(Component, Reflect, Serialize, Deserialize)]
+#[component(script_name = "Text")]
 pub struct Text {
     pub content: String,
     pub font_size: f32,
@@ -68,7 +69,7 @@ pub(crate) struct TextData {
 
 pub fn init_text(
     ctx: SystemRunContext,
-    text_query: Query<(&Rect, &mut Text), Changed<Text>>,
+    text_query: Query<(&Rect, &mut Text), (Changed<Text>, IncludeDisabled)>,
     ui_context: ResMut<UiContext>,
 ) -> eyre::Result<()> {
     let UiContextInner {
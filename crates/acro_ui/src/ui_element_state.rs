@@ -1,22 +1,30 @@
-use acro_ecs::{Name, Query, Res, ResMut, SystemRunContext};
+use acro_ecs::{Component, Disabled, Name, Query, Res, ResMut, SystemRunContext, Time, With};
 use acro_render::WindowState;
 use tracing::info;
 use winit::event::MouseButton;
 
 use crate::rect::Rect;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Component)]
 pub struct UiElementState {
     pub is_hovered: bool,
     pub is_pressed: bool,
+    /// Seconds the element has been hovered for, zero when not hovered. Follows the real clock so
+    /// UI keeps animating while the game is paused.
+    pub hovered_for: f32,
+    /// Seconds the element has been pressed for, zero when not pressed.
+    pub pressed_for: f32,
 }
 
 pub fn poll_ui_element_state(
     ctx: SystemRunContext,
     rect_query: Query<(&Name, &Rect, &mut UiElementState)>,
+    disabled_query: Query<&mut UiElementState, With<Disabled>>,
     mut window_state: ResMut<WindowState>,
+    time: Res<Time>,
 ) {
     let mut ui_processed_click = false;
+    let delta = time.real_delta().as_secs_f32();
 
     for (name, rect, mut state) in rect_query.over(&ctx) {
         state.is_hovered = rect.contains(window_state.mouse_position);
@@ -25,10 +33,28 @@ pub fn poll_ui_element_state(
                 .mouse_buttons_pressed
                 .contains(&MouseButton::Left);
 
+        state.hovered_for = if state.is_hovered {
+            state.hovered_for + delta
+        } else {
+            0.0
+        };
+        state.pressed_for = if state.is_pressed {
+            state.pressed_for + delta
+        } else {
+            0.0
+        };
+
         if state.is_pressed {
             ui_processed_click = true;
         }
     }
 
     window_state.ui_processed_click = ui_processed_click;
+
+    // Disabled elements can't be hovered or pressed
+    for mut state in disabled_query.over(&ctx) {
+        if state.is_hovered || state.is_pressed {
+            *state = UiElementState::default();
+        }
+    }
 }
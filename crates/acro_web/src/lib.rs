@@ -1,13 +1,8 @@
 mod panic_hook;
 
-use acro_assets::AssetsPlugin;
+use acro_default_plugins::DefaultPlugins;
 use acro_ecs::{Application, Plugin};
-use acro_math::MathPlugin;
-use acro_physics::PhysicsPlugin;
-use acro_render::RenderPlugin;
-use acro_scene::{SceneManager, ScenePlugin};
-use acro_scripting::ScriptingPlugin;
-use acro_ui::UiPlugin;
+use acro_scene::SceneManager;
 use wasm_bindgen::prelude::*;
 
 struct TestPlugin;
@@ -18,6 +13,10 @@ impl Plugin for TestPlugin {
 
         world.resource_mut::<SceneManager>().queue("main.scene");
     }
+
+    fn dependencies(&self) -> Vec<&'static str> {
+        vec!["scene"]
+    }
 }
 
 #[wasm_bindgen]
@@ -33,13 +32,7 @@ pub fn run() {
     tracing::info!("starting application..");
 
     acro_ecs::Application::new()
-        .add_plugin(AssetsPlugin)
-        .add_plugin(ScriptingPlugin)
-        .add_plugin(MathPlugin::default())
-        .add_plugin(ScenePlugin)
-        .add_plugin(RenderPlugin)
-        .add_plugin(PhysicsPlugin)
-        .add_plugin(UiPlugin)
+        .add_plugins(DefaultPlugins)
         .add_plugin(TestPlugin)
         .run();
 }
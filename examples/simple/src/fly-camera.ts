@@ -29,11 +29,11 @@ class FlyCamera extends Behavior {
 
     if (Input.isKeyPressed("KeyA")) {
       this.transform.position.addAssign(
-        this.transform.right.scale(MOVE_SPEED * deltaTime)
+        this.transform.right.scale(-MOVE_SPEED * deltaTime)
       );
     } else if (Input.isKeyPressed("KeyD")) {
       this.transform.position.addAssign(
-        this.transform.right.scale(-MOVE_SPEED * deltaTime)
+        this.transform.right.scale(MOVE_SPEED * deltaTime)
       );
     }
 
@@ -1,4 +1,4 @@
-import { Transform, Vec3 } from "jsr:@acro/math";
+import { GlobalTransform, Transform, Vec3 } from "jsr:@acro/math";
 import { createGlobalOp } from "./ops.ts";
 
 export class Entity {
@@ -60,7 +60,15 @@ export class Behavior {
     return this.entity.getComponent(ComponentClass);
   }
 
+  // World-space position, rotation and axes, as of the last transform propagation
+  get globalTransform(): GlobalTransform | null {
+    return GlobalTransform.of(this.entity);
+  }
+
   update(_deltaTime: number) {}
+
+  // Called when the Behavior component is removed or its entity is despawned
+  destroy() {}
 }
 
 const getEntityByAbsolutePath = createGlobalOp<
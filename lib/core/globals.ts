@@ -17,8 +17,11 @@ export class AcroGlobalHook {
     this.behaviors = new Map();
   }
 
-  update(deltaTime: number) {
-    for (const behavior of this.behaviors.values()) {
+  update(deltaTime: number, disabledBehaviors: ArrayLike<number> = []) {
+    // Behaviors on disabled entities are kept alive, but not updated
+    const disabled = new Set(Array.from(disabledBehaviors));
+    for (const [id, behavior] of this.behaviors) {
+      if (disabled.has(id)) continue;
       behavior.update(deltaTime);
     }
   }
@@ -54,6 +57,11 @@ export class AcroGlobalHook {
     this.behaviors.set(id, behavior);
   }
 
+  destroyBehavior(id: number) {
+    this.behaviors.get(id)?.destroy();
+    this.behaviors.delete(id);
+  }
+
   registerComponents(components: Record<string, number>) {
     acro.COMPONENT_IDS = components;
   }
@@ -23,6 +23,13 @@ export const createBehavior = (
   acro.createBehavior(generation, index, behaviorId, behaviorName);
 };
 
-export const update = (deltaTime: number) => {
-  acro.update(deltaTime);
+export const destroyBehavior = (behaviorId: number) => {
+  acro.destroyBehavior(behaviorId);
+};
+
+export const update = (
+  deltaTime: number,
+  disabledBehaviors: ArrayLike<number> = []
+) => {
+  acro.update(deltaTime, disabledBehaviors);
 };
@@ -0,0 +1,76 @@
+import { createGlobalOp, type Entity } from "jsr:@acro/core";
+import { Quaternion } from "./quaternion.ts";
+import { type Vec3Like, Vec3 } from "./vec3.ts";
+
+type Vec3Tuple = [number, number, number];
+
+interface GlobalTransformData {
+  position: Vec3Tuple;
+  rotation: [number, number, number, number];
+  scale: Vec3Tuple;
+  forward: Vec3Tuple;
+  right: Vec3Tuple;
+  up: Vec3Tuple;
+}
+
+const getGlobalTransform = createGlobalOp<
+  [number, number],
+  GlobalTransformData | null
+>("op_get_global_transform");
+
+const globalTransformPoint = createGlobalOp<
+  [number, number, number, number, number, boolean],
+  Vec3Tuple | null
+>("op_global_transform_point");
+
+// A snapshot of where an entity is in the world, as of the last transform propagation. Changes made
+// to the `Transform` during `update` are not reflected yet.
+export class GlobalTransform {
+  constructor(
+    public entity: Entity,
+    public position: Vec3,
+    public rotation: Quaternion,
+    public scale: Vec3,
+    public forward: Vec3,
+    public right: Vec3,
+    public up: Vec3
+  ) {}
+
+  static of(entity: Entity): GlobalTransform | null {
+    const data = getGlobalTransform(entity.generation, entity.index);
+    if (!data) return null;
+
+    return new GlobalTransform(
+      entity,
+      new Vec3(...data.position),
+      new Quaternion(...data.rotation),
+      new Vec3(...data.scale),
+      new Vec3(...data.forward),
+      new Vec3(...data.right),
+      new Vec3(...data.up)
+    );
+  }
+
+  // Moves a point from the entity's local space into world space
+  transformPoint(point: Vec3Like): Vec3 {
+    return this.callTransformPoint(point, false);
+  }
+
+  // Moves a point from world space into the entity's local space
+  inverseTransformPoint(point: Vec3Like): Vec3 {
+    return this.callTransformPoint(point, true);
+  }
+
+  private callTransformPoint(point: Vec3Like, inverse: boolean): Vec3 {
+    const result = globalTransformPoint(
+      this.entity.generation,
+      this.entity.index,
+      point.x,
+      point.y,
+      point.z,
+      inverse
+    );
+    if (!result) throw new Error("entity has no invertible global transform");
+    return new Vec3(...result);
+  }
+}
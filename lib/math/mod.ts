@@ -1,3 +1,4 @@
+export * from "./global_transform.ts";
 export * from "./quaternion.ts";
 export * from "./transform.ts";
 export * from "./vec2.ts";
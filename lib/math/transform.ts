@@ -1,9 +1,15 @@
 import {
   type Attachment,
+  createGlobalOp,
   getPropertyVec3,
   setPropertyVec3,
 } from "jsr:@acro/core";
-import { Vec3 } from "jsr:@acro/math";
+import { type Vec3Like, Vec3 } from "jsr:@acro/math";
+
+const transformLookAt = createGlobalOp<
+  [number, number, number, number, number, number, number, number],
+  void
+>("op_transform_look_at");
 
 export class Transform {
   private _position: Vec3;
@@ -83,20 +89,45 @@ export class Transform {
     this._scale = value;
   }
 
+  // The axes match `GlobalTransform` in the engine: forward is -z, right is +x and up is +y,
+  // rotated by the euler angles (roll, pitch, yaw) applied in x, y, z order
   get forward() {
+    const { x, y, z } = this.rotation;
     return new Vec3(
-      Math.cos(-this.rotation.x) * Math.sin(-this.rotation.y),
-      -Math.sin(-this.rotation.x),
-      Math.cos(-this.rotation.x) * Math.cos(-this.rotation.y)
-    ).normalized;
+      -(Math.cos(x) * Math.sin(y) * Math.cos(z) + Math.sin(x) * Math.sin(z)),
+      -(Math.cos(x) * Math.sin(y) * Math.sin(z) - Math.sin(x) * Math.cos(z)),
+      -Math.cos(x) * Math.cos(y)
+    );
   }
 
   get right() {
-    return new Vec3(Math.cos(-this.rotation.y), 0, -Math.sin(-this.rotation.y))
-      .normalized;
+    const { y, z } = this.rotation;
+    return new Vec3(
+      Math.cos(y) * Math.cos(z),
+      Math.cos(y) * Math.sin(z),
+      -Math.sin(y)
+    );
   }
 
   get up() {
-    return this.forward.cross(this.right);
+    return this.right.cross(this.forward);
+  }
+
+  // Turns the entity so its forward axis (-z) points at `target`, which is in world space
+  lookAt(target: Vec3Like, up: Vec3Like = new Vec3(0, 1, 0)) {
+    if (!this.attachment)
+      throw new Error("lookAt is only supported on attached transforms");
+
+    const entity = this.attachment.entity;
+    transformLookAt(
+      entity.generation,
+      entity.index,
+      target.x,
+      target.y,
+      target.z,
+      up.x,
+      up.y,
+      up.z
+    );
   }
 }